@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+
+use crate::{symbol_name, Production, Symbol};
+
+// 以开始符号为起点的分析。左部不是单个非终结符时这些问题一般不可判定，所以只对2型、3型文法做
+#[derive(Debug, Clone)]
+pub struct StartAnalysis {
+    pub start_symbol: String,
+    // 文法中的全部非终结符，包括开始符号
+    pub non_terminals: BTreeSet<String>,
+    // 从开始符号可达的非终结符
+    pub reachable: BTreeSet<String>,
+    // 能推出终结符串的非终结符
+    pub productive: BTreeSet<String>,
+    // 删去无用符号后剩下的产生式，顺序不变
+    pub reduced: Vec<Production>,
+    // 含有无用符号而被删去的产生式
+    pub removed: Vec<Production>,
+    // 不出现在任何剩下的产生式中的符号
+    pub useless_symbols: BTreeSet<String>,
+    pub empty: bool,
+    pub finite: bool,
+}
+
+impl StartAnalysis {
+    pub fn unreachable(&self) -> Vec<&String> {
+        self.non_terminals.difference(&self.reachable).collect()
+    }
+
+    pub fn unproductive(&self) -> Vec<&String> {
+        self.non_terminals.difference(&self.productive).collect()
+    }
+}
+
+fn lhs_name((lhs_symbols, _): &Production) -> Option<&str> {
+    match lhs_symbols.as_slice() {
+        [Symbol::NonTerminal(name)] => Some(name),
+        _ => None,
+    }
+}
+
+fn rhs_non_terminals((_, rhs_symbols): &Production) -> impl Iterator<Item = &String> {
+    rhs_symbols.iter().filter_map(|symbol| match symbol {
+        Symbol::NonTerminal(name) => Some(name),
+        Symbol::Terminal(_) => None,
+    })
+}
+
+// 能推出终结符串的非终结符：右部的非终结符都能推出终结符串时，左部也能，直到不再变化
+pub fn productive_non_terminals(productions: &[Production]) -> BTreeSet<String> {
+    let mut productive = BTreeSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for production in productions {
+            let Some(name) = lhs_name(production) else { continue };
+            if !productive.contains(name) && rhs_non_terminals(production).all(|symbol| productive.contains(symbol)) {
+                productive.insert(name.to_string());
+                changed = true;
+            }
+        }
+    }
+    productive
+}
+
+// 从开始符号出发，经过产生式右部能到达的非终结符
+pub fn reachable_non_terminals(productions: &[Production], start_symbol: &str) -> BTreeSet<String> {
+    let mut reachable = BTreeSet::from([start_symbol.to_string()]);
+    let mut stack = vec![start_symbol.to_string()];
+    while let Some(name) = stack.pop() {
+        for production in productions.iter().filter(|production| lhs_name(production) == Some(&name)) {
+            for symbol in rhs_non_terminals(production) {
+                if reachable.insert(symbol.clone()) {
+                    stack.push(symbol.clone());
+                }
+            }
+        }
+    }
+    reachable
+}
+
+// 化简后的文法里，A -> α B β 给出一条从 A 到 B 的边，α β 能推出非空串时这条边是“增长”的。
+// 有一条增长的边在环上，就有 A =>+ u A v 且 uv 非空，语言无穷；反之每个句子都有一棵路径上
+// 不重复非终结符的语法树（只推出 ε 的那段环可以剪掉），语言有穷
+fn is_infinite(reduced: &[Production]) -> bool {
+    let mut non_empty: BTreeSet<&str> = BTreeSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for production in reduced {
+            let Some(name) = lhs_name(production) else { continue };
+            let (_, rhs_symbols) = production;
+            let derives_non_empty = rhs_symbols.iter().any(|symbol| match symbol {
+                Symbol::Terminal(_) => true,
+                Symbol::NonTerminal(name) => non_empty.contains(name.as_str()),
+            });
+            if derives_non_empty && non_empty.insert(name) {
+                changed = true;
+            }
+        }
+    }
+
+    let mut edges: Vec<(&str, &str, bool)> = Vec::new();
+    for production in reduced {
+        let Some(from) = lhs_name(production) else { continue };
+        let (_, rhs_symbols) = production;
+        for (index, symbol) in rhs_symbols.iter().enumerate() {
+            if let Symbol::NonTerminal(to) = symbol {
+                let growing = rhs_symbols.iter().enumerate().any(|(other, symbol)| {
+                    other != index && (matches!(symbol, Symbol::Terminal(_)) || non_empty.contains(symbol_name(symbol)))
+                });
+                edges.push((from, to, growing));
+            }
+        }
+    }
+
+    let leads_back = |from: &str, to: &str| {
+        let mut seen = BTreeSet::from([from]);
+        let mut stack = vec![from];
+        while let Some(current) = stack.pop() {
+            if current == to {
+                return true;
+            }
+            for &(_, next, _) in edges.iter().filter(|(source, _, _)| *source == current) {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        false
+    };
+    edges.iter().any(|&(from, to, growing)| growing && leads_back(to, from))
+}
+
+// 先删去推不出终结符串的非终结符，再删去从开始符号不可达的；顺序反过来会留下无用符号
+pub fn analyse(productions: &[Production], start_symbol: &str) -> StartAnalysis {
+    let mut non_terminals: BTreeSet<String> = productions.iter().filter_map(lhs_name).map(String::from).collect();
+    non_terminals.extend(productions.iter().flat_map(rhs_non_terminals).cloned());
+    non_terminals.insert(start_symbol.to_string());
+
+    let productive = productive_non_terminals(productions);
+    let generating: Vec<Production> = productions
+        .iter()
+        .filter(|production| {
+            lhs_name(production).is_some_and(|name| productive.contains(name))
+                && rhs_non_terminals(production).all(|symbol| productive.contains(symbol))
+        })
+        .cloned()
+        .collect();
+    let reachable_after = reachable_non_terminals(&generating, start_symbol);
+    let (reduced, removed): (Vec<Production>, Vec<Production>) = productions.iter().cloned().partition(|production| {
+        generating.contains(production) && lhs_name(production).is_some_and(|name| reachable_after.contains(name))
+    });
+
+    let names = |productions: &[Production]| -> BTreeSet<String> {
+        productions
+            .iter()
+            .flat_map(|(lhs_symbols, rhs_symbols)| lhs_symbols.iter().chain(rhs_symbols))
+            .map(|symbol| symbol_name(symbol).to_string())
+            .collect()
+    };
+    let mut all_symbols = names(productions);
+    all_symbols.insert(start_symbol.to_string());
+    let useless_symbols = all_symbols.difference(&names(&reduced)).cloned().collect();
+
+    let empty = !productive.contains(start_symbol);
+    StartAnalysis {
+        start_symbol: start_symbol.to_string(),
+        reachable: reachable_non_terminals(productions, start_symbol),
+        productive,
+        finite: empty || !is_infinite(&reduced),
+        non_terminals,
+        reduced,
+        removed,
+        useless_symbols,
+        empty,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::classify;
+
+    #[test]
+    fn test_start_analysis() {
+        let analysis = |productions: &[(&str, &str)]| classify(productions, "S").unwrap().analysis.unwrap();
+
+        // A is unproductive, so S -> A goes first and B, only reachable through it, goes after
+        let reduced = analysis(&[("S", "a"), ("S", "A B"), ("A", "a A"), ("B", "b")]);
+        assert_eq!(reduced.unproductive(), vec!["A"]);
+        assert!(reduced.unreachable().is_empty());
+        assert_eq!(reduced.useless_symbols, BTreeSet::from(["A".to_string(), "B".to_string(), "b".to_string()]));
+        assert_eq!(reduced.reduced.len(), 1);
+        assert!(!reduced.empty && reduced.finite);
+
+        assert!(analysis(&[("S", "a S")]).empty);
+        assert!(!analysis(&[("S", "a S"), ("S", "b")]).finite);
+        // Cycles through unit and ε productions only do not pump anything
+        assert!(analysis(&[("S", "A B"), ("A", "S"), ("A", "a"), ("B", "ε")]).finite);
+        assert!(!analysis(&[("S", "A B"), ("A", "S"), ("A", "a"), ("B", "b")]).finite);
+
+        // Not done for context-sensitive grammars
+        assert!(classify(&[("S", "a A"), ("a A", "a b")], "S").unwrap().analysis.is_none());
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use crate::linear::{shape, Shape};
+use crate::{symbol_name, Production, Symbol};
+
+// 非确定有限自动机，label 为 None 的边是 ε 边
+#[derive(Debug, Clone)]
+pub struct Nfa {
+    pub names: Vec<String>,
+    pub start: usize,
+    pub finals: BTreeSet<usize>,
+    pub transitions: Vec<(usize, Option<String>, usize)>,
+}
+
+// 确定有限自动机。没有出边的 (状态, 符号) 表示拒绝，死状态不画出来
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    pub names: Vec<String>,
+    pub start: usize,
+    pub finals: BTreeSet<usize>,
+    pub alphabet: BTreeSet<String>,
+    pub transitions: BTreeMap<(usize, String), usize>,
+}
+
+// 3型文法对应的各个自动机，以及由最小 DFA 反过来得到的右线性文法
+#[derive(Debug, Clone)]
+pub struct Automata {
+    pub nfa: Nfa,
+    pub dfa: Dfa,
+    pub minimal: Dfa,
+    pub grammar: Vec<Production>,
+    pub grammar_start: String,
+}
+
+struct NfaBuilder {
+    nfa: Nfa,
+    index: HashMap<String, usize>,
+}
+
+impl NfaBuilder {
+    // 非终结符对应的状态，同名只建一次
+    fn state(&mut self, name: &str) -> usize {
+        if let Some(&state) = self.index.get(name) {
+            return state;
+        }
+        let state = self.nfa.names.len();
+        self.nfa.names.push(name.to_string());
+        self.index.insert(name.to_string(), state);
+        state
+    }
+
+    // 新增的状态，名字后面加 ' 直到不和已有的重名
+    fn fresh(&mut self, base: &str) -> usize {
+        let mut name = base.to_string();
+        while self.index.contains_key(&name) {
+            name.push('\'');
+        }
+        self.state(&name)
+    }
+
+    // 从 from 读入终结符串 word 到达 to，中间状态按 from 的名字编号；空串是一条 ε 边
+    fn path(&mut self, from: usize, word: &[Symbol], to: usize) {
+        if word.is_empty() {
+            self.nfa.transitions.push((from, None, to));
+            return;
+        }
+        let mut current = from;
+        for (index, symbol) in word.iter().enumerate() {
+            let next = if index + 1 == word.len() {
+                to
+            } else {
+                let base = format!("{}{}", self.nfa.names[from], index + 1);
+                self.fresh(&base)
+            };
+            self.nfa.transitions.push((current, Some(symbol_name(symbol).to_string()), next));
+            current = next;
+        }
+    }
+}
+
+// 由（扩展）右线性或左线性文法构造 NFA，产生式必须已经通过3型文法的检查。
+// 右线性：每个非终结符一个状态，另加终态 F，A -> w B 是从 A 读 w 到 B，A -> w 是从 A 读 w 到 F。
+// 左线性：另加初态，开始符号是终态，A -> B w 是从 B 读 w 到 A，A -> w 是从初态读 w 到 A
+pub fn grammar_to_nfa(productions: &[Production], start_symbol: &str) -> Nfa {
+    let mut builder = NfaBuilder {
+        nfa: Nfa {
+            names: Vec::new(),
+            start: 0,
+            finals: BTreeSet::new(),
+            transitions: Vec::new(),
+        },
+        index: HashMap::new(),
+    };
+    builder.state(start_symbol);
+    for (lhs_symbols, rhs_symbols) in productions {
+        for symbol in lhs_symbols.iter().chain(rhs_symbols) {
+            if let Symbol::NonTerminal(name) = symbol {
+                builder.state(name);
+            }
+        }
+    }
+
+    let left_linear = productions.iter().any(|(_, rhs_symbols)| shape(rhs_symbols) == Shape::Left);
+    let (start, last) = if left_linear {
+        (builder.fresh("q0"), builder.state(start_symbol))
+    } else {
+        (builder.state(start_symbol), builder.fresh("F"))
+    };
+    builder.nfa.start = start;
+    builder.nfa.finals.insert(last);
+
+    for (lhs_symbols, rhs_symbols) in productions {
+        let lhs = builder.state(symbol_name(&lhs_symbols[0]));
+        match (left_linear, rhs_symbols.first(), rhs_symbols.last()) {
+            (false, _, Some(Symbol::NonTerminal(name))) => {
+                let to = builder.state(name);
+                builder.path(lhs, &rhs_symbols[..rhs_symbols.len() - 1], to);
+            }
+            (false, _, _) => builder.path(lhs, rhs_symbols, last),
+            (true, Some(Symbol::NonTerminal(name)), _) => {
+                let from = builder.state(name);
+                builder.path(from, &rhs_symbols[1..], lhs);
+            }
+            (true, _, _) => builder.path(start, rhs_symbols, lhs),
+        }
+    }
+    builder.nfa
+}
+
+fn epsilon_closure(nfa: &Nfa, states: BTreeSet<usize>) -> BTreeSet<usize> {
+    let mut closure = states.clone();
+    let mut stack: Vec<usize> = states.into_iter().collect();
+    while let Some(state) = stack.pop() {
+        for (_, _, to) in nfa.transitions.iter().filter(|(from, label, _)| *from == state && label.is_none()) {
+            if closure.insert(*to) {
+                stack.push(*to);
+            }
+        }
+    }
+    closure
+}
+
+// 子集构造。只生成从初态可达的状态集，按广度优先编号，初态为 0
+pub fn determinise(nfa: &Nfa) -> Dfa {
+    let alphabet: BTreeSet<String> = nfa.transitions.iter().filter_map(|(_, label, _)| label.clone()).collect();
+    let mut sets = vec![epsilon_closure(nfa, BTreeSet::from([nfa.start]))];
+    let mut index = BTreeMap::from([(sets[0].clone(), 0)]);
+    let mut transitions = BTreeMap::new();
+    let mut queue = VecDeque::from([0]);
+    while let Some(current) = queue.pop_front() {
+        for symbol in &alphabet {
+            let targets: BTreeSet<usize> = nfa
+                .transitions
+                .iter()
+                .filter(|(from, label, _)| sets[current].contains(from) && label.as_ref() == Some(symbol))
+                .map(|(_, _, to)| *to)
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            let target = epsilon_closure(nfa, targets);
+            let next = *index.entry(target.clone()).or_insert_with(|| {
+                sets.push(target);
+                queue.push_back(sets.len() - 1);
+                sets.len() - 1
+            });
+            transitions.insert((current, symbol.clone()), next);
+        }
+    }
+
+    let names = sets
+        .iter()
+        .map(|set| {
+            let names: Vec<&str> = set.iter().map(|&state| nfa.names[state].as_str()).collect();
+            format!("{{{}}}", names.join(", "))
+        })
+        .collect();
+    let finals = (0..sets.len()).filter(|&state| !sets[state].is_disjoint(&nfa.finals)).collect();
+    Dfa {
+        names,
+        start: 0,
+        finals,
+        alphabet,
+        transitions,
+    }
+}
+
+// Hopcroft 算法。缺少的边都指向一个隐含的死状态 n，先把终态与非终态分开，再用工作表里的块
+// 反复切分，每次只把切开的两半中较小的一半放回工作表。与死状态等价的状态最后一起删去，
+// 剩下的状态从初态开始按广度优先编号为 q0、q1 ……
+pub fn minimise(dfa: &Dfa) -> Dfa {
+    let dead = dfa.names.len();
+    let target = |state: usize, symbol: &String| {
+        if state == dead {
+            dead
+        } else {
+            dfa.transitions.get(&(state, symbol.clone())).copied().unwrap_or(dead)
+        }
+    };
+
+    let (finals, others): (BTreeSet<usize>, BTreeSet<usize>) = (0..=dead).partition(|state| dfa.finals.contains(state));
+    let mut partition: Vec<BTreeSet<usize>> = [finals, others].into_iter().filter(|block| !block.is_empty()).collect();
+    let mut worklist: Vec<BTreeSet<usize>> = partition.iter().min_by_key(|block| block.len()).cloned().into_iter().collect();
+    while let Some(splitter) = worklist.pop() {
+        for symbol in &dfa.alphabet {
+            let into: BTreeSet<usize> = (0..=dead).filter(|&state| splitter.contains(&target(state, symbol))).collect();
+            let mut refined = Vec::new();
+            for block in partition {
+                let inside: BTreeSet<usize> = block.intersection(&into).copied().collect();
+                let outside: BTreeSet<usize> = block.difference(&into).copied().collect();
+                if inside.is_empty() || outside.is_empty() {
+                    refined.push(block);
+                    continue;
+                }
+                if let Some(position) = worklist.iter().position(|waiting| *waiting == block) {
+                    worklist.swap_remove(position);
+                    worklist.push(inside.clone());
+                    worklist.push(outside.clone());
+                } else if inside.len() <= outside.len() {
+                    worklist.push(inside.clone());
+                } else {
+                    worklist.push(outside.clone());
+                }
+                refined.push(inside);
+                refined.push(outside);
+            }
+            partition = refined;
+        }
+    }
+
+    let mut block_of = vec![0; dead + 1];
+    for (block, states) in partition.iter().enumerate() {
+        for &state in states {
+            block_of[state] = block;
+        }
+    }
+    let dead_block = block_of[dead];
+
+    // 从初态所在的块开始重新编号，死块不要
+    let mut number = HashMap::from([(block_of[dfa.start], 0)]);
+    let mut order = vec![block_of[dfa.start]];
+    let mut transitions = BTreeMap::new();
+    let mut position = 0;
+    while position < order.len() {
+        let block = order[position];
+        let representative = *partition[block].iter().next().unwrap();
+        if block != dead_block {
+            for symbol in &dfa.alphabet {
+                let next = block_of[target(representative, symbol)];
+                if next == dead_block {
+                    continue;
+                }
+                let next_number = *number.entry(next).or_insert_with(|| {
+                    order.push(next);
+                    order.len() - 1
+                });
+                transitions.insert((position, symbol.clone()), next_number);
+            }
+        }
+        position += 1;
+    }
+
+    let finals = order
+        .iter()
+        .enumerate()
+        .filter(|(_, &block)| partition[block].iter().any(|state| dfa.finals.contains(state)))
+        .map(|(state, _)| state)
+        .collect();
+    Dfa {
+        names: (0..order.len()).map(|state| format!("q{}", state)).collect(),
+        start: 0,
+        finals,
+        alphabet: dfa.alphabet.clone(),
+        transitions,
+    }
+}
+
+// 每个状态 i 对应非终结符 Qi：i 读 a 到 j 给出 Qi -> a Qj，终态 i 给出 Qi -> ε。返回产生式与开始符号
+pub fn dfa_to_grammar(dfa: &Dfa) -> (Vec<Production>, String) {
+    let non_terminal = |state: usize| Symbol::NonTerminal(format!("Q{}", state));
+    let mut productions = Vec::new();
+    for state in 0..dfa.names.len() {
+        for ((_, symbol), &next) in dfa.transitions.range((state, String::new())..).take_while(|((from, _), _)| *from == state) {
+            productions.push((vec![non_terminal(state)], vec![Symbol::Terminal(symbol.clone()), non_terminal(next)]));
+        }
+        if dfa.finals.contains(&state) {
+            productions.push((vec![non_terminal(state)], Vec::new()));
+        }
+    }
+    (productions, format!("Q{}", dfa.start))
+}
+
+pub fn build_automata(productions: &[Production], start_symbol: &str) -> Automata {
+    let nfa = grammar_to_nfa(productions, start_symbol);
+    let dfa = determinise(&nfa);
+    let minimal = minimise(&dfa);
+    let (grammar, grammar_start) = dfa_to_grammar(&minimal);
+    Automata {
+        nfa,
+        dfa,
+        minimal,
+        grammar,
+        grammar_start,
+    }
+}
+
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// 状态图：初态由一个点指入，终态画成双圈，同一对状态间的边合并成一条
+fn to_dot(names: &[String], start: usize, finals: &BTreeSet<usize>, edges: Vec<(usize, String, usize)>) -> String {
+    let mut out = String::from("digraph {\n    rankdir=LR;\n    node [shape=circle];\n    start [shape=point];\n");
+    for (state, name) in names.iter().enumerate() {
+        let shape = if finals.contains(&state) { ", shape=doublecircle" } else { "" };
+        out.push_str(&format!("    n{} [label={}{}];\n", state, dot_string(name), shape));
+    }
+    out.push_str(&format!("    start -> n{};\n", start));
+
+    let mut labels: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+    for (from, label, to) in edges {
+        let labels = labels.entry((from, to)).or_default();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    for ((from, to), labels) in labels {
+        out.push_str(&format!("    n{} -> n{} [label={}];\n", from, to, dot_string(&labels.join(", "))));
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub fn nfa_to_dot(nfa: &Nfa) -> String {
+    let edges = nfa
+        .transitions
+        .iter()
+        .map(|(from, label, to)| (*from, label.clone().unwrap_or_else(|| "ε".to_string()), *to))
+        .collect();
+    to_dot(&nfa.names, nfa.start, &nfa.finals, edges)
+}
+
+pub fn dfa_to_dot(dfa: &Dfa) -> String {
+    let edges = dfa.transitions.iter().map(|((from, symbol), to)| (*from, symbol.clone(), *to)).collect();
+    to_dot(&dfa.names, dfa.start, &dfa.finals, edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::classify;
+    use crate::{classify_grammar, production_to_string, ChomskyLevel, LinearForm};
+
+    #[test]
+    fn test_automata() {
+        let accepts = |dfa: &Dfa, sentence: &str| {
+            let mut state = dfa.start;
+            for symbol in sentence.split_whitespace() {
+                match dfa.transitions.get(&(state, symbol.to_string())) {
+                    Some(&next) => state = next,
+                    None => return false,
+                }
+            }
+            dfa.finals.contains(&state)
+        };
+
+        // a b* c, with A and B doing the same thing
+        let right = classify(&[("S", "a A"), ("S", "a B"), ("A", "b A"), ("A", "c"), ("B", "b B"), ("B", "c")], "S").unwrap();
+        let automata = right.automata.unwrap();
+        assert_eq!(automata.nfa.names.len(), 4);
+        assert_eq!(automata.minimal.names.len(), 3);
+        for (sentence, accepted) in [("a c", true), ("a b b c", true), ("a", false), ("a b", false), ("b c", false)] {
+            assert_eq!(accepts(&automata.dfa, sentence), accepted, "{}", sentence);
+            assert_eq!(accepts(&automata.minimal, sentence), accepted, "{}", sentence);
+        }
+
+        // The same language from a left-linear grammar, with an intermediate state for "a b"
+        let left = classify(&[("S", "A c"), ("S", "a c"), ("A", "A b"), ("A", "a b")], "S").unwrap();
+        let minimal = left.automata.unwrap().minimal;
+        assert_eq!(minimal.names.len(), 3);
+        assert!(accepts(&minimal, "a b b c") && !accepts(&minimal, "a b"));
+
+        // Back to a grammar: right-linear and regular again
+        assert_eq!(
+            automata.grammar.iter().map(production_to_string).collect::<Vec<_>>(),
+            vec!["Q0 -> a Q1", "Q1 -> b Q1", "Q1 -> c Q2", "Q2 -> ε"]
+        );
+        let regenerated = classify_grammar(&automata.grammar, &automata.grammar_start).unwrap();
+        assert_eq!(regenerated.level, ChomskyLevel::Regular);
+        assert_eq!(regenerated.linear_form, Some(LinearForm::RightLinear));
+
+        assert!(dfa_to_dot(&automata.minimal).contains("    n2 [label=\"q2\", shape=doublecircle];\n"));
+        assert!(classify(&[("S", "a S b"), ("S", "c")], "S").unwrap().automata.is_none());
+    }
+}
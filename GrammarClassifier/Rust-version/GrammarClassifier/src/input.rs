@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+use std::io;
+
+// 读入的文法，符号还没有区分终结符与非终结符
+#[derive(Debug, Clone)]
+pub struct GrammarInput {
+    pub non_terminals: HashSet<String>,
+    pub terminals: HashSet<String>,
+    pub productions: Vec<(Vec<String>, Vec<String>)>,
+    pub start_symbol: String,
+}
+
+fn split_production(line: &str) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    let (lhs, rhs) = line.split_once("->")?;
+    let lhs: Vec<String> = lhs.split_whitespace().map(String::from).collect();
+    let alternatives = rhs
+        .split('|')
+        .map(|alternative| alternative.split_whitespace().map(String::from).collect())
+        .collect();
+    Some((lhs, alternatives))
+}
+
+// A grammar file in the format crosscheck reads for the LL(1) parsers, with a left-hand side of several symbols allowed
+// for type 0 and type 1 grammars:
+//
+//   # comment
+//   %nonterminals S A B
+//   %terminals a b c
+//   %start S
+//   S -> a S B c | a b c
+//   c B -> B c
+//   b B -> b b
+//
+// All three declarations are optional. Without %start the left-hand side of the first rule is the start
+// symbol. Undeclared symbols are sorted the textbook way, also when only one of the two sets is declared: a
+// symbol is a non-terminal when it is the whole left-hand side of a rule or starts with an upper-case letter,
+// otherwise a terminal. When both sets are declared, every symbol has to be in one of them.
+pub fn read_grammar(text: &str) -> Result<GrammarInput, String> {
+    let mut declared_non_terminals: Option<HashSet<String>> = None;
+    let mut declared_terminals: Option<HashSet<String>> = None;
+    let mut start_symbol = None;
+    let mut productions = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('%') {
+            let mut words = line.split_whitespace();
+            let directive = words.next().unwrap();
+            let symbols: HashSet<String> = words.map(String::from).collect();
+            match directive {
+                "%nonterminals" => declared_non_terminals.get_or_insert_with(HashSet::new).extend(symbols),
+                "%terminals" => declared_terminals.get_or_insert_with(HashSet::new).extend(symbols),
+                "%start" if symbols.len() == 1 => start_symbol = symbols.into_iter().next(),
+                "%start" => return Err(format!("line {}: %start takes exactly one symbol", number + 1)),
+                _ => return Err(format!("line {}: unknown directive {}", number + 1, directive)),
+            }
+            continue;
+        }
+
+        let (lhs, alternatives) =
+            split_production(line).ok_or_else(|| format!("line {}: expected LHS -> RHS", number + 1))?;
+        if lhs.is_empty() {
+            return Err(format!("line {}: the left-hand side is empty", number + 1));
+        }
+        for rhs in alternatives {
+            productions.push((lhs.clone(), rhs));
+        }
+    }
+
+    let first_lhs = productions.first().map(|(lhs, _)| lhs.clone()).ok_or("the grammar has no rules")?;
+    let start_symbol = match start_symbol {
+        Some(symbol) => symbol,
+        None if first_lhs.len() == 1 => first_lhs[0].clone(),
+        None => return Err("the first rule has more than one symbol on its left-hand side, declare the start symbol with %start".to_string()),
+    };
+
+    let (non_terminals, terminals) = match (declared_non_terminals, declared_terminals) {
+        (Some(non_terminals), Some(terminals)) => (non_terminals, terminals),
+        (non_terminals, terminals) => infer_symbols(&productions, non_terminals, terminals),
+    };
+    if let Some(symbol) = non_terminals.intersection(&terminals).next() {
+        return Err(format!("{} is declared both as a terminal and as a non-terminal", symbol));
+    }
+    if !non_terminals.contains(&start_symbol) {
+        return Err(format!("the start symbol {} is not a non-terminal", start_symbol));
+    }
+
+    Ok(GrammarInput {
+        non_terminals,
+        terminals,
+        productions,
+        start_symbol,
+    })
+}
+
+// Complete the sets that were not declared; see read_grammar
+fn infer_symbols(
+    productions: &[(Vec<String>, Vec<String>)],
+    non_terminals: Option<HashSet<String>>,
+    terminals: Option<HashSet<String>>,
+) -> (HashSet<String>, HashSet<String>) {
+    let symbols: HashSet<&String> = productions
+        .iter()
+        .flat_map(|(lhs, rhs)| lhs.iter().chain(rhs))
+        .filter(|symbol| symbol.as_str() != "ε")
+        .collect();
+    let looks_non_terminal = |symbol: &str| {
+        symbol.starts_with(|c: char| c.is_ascii_uppercase())
+            || productions.iter().any(|(lhs, _)| lhs.len() == 1 && lhs[0] == symbol)
+    };
+
+    // Declared symbols keep their declaration, the others are sorted by the textbook rule
+    let mut non_terminals = non_terminals.unwrap_or_default();
+    let mut terminals = terminals.unwrap_or_default();
+    for symbol in symbols {
+        if non_terminals.contains(symbol) || terminals.contains(symbol) {
+            continue;
+        }
+        if looks_non_terminal(symbol) {
+            non_terminals.insert(symbol.clone());
+        } else {
+            terminals.insert(symbol.clone());
+        }
+    }
+    (non_terminals, terminals)
+}
+
+fn read_symbols(prompt: &str) -> HashSet<String> {
+    eprint!("{} (用空格分隔): ", prompt);
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).unwrap();
+    line.split_whitespace()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn read_productions() -> Vec<(Vec<String>, Vec<String>)> {
+    let mut productions = Vec::new();
+    loop {
+        eprint!("输入产生式 (格式: LHS -> RHS，用空格分隔，输入空行结束): ");
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).unwrap();
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        match split_production(line) {
+            Some((lhs, alternatives)) if !lhs.is_empty() => {
+                for rhs in alternatives {
+                    productions.push((lhs.clone(), rhs));
+                }
+            }
+            _ => eprintln!("格式错误，请重新输入。"),
+        }
+    }
+    productions
+}
+
+// 交互式输入：依次读入非终结符、终结符、产生式和开始符号
+pub fn read_interactive() -> Result<GrammarInput, String> {
+    let non_terminals = read_symbols("输入非终结符");
+    let terminals = read_symbols("输入终结符");
+    let productions = read_productions();
+    eprint!("输入开始符号: ");
+    let mut start_symbol = String::new();
+    io::stdin().read_line(&mut start_symbol).unwrap();
+    let start_symbol = start_symbol.trim().to_string();
+    if !non_terminals.contains(&start_symbol) {
+        return Err(format!("Unknown start symbol: {}", start_symbol));
+    }
+    Ok(GrammarInput {
+        non_terminals,
+        terminals,
+        productions,
+        start_symbol,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(names: &str) -> HashSet<String> {
+        names.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_grammar_file() {
+        // Undeclared symbols: whole left-hand sides and upper-case names are non-terminals
+        let input = read_grammar("# a^n b^n c^n\nS -> a S B c | a b c\nc B -> B c\nb B -> b b\n").unwrap();
+        assert_eq!(input.start_symbol, "S");
+        assert_eq!(input.non_terminals, symbols("S B"));
+        assert_eq!(input.terminals, symbols("a b c"));
+
+        // With only one set declared the others still follow the textbook rule
+        let input = read_grammar("%terminals a\nS -> a B\nB -> x\n").unwrap();
+        assert_eq!(input.non_terminals, symbols("S B"));
+        assert_eq!(input.terminals, symbols("a x"));
+        let input = read_grammar("%nonterminals S b\nS -> a b\nb -> C\n").unwrap();
+        assert_eq!(input.non_terminals, symbols("S b C"));
+        assert_eq!(input.terminals, symbols("a"));
+
+        assert!(read_grammar("%start A\nS -> a").is_err());
+        assert!(read_grammar("a b -> c").is_err());
+    }
+}
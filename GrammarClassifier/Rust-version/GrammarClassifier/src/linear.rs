@@ -0,0 +1,159 @@
+use crate::{Production, Reason, Symbol, Violation};
+
+// 右部的形状，w、u、v 为终结符串，B 为非终结符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    // A -> w，包括 A -> ε
+    Terminals,
+    // A -> B
+    Unit,
+    // A -> w B，w 非空
+    Right,
+    // A -> B w，w 非空
+    Left,
+    // A -> u B v，u 和 v 都非空
+    Middle,
+    // 右部有不止一个非终结符
+    NonLinear,
+}
+
+pub fn shape(rhs_symbols: &[Symbol]) -> Shape {
+    let positions: Vec<usize> = rhs_symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| matches!(s, Symbol::NonTerminal(_)))
+        .map(|(index, _)| index)
+        .collect();
+    match positions.as_slice() {
+        [] => Shape::Terminals,
+        [_] if rhs_symbols.len() == 1 => Shape::Unit,
+        [index] if *index + 1 == rhs_symbols.len() => Shape::Right,
+        [0] => Shape::Left,
+        [_] => Shape::Middle,
+        _ => Shape::NonLinear,
+    }
+}
+
+// 线性文法的细分，越往前限制越严
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinearForm {
+    // A -> a B | a | ε
+    RightLinear,
+    // A -> B a | a | ε
+    LeftLinear,
+    // A -> w B | w
+    ExtendedRightLinear,
+    // A -> B w | w
+    ExtendedLeftLinear,
+    // A -> u B v | w，右部至多一个非终结符；左、右线性产生式混用的文法也只是线性文法
+    Linear,
+}
+
+// 最严的线性形式，左部不是单个非终结符或右部有两个以上非终结符的文法不是线性文法。
+// 只有 A -> w 产生式的文法既是右线性的也是左线性的，算作右线性文法
+pub fn linear_form(productions: &[Production]) -> Option<LinearForm> {
+    if !productions
+        .iter()
+        .all(|(lhs_symbols, _)| matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(_)]))
+    {
+        return None;
+    }
+    let shapes: Vec<(Shape, usize)> = productions.iter().map(|(_, rhs)| (shape(rhs), rhs.len())).collect();
+    let all = |allowed: &dyn Fn(Shape, usize) -> bool| shapes.iter().all(|&(shape, len)| allowed(shape, len));
+
+    if shapes.iter().any(|(shape, _)| *shape == Shape::NonLinear) {
+        return None;
+    }
+    let form = if all(&|shape, len| matches!(shape, Shape::Terminals) && len <= 1 || shape == Shape::Right && len == 2) {
+        LinearForm::RightLinear
+    } else if all(&|shape, len| matches!(shape, Shape::Terminals) && len <= 1 || shape == Shape::Left && len == 2) {
+        LinearForm::LeftLinear
+    } else if all(&|shape, _| matches!(shape, Shape::Terminals | Shape::Unit | Shape::Right)) {
+        LinearForm::ExtendedRightLinear
+    } else if all(&|shape, _| matches!(shape, Shape::Terminals | Shape::Unit | Shape::Left)) {
+        LinearForm::ExtendedLeftLinear
+    } else {
+        LinearForm::Linear
+    };
+    Some(form)
+}
+
+// 3型文法要么（扩展）右线性，要么（扩展）左线性。右部的非终结符不在两端、或者不止一个的产生式逐个
+// 报告；两种方向混用时，报告较少的那一方的产生式
+pub fn regular_violations(productions: &[Production]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut right = Vec::new();
+    let mut left = Vec::new();
+    for production in productions {
+        let (lhs_symbols, rhs_symbols) = production;
+        let reason = if !matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(_)]) {
+            Reason::LhsNotSingleNonTerminal
+        } else {
+            match shape(rhs_symbols) {
+                Shape::Terminals | Shape::Unit => continue,
+                Shape::Right => {
+                    right.push(production);
+                    continue;
+                }
+                Shape::Left => {
+                    left.push(production);
+                    continue;
+                }
+                Shape::Middle => Reason::NotLeftOrRightLinear,
+                Shape::NonLinear => Reason::NotLinear,
+            }
+        };
+        violations.push(Violation {
+            production: production.clone(),
+            reason,
+        });
+    }
+
+    if !right.is_empty() && !left.is_empty() {
+        let (minority, reason) = if left.len() <= right.len() {
+            (left, Reason::LeftLinearAmongRightLinear)
+        } else {
+            (right, Reason::RightLinearAmongLeftLinear)
+        };
+        for production in minority {
+            violations.push(Violation {
+                production: production.clone(),
+                reason: reason.clone(),
+            });
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::classify;
+    use crate::{production_to_string, ChomskyLevel};
+
+    #[test]
+    fn test_linear_forms() {
+        let form = |productions: &[(&str, &str)]| classify(productions, "S").unwrap().linear_form;
+        let level = |productions: &[(&str, &str)]| classify(productions, "S").unwrap().level;
+
+        assert_eq!(form(&[("S", "a A"), ("A", "b"), ("A", "ε")]), Some(LinearForm::RightLinear));
+        assert_eq!(form(&[("S", "A a"), ("A", "b")]), Some(LinearForm::LeftLinear));
+        assert_eq!(form(&[("S", "a b A"), ("A", "B"), ("B", "c")]), Some(LinearForm::ExtendedRightLinear));
+        assert_eq!(form(&[("S", "A a b"), ("A", "c")]), Some(LinearForm::ExtendedLeftLinear));
+        assert_eq!(form(&[("S", "a S b"), ("S", "c")]), Some(LinearForm::Linear));
+        assert_eq!(form(&[("S", "A B")]), None);
+
+        // Left-linear grammars are regular too
+        assert_eq!(level(&[("S", "A a"), ("A", "b")]), ChomskyLevel::Regular);
+        assert_eq!(level(&[("S", "A a b"), ("A", "c")]), ChomskyLevel::Regular);
+
+        // Mixing the two directions gives a linear grammar that is not regular
+        let mixed = classify(&[("S", "a A"), ("S", "b B"), ("A", "B c"), ("B", "a")], "S").unwrap();
+        assert_eq!(mixed.level, ChomskyLevel::ContextFree);
+        assert_eq!(mixed.linear_form, Some(LinearForm::Linear));
+        let regular = &mixed.checks[0];
+        assert_eq!(regular.violations.len(), 1);
+        assert_eq!(regular.violations[0].reason, Reason::LeftLinearAmongRightLinear);
+        assert_eq!(production_to_string(&regular.violations[0].production), "A -> B c");
+    }
+}
@@ -1,5 +1,16 @@
+mod analysis;
+mod automaton;
+mod linear;
+mod report;
+
 use std::collections::HashSet;
-use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use analysis::{analyse, StartAnalysis};
+use automaton::{build_automata, dfa_to_dot, nfa_to_dot, Automata};
+use grammar_classifier::input::{read_grammar, read_interactive, GrammarInput};
+use linear::{linear_form, regular_violations, LinearForm};
+use report::{escape_json, render_report, ReportFormat};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum Symbol {
@@ -9,157 +20,415 @@ enum Symbol {
 
 type Production = (Vec<Symbol>, Vec<Symbol>);
 
-fn parse_symbol(s: &str, non_terminals: &HashSet<String>, terminals: &HashSet<String>) -> Symbol {
+fn parse_symbol(s: &str, non_terminals: &HashSet<String>, terminals: &HashSet<String>) -> Result<Symbol, String> {
     if non_terminals.contains(s) {
-        Symbol::NonTerminal(s.to_string())
+        Ok(Symbol::NonTerminal(s.to_string()))
     } else if terminals.contains(s) {
-        Symbol::Terminal(s.to_string())
+        Ok(Symbol::Terminal(s.to_string()))
     } else {
-        panic!("Unknown symbol: {}", s)
+        Err(format!("Unknown symbol: {}", s))
     }
 }
 
+// ε on the right-hand side stands for the empty string and is not a symbol of the grammar
 fn parse_production(
-    (lhs, rhs): (Vec<String>, Vec<String>),
+    (lhs, rhs): &(Vec<String>, Vec<String>),
     non_terminals: &HashSet<String>,
     terminals: &HashSet<String>,
-) -> Production {
+) -> Result<Production, String> {
     let lhs_symbols = lhs
         .iter()
         .map(|s| parse_symbol(s, non_terminals, terminals))
-        .collect();
+        .collect::<Result<Vec<Symbol>, String>>();
     let rhs_symbols = rhs
         .iter()
+        .filter(|s| s.as_str() != "ε")
         .map(|s| parse_symbol(s, non_terminals, terminals))
-        .collect();
-    (lhs_symbols, rhs_symbols)
+        .collect::<Result<Vec<Symbol>, String>>();
+    match (lhs_symbols, rhs_symbols) {
+        (Ok(lhs_symbols), Ok(rhs_symbols)) => Ok((lhs_symbols, rhs_symbols)),
+        (Err(message), _) | (_, Err(message)) => Err(format!("{} -> {}: {}", lhs.join(" "), rhs.join(" "), message)),
+    }
 }
 
+// 所有产生式中的符号都必须是声明过的终结符或非终结符，第一个未知符号作为错误返回
 fn parse_productions(
-    productions: Vec<(Vec<String>, Vec<String>)>,
+    productions: &[(Vec<String>, Vec<String>)],
     non_terminals: &HashSet<String>,
     terminals: &HashSet<String>,
-) -> Vec<Production> {
+) -> Result<Vec<Production>, String> {
     productions
-        .into_iter()
+        .iter()
         .map(|production| parse_production(production, non_terminals, terminals))
         .collect()
 }
 
-fn is_linear(productions: &[Production], is_left: bool) -> bool {
-    productions.iter().all(|(lhs_symbols, rhs_symbols)| {
-        let rhs_terminals = rhs_symbols.iter().filter(|s| matches!(s, Symbol::Terminal(_))).count();
-        let rhs_non_terminals = rhs_symbols.iter().filter(|s| matches!(s, Symbol::NonTerminal(_))).count();
+fn symbol_name(symbol: &Symbol) -> &str {
+    match symbol {
+        Symbol::Terminal(name) | Symbol::NonTerminal(name) => name,
+    }
+}
 
-        matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(_)])
-            && (
-                (rhs_terminals == 1 && rhs_non_terminals == 0)  // Only one terminal symbol
-                || (rhs_terminals == 1 && rhs_non_terminals == 1 && (is_left && rhs_symbols.len() == 2 || !is_left && rhs_symbols.len() == 1))
-            )
-    })
+fn production_to_string((lhs_symbols, rhs_symbols): &Production) -> String {
+    let side = |symbols: &[Symbol]| {
+        if symbols.is_empty() {
+            "ε".to_string()
+        } else {
+            symbols.iter().map(symbol_name).collect::<Vec<&str>>().join(" ")
+        }
+    };
+    format!("{} -> {}", side(lhs_symbols), side(rhs_symbols))
 }
 
-fn is_regular_grammar(productions: &[Production]) -> bool {
-    productions.iter().all(|(lhs_symbols, rhs_symbols)| {
-        matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(_)])
-            && (matches!(rhs_symbols.as_slice(), [Symbol::Terminal(_)])
-                || matches!(
-                    rhs_symbols.as_slice(),
-                    [Symbol::Terminal(_), Symbol::NonTerminal(_)]
-                ))
-    })
+// 乔姆斯基文法等级，越往后限制越严
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ChomskyLevel {
+    // 0型文法（短语结构文法）：左部含有非终结符即可
+    Unrestricted,
+    // 1型文法（上下文有关文法）：右部不短于左部
+    ContextSensitive,
+    // 2型文法（上下文无关文法）：左部是单个非终结符
+    ContextFree,
+    // 3型文法（正则文法）
+    Regular,
 }
 
-fn is_type_2(productions: &[Production]) -> bool {
-    productions
-        .iter()
-        .all(|(lhs_symbols, _)| matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(_)]))
+impl ChomskyLevel {
+    fn number(self) -> usize {
+        match self {
+            ChomskyLevel::Unrestricted => 0,
+            ChomskyLevel::ContextSensitive => 1,
+            ChomskyLevel::ContextFree => 2,
+            ChomskyLevel::Regular => 3,
+        }
+    }
+}
+
+// 产生式不满足某一等级的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reason {
+    // 左部没有非终结符，连0型文法都不是
+    NoNonTerminalOnLhs,
+    // 右部比左部短
+    Contracting,
+    // ε 产生式只允许 S -> ε，且 S 是开始符号并且不出现在任何右部
+    EpsilonProduction,
+    // 左部不是单个非终结符
+    LhsNotSingleNonTerminal,
+    // 右部的非终结符既不在最左也不在最右
+    NotLeftOrRightLinear,
+    // 右部有不止一个非终结符
+    NotLinear,
+    // 左线性产生式，而文法中更多的是右线性产生式
+    LeftLinearAmongRightLinear,
+    // 右线性产生式，而文法中更多的是左线性产生式
+    RightLinearAmongLeftLinear,
+}
+
+// 不满足某一等级的产生式及原因
+#[derive(Debug, Clone)]
+struct Violation {
+    production: Production,
+    reason: Reason,
 }
 
-fn is_type_1(productions: &[Production]) -> bool {
-    productions.iter().all(|(lhs_symbols, rhs_symbols)| {
-        lhs_symbols.len() >= 1 && rhs_symbols.len() >= 1 && lhs_symbols.len() <= rhs_symbols.len()
+// 某一等级是否成立：没有违反它的产生式就成立
+#[derive(Debug, Clone)]
+struct LevelCheck {
+    level: ChomskyLevel,
+    violations: Vec<Violation>,
+}
+
+impl LevelCheck {
+    fn holds(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Classification {
+    // 所有产生式都满足的最严等级
+    level: ChomskyLevel,
+    // 3型到0型每个等级各自的检查结果；ε 产生式可以让2型成立而1型不成立
+    checks: Vec<LevelCheck>,
+    // 线性文法的细分，不是线性文法时为 None
+    linear_form: Option<LinearForm>,
+    // 以开始符号为起点的分析，只对2型、3型文法做
+    analysis: Option<StartAnalysis>,
+    // 3型文法的 NFA、DFA、最小 DFA 与由它得到的右线性文法
+    automata: Option<Automata>,
+}
+
+// Why the production is not of the given level, or None if it is. ε productions A -> ε are allowed in
+// type 2 and type 3 grammars, since removing them changes the language by ε at most; type 1 grammars are
+// non-contracting and only allow S -> ε for a start symbol S that appears on no right-hand side.
+fn check_production(
+    level: ChomskyLevel,
+    production: &Production,
+    start_symbol: &str,
+    start_on_rhs: bool,
+) -> Option<Reason> {
+    let (lhs_symbols, rhs_symbols) = production;
+    let single_non_terminal = matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(_)]);
+    match level {
+        ChomskyLevel::Unrestricted => {
+            if !lhs_symbols.iter().any(|s| matches!(s, Symbol::NonTerminal(_))) {
+                return Some(Reason::NoNonTerminalOnLhs);
+            }
+        }
+        ChomskyLevel::ContextSensitive => {
+            if rhs_symbols.is_empty() {
+                let is_start = matches!(lhs_symbols.as_slice(), [Symbol::NonTerminal(name)] if name == start_symbol);
+                if !is_start || start_on_rhs {
+                    return Some(Reason::EpsilonProduction);
+                }
+            } else if rhs_symbols.len() < lhs_symbols.len() {
+                return Some(Reason::Contracting);
+            }
+        }
+        ChomskyLevel::ContextFree => {
+            if !single_non_terminal {
+                return Some(Reason::LhsNotSingleNonTerminal);
+            }
+        }
+        // 3型文法要看所有产生式的方向是否一致，见 linear::regular_violations
+        ChomskyLevel::Regular => {}
+    }
+    None
+}
+
+// 左部不含非终结符的产生式使它根本不是文法，返回第一个这样的产生式；否则逐级检查3型到0型，
+// 文法属于从3型往下第一个成立的等级
+fn classify_grammar(parsed_productions: &[Production], start_symbol: &str) -> Result<Classification, Violation> {
+    let start_on_rhs = parsed_productions
+        .iter()
+        .any(|(_, rhs_symbols)| rhs_symbols.contains(&Symbol::NonTerminal(start_symbol.to_string())));
+
+    let checks: Vec<LevelCheck> = [
+        ChomskyLevel::Regular,
+        ChomskyLevel::ContextFree,
+        ChomskyLevel::ContextSensitive,
+        ChomskyLevel::Unrestricted,
+    ]
+    .into_iter()
+    .map(|level| LevelCheck {
+        level,
+        violations: if level == ChomskyLevel::Regular {
+            regular_violations(parsed_productions)
+        } else {
+            parsed_productions
+                .iter()
+                .filter_map(|production| {
+                    check_production(level, production, start_symbol, start_on_rhs).map(|reason| Violation {
+                        production: production.clone(),
+                        reason,
+                    })
+                })
+                .collect()
+        },
+    })
+    .collect();
+
+    if let Some(violation) = checks[3].violations.first() {
+        return Err(violation.clone());
+    }
+    let level = checks.iter().find(|check| check.holds()).map_or(ChomskyLevel::Unrestricted, |check| check.level);
+    Ok(Classification {
+        level,
+        checks,
+        linear_form: linear_form(parsed_productions),
+        analysis: (level >= ChomskyLevel::ContextFree).then(|| analyse(parsed_productions, start_symbol)),
+        automata: (level == ChomskyLevel::Regular).then(|| build_automata(parsed_productions, start_symbol)),
     })
 }
 
-fn classify_grammar(
-    non_terminals: &HashSet<String>,
-    terminals: &HashSet<String>,
-    productions: Vec<(Vec<String>, Vec<String>)>,
-) -> String {
-    let parsed_productions = parse_productions(productions, non_terminals, terminals);
-    if is_regular_grammar(&parsed_productions) {
-        "正则文法".to_string()
-    } else if is_linear(&parsed_productions, false) {
-        "右线性文法".to_string()
-    } else if is_linear(&parsed_productions, true) {
-        "左线性文法".to_string()
-    } else if is_type_2(&parsed_productions) {
-        "上下文无关文法".to_string()
-    } else if is_type_1(&parsed_productions) {
-        "上下文有关文法".to_string()
-    } else {
-        "未知文法".to_string()
+struct Options {
+    format: ReportFormat,
+    // Where the state diagrams of regular grammars go, if anywhere
+    dot_directory: Option<PathBuf>,
+}
+
+// The state diagrams of a regular grammar as <name>.nfa.dot, <name>.dfa.dot and <name>.min.dot
+fn write_diagrams(automata: &Automata, directory: &Path, name: &str) -> Result<(), String> {
+    let diagrams = [
+        ("nfa", nfa_to_dot(&automata.nfa)),
+        ("dfa", dfa_to_dot(&automata.dfa)),
+        ("min", dfa_to_dot(&automata.minimal)),
+    ];
+    for (suffix, dot) in diagrams {
+        let path = directory.join(format!("{}.{}.dot", name, suffix));
+        std::fs::write(&path, dot).map_err(|err| format!("{}: {}", path.display(), err))?;
     }
+    Ok(())
 }
 
-fn read_symbols(prompt: &str) -> HashSet<String> {
-    print!("{} (用空格分隔): ", prompt);
-    io::stdout().flush().unwrap();
-    let mut line = String::new();
-    io::stdin().read_line(&mut line).unwrap();
-    line.trim()
-        .split_whitespace()
-        .map(|s| s.to_string())
-        .collect()
+// The report for one grammar; unknown symbols are an error of the input, not a classification
+fn report_grammar(input: &GrammarInput, name: &str, options: &Options) -> Result<String, String> {
+    let productions = parse_productions(&input.productions, &input.non_terminals, &input.terminals)?;
+    let result = classify_grammar(&productions, &input.start_symbol);
+    if let (Ok(Classification { automata: Some(automata), .. }), Some(directory)) = (&result, &options.dot_directory) {
+        write_diagrams(automata, directory, name)?;
+    }
+    Ok(render_report(&result, options.format))
+}
+
+fn report_file(path: &Path, options: &Options) -> Result<String, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let name = path.file_stem().map_or("grammar".into(), |stem| stem.to_string_lossy());
+    report_grammar(&read_grammar(&text)?, &name, options)
 }
 
-fn read_productions() -> Vec<(Vec<String>, Vec<String>)> {
-    let mut productions = Vec::new();
-    loop {
-        print!("输入产生式 (格式: LHS -> RHS，用空格分隔，输入空行结束): ");
-        io::stdout().flush().unwrap();
-        let mut line = String::new();
-        io::stdin().read_line(&mut line).unwrap();
-        let line = line.trim();
-        if line.is_empty() {
-            break;
+// Every file of the directory in name order; a grammar that cannot be read is reported and skipped.
+// Returns whether all of them could be classified.
+fn report_directory(directory: &Path, options: &Options) -> Result<bool, String> {
+    let format = options.format;
+    let mut paths: Vec<_> = std::fs::read_dir(directory)
+        .map_err(|err| err.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    let mut all_ok = true;
+    let mut json_entries = Vec::new();
+    for path in &paths {
+        let result = report_file(path, options);
+        all_ok &= result.is_ok();
+        let name = path.display().to_string();
+        match (format, result) {
+            (ReportFormat::Json, Ok(report)) => {
+                json_entries.push(format!("{{\"file\": \"{}\", \"report\": {}}}", escape_json(&name), report.trim_end()))
+            }
+            (ReportFormat::Json, Err(message)) => json_entries.push(format!(
+                "{{\"file\": \"{}\", \"input_error\": \"{}\"}}",
+                escape_json(&name),
+                escape_json(&message)
+            )),
+            (_, Ok(report)) => println!("== {} ==\n{}", name, report),
+            (_, Err(message)) => println!("== {} ==\n{}\n", name, message),
+        }
+    }
+    if format == ReportFormat::Json {
+        println!("[\n{}\n]", json_entries.join(",\n"));
+    }
+    Ok(all_ok)
+}
+
+fn main() {
+    // GrammarClassifier [zh|en|json] [--dot directory] [grammar file or directory]
+    // The report is in Chinese by default. Without a path the grammar is read interactively, with the
+    // prompts on stderr so that the report can be redirected on its own. With --dot the state diagrams of
+    // every regular grammar are written to the directory.
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let dot_directory = match args.iter().position(|arg| arg == "--dot") {
+        Some(position) if position + 1 < args.len() => {
+            args.remove(position);
+            Some(PathBuf::from(args.remove(position)))
+        }
+        Some(_) => {
+            eprintln!("Error: --dot needs a directory");
+            std::process::exit(1);
         }
-        let parts: Vec<&str> = line.split("->").collect();
-        if parts.len() != 2 {
-            println!("格式错误，请重新输入。");
-            continue;
+        None => None,
+    };
+    let format = match args.first().and_then(|name| ReportFormat::from_name(name)) {
+        Some(format) => {
+            args.remove(0);
+            format
         }
-        let lhs: Vec<String> = parts[0]
-            .trim()
-            .split_whitespace()
-            .map(String::from)
+        None => ReportFormat::Chinese,
+    };
+    let options = Options { format, dot_directory };
+
+    let result = match args.first() {
+        Some(path) if Path::new(path).is_dir() => report_directory(Path::new(path), &options),
+        Some(path) => report_file(Path::new(path), &options).map(|report| {
+            print!("{}", report);
+            true
+        }),
+        None => read_interactive().and_then(|input| report_grammar(&input, "grammar", &options)).map(|report| {
+            print!("{}", report);
+            true
+        }),
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(names: &str) -> HashSet<String> {
+        names.split_whitespace().map(String::from).collect()
+    }
+
+    pub(crate) fn classify(productions: &[(&str, &str)], start_symbol: &str) -> Result<Classification, Violation> {
+        let productions: Vec<(Vec<String>, Vec<String>)> = productions
+            .iter()
+            .map(|(lhs, rhs)| {
+                (
+                    lhs.split_whitespace().map(String::from).collect(),
+                    rhs.split_whitespace().map(String::from).collect(),
+                )
+            })
             .collect();
-        let rhs: Vec<String> = parts[1]
-            .trim()
-            .split_whitespace()
-            .map(String::from)
+        let productions = parse_productions(&productions, &symbols("S A B"), &symbols("a b c")).unwrap();
+        classify_grammar(&productions, start_symbol)
+    }
+
+    #[test]
+    fn test_epsilon_and_type_0() {
+        // S -> ε is allowed in a type 1 grammar as long as S is on no right-hand side
+        let csg = classify(&[("S", "ε"), ("S", "a A"), ("a A", "a b c")], "S").unwrap();
+        assert_eq!(csg.level, ChomskyLevel::ContextSensitive);
+        let csg = classify(&[("S", "ε"), ("S", "a S"), ("a A", "a b c")], "S").unwrap();
+        assert_eq!(csg.level, ChomskyLevel::Unrestricted);
+        assert_eq!(csg.checks[2].violations[0].reason, Reason::EpsilonProduction);
+
+        // A contracting production makes the grammar type 0, not "unknown"
+        let unrestricted = classify(&[("S", "A B"), ("A B", "a")], "S").unwrap();
+        assert_eq!(unrestricted.level, ChomskyLevel::Unrestricted);
+        let reasons: Vec<(ChomskyLevel, Reason)> = unrestricted
+            .checks
+            .iter()
+            .flat_map(|check| check.violations.iter().map(|violation| (check.level, violation.reason.clone())))
             .collect();
-        productions.push((lhs, rhs));
+        assert_eq!(
+            reasons,
+            vec![
+                (ChomskyLevel::Regular, Reason::NotLinear),
+                (ChomskyLevel::Regular, Reason::LhsNotSingleNonTerminal),
+                (ChomskyLevel::ContextFree, Reason::LhsNotSingleNonTerminal),
+                (ChomskyLevel::ContextSensitive, Reason::Contracting),
+            ]
+        );
+
+        // ε productions of other non-terminals keep a grammar context-free and regular
+        assert_eq!(classify(&[("S", "a S"), ("S", "ε")], "S").unwrap().level, ChomskyLevel::Regular);
+        assert_eq!(classify(&[("S", "a S b"), ("S", "ε")], "S").unwrap().level, ChomskyLevel::ContextFree);
+
+        assert!(classify(&[("a", "b")], "S").is_err());
     }
-    productions
-}
 
-fn main() {
-    let non_terminals = read_symbols("输入非终结符");
-    let terminals = read_symbols("输入终结符");
-    let productions = read_productions();
-    print!("输入开始符号: ");
-    io::stdout().flush().unwrap();
-    let mut start_symbol_input = String::new();
-    io::stdin().read_line(&mut start_symbol_input).unwrap();
-    let start_symbol_input = start_symbol_input.trim();
-    let start_symbol = non_terminals
-        .iter()
-        .find(|nt| nt == &&start_symbol_input)
-        .cloned()
-        .expect(&format!("Unknown start symbol: {}", start_symbol_input));
+    #[test]
+    fn test_grammar_file() {
+        let options = Options {
+            format: ReportFormat::English,
+            dot_directory: None,
+        };
+        let input = read_grammar("# a^n b^n c^n\nS -> a S B c | a b c\nc B -> B c\nb B -> b b\n").unwrap();
+        let report = report_grammar(&input, "grammar", &options).unwrap();
+        assert!(report.starts_with("Grammar type: type 1 (context-sensitive)\n"));
 
-    let grammar_type = classify_grammar(&non_terminals, &terminals, productions);
-    println!("文法类型: {}", grammar_type);
+        // Declared sets are taken as they are, so a symbol in neither of them is an error
+        let input = read_grammar("%nonterminals S\n%terminals a\nS -> a S | b\n").unwrap();
+        assert_eq!(report_grammar(&input, "grammar", &options), Err("S -> b: Unknown symbol: b".to_string()));
+    }
 }
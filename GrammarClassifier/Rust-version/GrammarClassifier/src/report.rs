@@ -0,0 +1,345 @@
+use crate::analysis::StartAnalysis;
+use crate::automaton::Automata;
+use crate::{production_to_string, symbol_name, ChomskyLevel, Classification, LinearForm, Reason, Symbol, Violation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Chinese,
+    English,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Option<ReportFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "zh" | "chinese" | "中文" => Some(ReportFormat::Chinese),
+            "en" | "english" => Some(ReportFormat::English),
+            "json" => Some(ReportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+fn level_name(level: ChomskyLevel, format: ReportFormat) -> String {
+    let number = level.number();
+    match format {
+        ReportFormat::Chinese => {
+            let name = match level {
+                ChomskyLevel::Unrestricted => "短语结构文法",
+                ChomskyLevel::ContextSensitive => "上下文有关文法",
+                ChomskyLevel::ContextFree => "上下文无关文法",
+                ChomskyLevel::Regular => "正则文法",
+            };
+            format!("{}型文法（{}）", number, name)
+        }
+        ReportFormat::English | ReportFormat::Json => {
+            let name = match level {
+                ChomskyLevel::Unrestricted => "unrestricted",
+                ChomskyLevel::ContextSensitive => "context-sensitive",
+                ChomskyLevel::ContextFree => "context-free",
+                ChomskyLevel::Regular => "regular",
+            };
+            if format == ReportFormat::Json {
+                name.to_string()
+            } else {
+                format!("type {} ({})", number, name)
+            }
+        }
+    }
+}
+
+fn reason_text(reason: &Reason, format: ReportFormat) -> &'static str {
+    match format {
+        ReportFormat::Chinese => match reason {
+            Reason::NoNonTerminalOnLhs => "左部不含非终结符",
+            Reason::Contracting => "右部比左部短",
+            Reason::EpsilonProduction => "只有不出现在任何右部的开始符号才能推出 ε",
+            Reason::LhsNotSingleNonTerminal => "左部不止一个符号",
+            Reason::NotLeftOrRightLinear => "右部的非终结符不在两端",
+            Reason::NotLinear => "右部有不止一个非终结符",
+            Reason::LeftLinearAmongRightLinear => "左线性产生式与右线性产生式混用",
+            Reason::RightLinearAmongLeftLinear => "右线性产生式与左线性产生式混用",
+        },
+        ReportFormat::English | ReportFormat::Json => match reason {
+            Reason::NoNonTerminalOnLhs => "LHS has no non-terminal",
+            Reason::Contracting => "RHS is shorter than LHS",
+            Reason::EpsilonProduction => "only a start symbol that appears on no RHS may derive ε",
+            Reason::LhsNotSingleNonTerminal => "LHS has more than one symbol",
+            Reason::NotLeftOrRightLinear => "the non-terminal is at neither end of the RHS",
+            Reason::NotLinear => "more than one non-terminal on the RHS",
+            Reason::LeftLinearAmongRightLinear => "left-linear, while the other productions are right-linear",
+            Reason::RightLinearAmongLeftLinear => "right-linear, while the other productions are left-linear",
+        },
+    }
+}
+
+fn linear_form_name(form: Option<LinearForm>, format: ReportFormat) -> &'static str {
+    match format {
+        ReportFormat::Chinese => match form {
+            Some(LinearForm::RightLinear) => "右线性文法",
+            Some(LinearForm::LeftLinear) => "左线性文法",
+            Some(LinearForm::ExtendedRightLinear) => "扩展右线性文法",
+            Some(LinearForm::ExtendedLeftLinear) => "扩展左线性文法",
+            Some(LinearForm::Linear) => "线性文法",
+            None => "非线性文法",
+        },
+        ReportFormat::English => match form {
+            Some(LinearForm::RightLinear) => "right-linear",
+            Some(LinearForm::LeftLinear) => "left-linear",
+            Some(LinearForm::ExtendedRightLinear) => "extended right-linear",
+            Some(LinearForm::ExtendedLeftLinear) => "extended left-linear",
+            Some(LinearForm::Linear) => "linear",
+            None => "not linear",
+        },
+        ReportFormat::Json => match form {
+            Some(LinearForm::RightLinear) => "\"right_linear\"",
+            Some(LinearForm::LeftLinear) => "\"left_linear\"",
+            Some(LinearForm::ExtendedRightLinear) => "\"extended_right_linear\"",
+            Some(LinearForm::ExtendedLeftLinear) => "\"extended_left_linear\"",
+            Some(LinearForm::Linear) => "\"linear\"",
+            None => "null",
+        },
+    }
+}
+
+// Stable identifier of the reason for JSON consumers
+fn reason_code(reason: &Reason) -> &'static str {
+    match reason {
+        Reason::NoNonTerminalOnLhs => "no_non_terminal_on_lhs",
+        Reason::Contracting => "contracting",
+        Reason::EpsilonProduction => "epsilon_production",
+        Reason::LhsNotSingleNonTerminal => "lhs_not_single_non_terminal",
+        Reason::NotLeftOrRightLinear => "not_left_or_right_linear",
+        Reason::NotLinear => "not_linear",
+        Reason::LeftLinearAmongRightLinear => "left_linear_among_right_linear",
+        Reason::RightLinearAmongLeftLinear => "right_linear_among_left_linear",
+    }
+}
+
+pub fn escape_json(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn json_symbols(symbols: &[Symbol]) -> String {
+    let symbols: Vec<String> = symbols.iter().map(|s| format!("\"{}\"", escape_json(symbol_name(s)))).collect();
+    format!("[{}]", symbols.join(", "))
+}
+
+fn json_violation(violation: &Violation) -> String {
+    let (lhs_symbols, rhs_symbols) = &violation.production;
+    format!(
+        "{{\"production\": \"{}\", \"lhs\": {}, \"rhs\": {}, \"reason\": \"{}\", \"message\": \"{}\"}}",
+        escape_json(&production_to_string(&violation.production)),
+        json_symbols(lhs_symbols),
+        json_symbols(rhs_symbols),
+        reason_code(&violation.reason),
+        escape_json(reason_text(&violation.reason, ReportFormat::Json))
+    )
+}
+
+fn json_names<'a>(names: impl IntoIterator<Item = &'a String>) -> String {
+    let names: Vec<String> = names.into_iter().map(|name| format!("\"{}\"", escape_json(name))).collect();
+    format!("[{}]", names.join(", "))
+}
+
+fn json_analysis(analysis: &Option<StartAnalysis>) -> String {
+    let Some(analysis) = analysis else {
+        return "null".to_string();
+    };
+    let productions = |productions: &[crate::Production]| -> String {
+        let productions: Vec<String> = productions.iter().map(production_to_string).collect();
+        json_names(&productions)
+    };
+    format!(
+        "{{\"start_symbol\": \"{}\", \"unreachable\": {}, \"unproductive\": {}, \"useless_symbols\": {}, \"removed_productions\": {}, \"reduced_productions\": {}, \"empty\": {}, \"finite\": {}}}",
+        escape_json(&analysis.start_symbol),
+        json_names(analysis.unreachable()),
+        json_names(analysis.unproductive()),
+        json_names(&analysis.useless_symbols),
+        productions(&analysis.removed),
+        productions(&analysis.reduced),
+        analysis.empty,
+        analysis.finite
+    )
+}
+
+fn json_automata(automata: &Option<Automata>) -> String {
+    let Some(automata) = automata else {
+        return "null".to_string();
+    };
+    let grammar: Vec<String> = automata.grammar.iter().map(production_to_string).collect();
+    format!(
+        "{{\"nfa_states\": {}, \"dfa_states\": {}, \"minimal_dfa_states\": {}, \"right_linear_start\": \"{}\", \"right_linear_grammar\": {}}}",
+        automata.nfa.names.len(),
+        automata.dfa.names.len(),
+        automata.minimal.names.len(),
+        escape_json(&automata.grammar_start),
+        json_names(&grammar)
+    )
+}
+
+// 各个自动机的规模，以及由最小 DFA 得到的右线性文法
+fn automata_text(automata: &Automata, format: ReportFormat) -> String {
+    let (nfa, dfa, minimal) = (automata.nfa.names.len(), automata.dfa.names.len(), automata.minimal.names.len());
+    let mut out = if format == ReportFormat::Chinese {
+        format!(
+            "有限自动机: NFA {} 个状态，DFA {} 个状态，最小 DFA {} 个状态\n最小 DFA 对应的右线性文法（开始符号 {}）:\n",
+            nfa, dfa, minimal, automata.grammar_start
+        )
+    } else {
+        format!(
+            "Finite automata: NFA with {} states, DFA with {}, minimal DFA with {}\nRight-linear grammar of the minimal DFA (start symbol {}):\n",
+            nfa, dfa, minimal, automata.grammar_start
+        )
+    };
+    for production in &automata.grammar {
+        out.push_str(&format!("  {}\n", production_to_string(production)));
+    }
+    out
+}
+
+// 开始符号、无用符号与语言是否为空、是否有穷
+fn analysis_text(analysis: &Option<StartAnalysis>, format: ReportFormat) -> String {
+    let chinese = format == ReportFormat::Chinese;
+    let Some(analysis) = analysis else {
+        return if chinese {
+            "开始符号分析: 只适用于2型、3型文法\n".to_string()
+        } else {
+            "Start symbol analyses: only for type 2 and type 3 grammars\n".to_string()
+        };
+    };
+    let list = |names: Vec<&String>| {
+        if names.is_empty() {
+            if chinese { "无" } else { "none" }.to_string()
+        } else {
+            names.iter().map(|name| name.as_str()).collect::<Vec<&str>>().join(" ")
+        }
+    };
+    let (titles, language) = if chinese {
+        (
+            ["开始符号", "不可达的非终结符", "推不出终结符串的非终结符", "无用符号", "删去的产生式", "语言"],
+            match (analysis.empty, analysis.finite) {
+                (true, _) => "空",
+                (false, true) => "非空，有穷",
+                (false, false) => "非空，无穷",
+            },
+        )
+    } else {
+        (
+            ["Start symbol", "Unreachable non-terminals", "Unproductive non-terminals", "Useless symbols", "Removed productions", "Language"],
+            match (analysis.empty, analysis.finite) {
+                (true, _) => "empty",
+                (false, true) => "non-empty, finite",
+                (false, false) => "non-empty, infinite",
+            },
+        )
+    };
+
+    let mut out = format!("{}: {}\n", titles[0], analysis.start_symbol);
+    out.push_str(&format!("{}: {}\n", titles[1], list(analysis.unreachable())));
+    out.push_str(&format!("{}: {}\n", titles[2], list(analysis.unproductive())));
+    out.push_str(&format!("{}: {}\n", titles[3], list(analysis.useless_symbols.iter().collect())));
+    out.push_str(&format!("{}: {}\n", titles[4], analysis.removed.len()));
+    for production in &analysis.removed {
+        out.push_str(&format!("  {}\n", production_to_string(production)));
+    }
+    out.push_str(&format!("{}: {}\n", titles[5], language));
+    out
+}
+
+fn violation_line(violation: &Violation, format: ReportFormat) -> String {
+    format!("  {}: {}\n", production_to_string(&violation.production), reason_text(&violation.reason, format))
+}
+
+// The level of the grammar, then every level from 3 down to 0 with the productions that break it, then the
+// start symbol analyses and, for a regular grammar, its finite automata.
+// A production whose LHS has no non-terminal makes the input no grammar at all, which is reported instead.
+pub fn render_report(result: &Result<Classification, Violation>, format: ReportFormat) -> String {
+    let classification = match result {
+        Ok(classification) => classification,
+        Err(violation) => {
+            return match format {
+                ReportFormat::Chinese => format!("不是文法:\n{}", violation_line(violation, format)),
+                ReportFormat::English => format!("Not a grammar:\n{}", violation_line(violation, format)),
+                ReportFormat::Json => format!("{{\"error\": {}}}\n", json_violation(violation)),
+            };
+        }
+    };
+
+    if format == ReportFormat::Json {
+        let checks: Vec<String> = classification
+            .checks
+            .iter()
+            .map(|check| {
+                let violations: Vec<String> = check.violations.iter().map(json_violation).collect();
+                format!(
+                    "    {{\"level\": {}, \"name\": \"{}\", \"holds\": {}, \"violations\": [{}]}}",
+                    check.level.number(),
+                    level_name(check.level, format),
+                    check.holds(),
+                    violations.join(", ")
+                )
+            })
+            .collect();
+        return format!(
+            "{{\n  \"level\": {},\n  \"name\": \"{}\",\n  \"linear_form\": {},\n  \"checks\": [\n{}\n  ],\n  \"analysis\": {},\n  \"automata\": {}\n}}\n",
+            classification.level.number(),
+            level_name(classification.level, format),
+            linear_form_name(classification.linear_form, format),
+            checks.join(",\n"),
+            json_analysis(&classification.analysis),
+            json_automata(&classification.automata)
+        );
+    }
+
+    let (title, linear_title, holds, fails) = match format {
+        ReportFormat::Chinese => ("文法类型", "线性形式", "成立", "不成立"),
+        _ => ("Grammar type", "Linear form", "holds", "does not hold"),
+    };
+    let mut out = format!("{}: {}\n", title, level_name(classification.level, format));
+    out.push_str(&format!("{}: {}\n", linear_title, linear_form_name(classification.linear_form, format)));
+    for check in &classification.checks {
+        let verdict = if check.holds() { holds } else { fails };
+        out.push_str(&format!("{}: {}\n", level_name(check.level, format), verdict));
+        for violation in &check.violations {
+            out.push_str(&violation_line(violation, format));
+        }
+    }
+    out.push_str(&analysis_text(&classification.analysis, format));
+    if let Some(automata) = &classification.automata {
+        out.push_str(&automata_text(automata, format));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::classify;
+
+    #[test]
+    fn test_report() {
+        let result = classify(&[("S", "a S b"), ("S", "A"), ("A", "ε")], "S");
+        let english = render_report(&result, ReportFormat::English);
+        assert!(english.starts_with("Grammar type: type 2 (context-free)\n"));
+        assert!(english.contains("Linear form: linear\n"));
+        assert!(english.contains("type 3 (regular): does not hold\n  S -> a S b: the non-terminal is at neither end of the RHS\n"));
+        assert!(english.contains("  A -> ε: only a start symbol that appears on no RHS may derive ε\n"));
+
+        let chinese = render_report(&result, ReportFormat::Chinese);
+        assert!(chinese.starts_with("文法类型: 2型文法（上下文无关文法）\n"));
+
+        let json = render_report(&classify(&[("A B", "a")], "S"), ReportFormat::Json);
+        assert!(json.contains("{\"level\": 2, \"name\": \"context-free\", \"holds\": false, \"violations\": [{\"production\": \"A B -> a\", \"lhs\": [\"A\", \"B\"], \"rhs\": [\"a\"], \"reason\": \"lhs_not_single_non_terminal\", \"message\": \"LHS has more than one symbol\"}]}"));
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeSet;
+
+use crate::tree::{from_ast, from_derivation, from_node, is_epsilon, strip_epsilon, tree_to_string, Tree};
+use crate::Grammar;
+
+// What one parser made of a sentence: the trees it found, with ε stripped, or None if it rejected it
+pub type Outcome = Option<BTreeSet<Tree>>;
+
+pub type Run<'a> = Box<dyn Fn(&[String]) -> Outcome + 'a>;
+
+pub struct Backend<'a> {
+    pub name: String,
+    pub run: Run<'a>,
+}
+
+// The LL(1) crates write ε as "", parseTreeGenerator as "ε"
+fn with_epsilon(grammar: &Grammar, epsilon: &str) -> Grammar {
+    grammar
+        .iter()
+        .map(|(non_terminal, productions)| {
+            let productions = productions
+                .iter()
+                .map(|production| {
+                    let production: Vec<String> = production.iter().filter(|symbol| !is_epsilon(symbol)).cloned().collect();
+                    if production.is_empty() {
+                        vec![epsilon.to_string()]
+                    } else {
+                        production
+                    }
+                })
+                .collect();
+            (non_terminal.clone(), productions)
+        })
+        .collect()
+}
+
+fn single(grammar: &Grammar, tree: Result<Tree, String>) -> Outcome {
+    match tree {
+        Ok(tree) => Some(BTreeSet::from([strip_epsilon(grammar, &tree)])),
+        // A derivation that does not fit together is a tree no other parser can agree with
+        Err(message) => Some(BTreeSet::from([Tree {
+            label: format!("<invalid derivation: {}>", message),
+            children: Vec::new(),
+        }])),
+    }
+}
+
+fn tokens(sentence: &[String]) -> Vec<&str> {
+    sentence.iter().map(String::as_str).collect()
+}
+
+// LL(1) table cells with more than one production, as found by ll1parser
+pub fn ll1_conflicts(grammar: &Grammar, start_symbol: &str) -> Vec<(String, String)> {
+    let grammar = with_epsilon(grammar, "");
+    let first = ll1parser::compute_first(&grammar);
+    let follow = ll1parser::compute_follow(&grammar, start_symbol);
+    ll1parser::table_conflicts(&ll1parser::collect_table_entries(&grammar, &first, &follow))
+}
+
+// Every parser that applies to the grammar: parseTreeGenerator always, the three LL(1) parsers only when
+// the grammar is LL(1). Each one builds its own tables with its own code.
+pub fn backends<'a>(grammar: &'a Grammar, start_symbol: &'a str) -> Vec<Backend<'a>> {
+    let mut backends = Vec::new();
+
+    let general = with_epsilon(grammar, "ε");
+    backends.push(Backend {
+        name: "parseTreeGenerator".to_string(),
+        run: Box::new(move |sentence| {
+            let trees = parse_tree_generator::parse(&general, start_symbol, &tokens(sentence));
+            if trees.is_empty() {
+                return None;
+            }
+            Some(trees.iter().map(|tree| strip_epsilon(grammar, &from_node(tree))).collect())
+        }),
+    });
+
+    if !ll1_conflicts(grammar, start_symbol).is_empty() {
+        return backends;
+    }
+    let ll = with_epsilon(grammar, "");
+
+    {
+        use ll1parser::recovery::{RecoveryOptions, RecoveryStrategy};
+        let grammar = ll.clone();
+        let first = ll1parser::compute_first(&grammar);
+        let follow = ll1parser::compute_follow(&grammar, start_symbol);
+        let table = ll1parser::build_parsing_table(&grammar, &first, &follow);
+        let options = RecoveryOptions { strategy: RecoveryStrategy::Abort, ..RecoveryOptions::default() };
+        backends.push(Backend {
+            name: "ll1parser".to_string(),
+            run: Box::new(move |sentence| {
+                let (errors, trace) = ll1parser::parse(&grammar, &table, &first, &follow, start_symbol, &tokens(sentence), &options);
+                if !errors.is_empty() {
+                    return None;
+                }
+                let derivation = ll1parser::derivation::productions_from_trace(&trace);
+                single(&grammar, from_derivation(&grammar, start_symbol, &derivation))
+            }),
+        });
+    }
+
+    {
+        use recursivell1parser::recovery::{RecoveryOptions, RecoveryStrategy};
+        let grammar = ll.clone();
+        let first = recursivell1parser::compute_first(&grammar);
+        let follow = recursivell1parser::compute_follow(&grammar, start_symbol);
+        let table = recursivell1parser::build_parsing_table(&grammar, &first, &follow);
+        backends.push(Backend {
+            name: "recursivell1parser".to_string(),
+            run: Box::new(move |sentence| {
+                let options = RecoveryOptions {
+                    strategy: RecoveryStrategy::Abort,
+                    verbose: false,
+                    ..RecoveryOptions::default()
+                };
+                let (errors, derivation) =
+                    recursivell1parser::parse(&grammar, &table, &first, &follow, start_symbol, &tokens(sentence), options);
+                if !errors.is_empty() {
+                    return None;
+                }
+                single(&grammar, from_derivation(&grammar, start_symbol, &derivation))
+            }),
+        });
+    }
+
+    {
+        use ll1parser_with_cst::recovery::{RecoveryOptions, RecoveryStrategy};
+        let grammar = ll;
+        let first = ll1parser_with_cst::compute_first(&grammar);
+        let follow = ll1parser_with_cst::compute_follow(&grammar, start_symbol);
+        let table = ll1parser_with_cst::build_parsing_table(&grammar, &first, &follow);
+        backends.push(Backend {
+            name: "ll1parserWithCst".to_string(),
+            run: Box::new(move |sentence| {
+                let options = RecoveryOptions {
+                    strategy: RecoveryStrategy::Abort,
+                    verbose: false,
+                    ..RecoveryOptions::default()
+                };
+                match ll1parser_with_cst::parse(&grammar, &table, &first, &follow, start_symbol, &tokens(sentence), options) {
+                    (errors, Some(ast)) if errors.is_empty() => Some(BTreeSet::from([strip_epsilon(&grammar, &from_ast(&ast))])),
+                    _ => None,
+                }
+            }),
+        });
+    }
+
+    backends
+}
+
+// Parsers grouped by what they made of one sentence; they agree when there is a single group
+pub fn classify(backends: &[Backend], sentence: &[String]) -> Vec<(Outcome, Vec<String>)> {
+    let mut groups: Vec<(Outcome, Vec<String>)> = Vec::new();
+    for backend in backends {
+        let outcome = (backend.run)(sentence);
+        match groups.iter_mut().find(|(seen, _)| *seen == outcome) {
+            Some((_, names)) => names.push(backend.name.clone()),
+            None => groups.push((outcome, vec![backend.name.clone()])),
+        }
+    }
+    groups
+}
+
+// Who accepted the sentence, and whether the accepting parsers built the same trees
+fn signature(groups: &[(Outcome, Vec<String>)]) -> (Vec<String>, bool) {
+    let mut accepting: Vec<String> = groups
+        .iter()
+        .filter(|(outcome, _)| outcome.is_some())
+        .flat_map(|(_, names)| names.iter().cloned())
+        .collect();
+    accepting.sort();
+    let trees_differ = groups.iter().filter(|(outcome, _)| outcome.is_some()).count() > 1;
+    (accepting, trees_differ)
+}
+
+// Shrink a sentence the parsers disagree on, keeping the kind of disagreement: delete runs of tokens, from
+// half the sentence down to single tokens, as long as the same parsers still accept and the trees still
+// differ (or agree) as before
+pub fn minimise(backends: &[Backend], sentence: &[String]) -> Vec<String> {
+    let target = signature(&classify(backends, sentence));
+    let still_fails = |candidate: &[String]| {
+        let groups = classify(backends, candidate);
+        groups.len() > 1 && signature(&groups) == target
+    };
+
+    let mut current = sentence.to_vec();
+    let mut chunk = current.len().div_ceil(2).max(1);
+    while !current.is_empty() {
+        let mut start = 0;
+        let mut shrunk = false;
+        while start < current.len() {
+            let end = (start + chunk).min(current.len());
+            let candidate: Vec<String> = current[..start].iter().chain(&current[end..]).cloned().collect();
+            if still_fails(&candidate) {
+                current = candidate;
+                shrunk = true;
+            } else {
+                start += 1;
+            }
+        }
+        if !shrunk {
+            if chunk == 1 {
+                break;
+            }
+            chunk = chunk.div_ceil(2);
+        }
+    }
+    current
+}
+
+#[derive(Debug, Clone)]
+pub struct Discrepancy {
+    pub sentence: Vec<String>,
+    pub minimised: Vec<String>,
+    pub groups: Vec<(Outcome, Vec<String>)>,
+}
+
+// Run every backend on every sentence and report the sentences they disagree on
+pub fn check(backends: &[Backend], sentences: &[Vec<String>]) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    for sentence in sentences {
+        let groups = classify(backends, sentence);
+        if groups.len() > 1 {
+            let minimised = minimise(backends, sentence);
+            discrepancies.push(Discrepancy {
+                sentence: sentence.clone(),
+                minimised: minimised.clone(),
+                groups: classify(backends, &minimised),
+            });
+        }
+    }
+    discrepancies
+}
+
+fn sentence_to_string(sentence: &[String]) -> String {
+    if sentence.is_empty() {
+        "ε".to_string()
+    } else {
+        sentence.join(" ")
+    }
+}
+
+pub fn discrepancy_to_string(discrepancy: &Discrepancy) -> String {
+    let mut out = format!(
+        "Parsers disagree on: {}\nMinimised counterexample: {}\n",
+        sentence_to_string(&discrepancy.sentence),
+        sentence_to_string(&discrepancy.minimised)
+    );
+    for (outcome, names) in &discrepancy.groups {
+        match outcome {
+            None => out.push_str(&format!("  {}: rejected\n", names.join(", "))),
+            Some(trees) => {
+                out.push_str(&format!("  {}: accepted with {} tree(s)\n", names.join(", "), trees.len()));
+                for tree in trees {
+                    out.push_str(&format!("    {}\n", tree_to_string(tree)));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_grammar;
+
+    fn words(sentence: &str) -> Vec<String> {
+        sentence.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parsers_agree_on_expressions() {
+        let (grammar, start) = read_grammar(crate::EXPRESSION_GRAMMAR).unwrap();
+        let backends = backends(&grammar, &start);
+        assert_eq!(backends.len(), 4);
+
+        let sentences: Vec<Vec<String>> = ["number", "number + ( number * number )", "", "number +", "( number ) number"]
+            .iter()
+            .map(|sentence| words(sentence))
+            .collect();
+        assert!(check(&backends, &sentences).is_empty());
+
+        let groups = classify(&backends, &words("number * number"));
+        let trees = groups[0].0.as_ref().unwrap();
+        assert_eq!(
+            tree_to_string(trees.first().unwrap()),
+            "(E (T (F number) (T' (MUL_DIV *) (F number))))"
+        );
+    }
+
+    #[test]
+    fn test_minimised_counterexample() {
+        let (grammar, start) = read_grammar(crate::EXPRESSION_GRAMMAR).unwrap();
+        let mut backends = backends(&grammar, &start);
+        // A broken parser that rejects every sentence with a parenthesis
+        backends.push(Backend {
+            name: "broken".to_string(),
+            run: Box::new(|sentence| {
+                if sentence.iter().any(|token| token == "(") {
+                    None
+                } else {
+                    Some(BTreeSet::new())
+                }
+            }),
+        });
+        let sentence = words("number + ( number * number ) - number");
+        let discrepancies = check(&backends, &[sentence]);
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].minimised, words("( number )"));
+
+        // Not LL(1), so only parseTreeGenerator applies
+        let (ambiguous, start) = read_grammar("E -> E + E | number").unwrap();
+        assert_eq!(ll1_conflicts(&ambiguous, &start).len(), 1);
+        let general_only = super::backends(&ambiguous, &start);
+        assert_eq!(general_only.len(), 1);
+        let groups = classify(&general_only, &words("number + number + number"));
+        assert_eq!(groups[0].0.as_ref().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,112 @@
+mod check;
+mod tree;
+
+use std::collections::HashMap;
+
+use check::{backends, check, discrepancy_to_string, ll1_conflicts};
+use ll1parser::generate::{enumerate_sentences, near_misses, sample_sentence, Rng, SampleOptions};
+
+type Grammar = HashMap<String, Vec<Vec<String>>>;
+
+// The expression grammar the LL(1) parsers use as their example
+const EXPRESSION_GRAMMAR: &str = "
+E -> T E'
+E' -> ADD_SUB T E' | ε
+T -> F T'
+T' -> MUL_DIV F T' | ε
+F -> number | ( E )
+ADD_SUB -> + | -
+MUL_DIV -> * | div | mod
+";
+
+// The grammar file format of GrammarClassifier: one rule per line, `A -> x y | z`, with ε (or nothing) for
+// the empty right-hand side, # starting a comment and optional %start, %terminals and %nonterminals lines.
+// The parsers here are context-free, so every left-hand side has to be a single symbol.
+fn read_grammar(text: &str) -> Result<(Grammar, String), String> {
+    let input = grammar_classifier::input::read_grammar(text)?;
+    let mut grammar: Grammar = HashMap::new();
+    for (lhs, rhs) in input.productions {
+        let [lhs] = <[String; 1]>::try_from(lhs)
+            .map_err(|lhs| format!("{} -> ...: the left-hand side must be a single symbol", lhs.join(" ")))?;
+        let symbols: Vec<String> = rhs.into_iter().filter(|symbol| symbol != "ε").collect();
+        grammar.entry(lhs).or_default().push(if symbols.is_empty() { vec!["ε".to_string()] } else { symbols });
+    }
+    Ok((grammar, input.start_symbol))
+}
+
+// One sentence per line, tokens separated by whitespace; a line holding just ε is the empty sentence
+fn read_sentences(text: &str) -> Vec<Vec<String>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split_whitespace().filter(|token| *token != "ε").map(str::to_string).collect())
+        .collect()
+}
+
+fn read_file(path: &str) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        std::process::exit(1);
+    })
+}
+
+fn main() {
+    // crosscheck [grammar-file [sentences-file]]; without a sentences file they are generated from the grammar
+    let text = std::env::args().nth(1).map_or(EXPRESSION_GRAMMAR.to_string(), |path| read_file(&path));
+    let (grammar, start) = read_grammar(&text).unwrap_or_else(|message| {
+        eprintln!("Invalid grammar: {}", message);
+        std::process::exit(1);
+    });
+
+    let conflicts = ll1_conflicts(&grammar, &start);
+    if !conflicts.is_empty() {
+        let cells: Vec<String> = conflicts.iter().map(|(non_terminal, terminal)| format!("[{}, {}]", non_terminal, terminal)).collect();
+        println!("Not LL(1), conflicts in {}; only parseTreeGenerator applies", cells.join(" "));
+    }
+    let backends = backends(&grammar, &start);
+    let names: Vec<&str> = backends.iter().map(|backend| backend.name.as_str()).collect();
+    println!("Parsers: {}", names.join(", "));
+
+    let sentences = match std::env::args().nth(2) {
+        Some(path) => read_sentences(&read_file(&path)),
+        None => {
+            // The shortest sentences, random longer ones and near misses that the general parser rejects
+            let mut sentences = enumerate_sentences(&grammar, &start, 5, 40);
+            let mut rng = Rng::new(2024);
+            let options = SampleOptions { max_depth: 6, ..SampleOptions::default() };
+            let samples: Vec<Vec<String>> = (0..20).filter_map(|_| sample_sentence(&grammar, &start, &options, &mut rng)).collect();
+            let mut accepts = |sentence: &[String]| (backends[0].run)(sentence).is_some();
+            let misses = near_misses(&grammar, &samples, 20, &mut rng, &mut accepts);
+            sentences.extend(samples);
+            sentences.extend(misses);
+            sentences
+        }
+    };
+
+    let discrepancies = check(&backends, &sentences);
+    for discrepancy in &discrepancies {
+        print!("{}", discrepancy_to_string(discrepancy));
+    }
+    println!("{} sentences checked, {} discrepancies", sentences.len(), discrepancies.len());
+    if !discrepancies.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_grammar() {
+        let (grammar, start) = read_grammar("%start S\nA -> a A | ε\nS -> A b | # comment\n").unwrap();
+        assert_eq!(start, "S");
+        assert_eq!(grammar["A"], vec![vec!["a".to_string(), "A".to_string()], vec!["ε".to_string()]]);
+        assert_eq!(grammar["S"], vec![vec!["A".to_string(), "b".to_string()], vec!["ε".to_string()]]);
+
+        assert_eq!(
+            read_grammar("S -> a S b\na S -> b").unwrap_err(),
+            "a S -> ...: the left-hand side must be a single symbol"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use ll1parser_with_cst::ASTNode;
+use parse_tree_generator::Node;
+
+use crate::Grammar;
+
+// A parse tree in the one shape every parser's output is converted to; terminals are childless
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tree {
+    pub label: String,
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    fn leaf(label: &str) -> Tree {
+        Tree {
+            label: label.to_string(),
+            children: Vec::new(),
+        }
+    }
+}
+
+pub fn is_epsilon(symbol: &str) -> bool {
+    symbol.is_empty() || symbol == "ε"
+}
+
+// The tree a leftmost derivation describes, as returned by the table-driven and recursive LL(1) parsers
+pub fn from_derivation(grammar: &Grammar, start_symbol: &str, derivation: &[(String, Vec<String>)]) -> Result<Tree, String> {
+    fn build<'a>(
+        grammar: &Grammar,
+        symbol: &str,
+        steps: &mut impl Iterator<Item = &'a (String, Vec<String>)>,
+    ) -> Result<Tree, String> {
+        if !grammar.contains_key(symbol) {
+            return Ok(Tree::leaf(symbol));
+        }
+        let (non_terminal, production) = steps
+            .next()
+            .ok_or_else(|| format!("the derivation ends before {} is expanded", symbol))?;
+        if non_terminal != symbol {
+            return Err(format!("expected a production for {}, found one for {}", symbol, non_terminal));
+        }
+        let children = production
+            .iter()
+            .map(|child| build(grammar, child, steps))
+            .collect::<Result<Vec<Tree>, String>>()?;
+        Ok(Tree {
+            label: symbol.to_string(),
+            children,
+        })
+    }
+
+    let mut steps = derivation.iter();
+    let tree = build(grammar, start_symbol, &mut steps)?;
+    match steps.next() {
+        None => Ok(tree),
+        Some((non_terminal, _)) => Err(format!("the derivation goes on after the tree is complete, with {}", non_terminal)),
+    }
+}
+
+pub fn from_ast(ast: &ASTNode) -> Tree {
+    match ast {
+        ASTNode::NonTerminal(label, children) => Tree {
+            label: label.clone(),
+            children: children.iter().map(from_ast).collect(),
+        },
+        ASTNode::Terminal(label, _) => Tree::leaf(label),
+        ASTNode::Error(message) => Tree::leaf(&format!("<error: {}>", message)),
+    }
+}
+
+pub fn from_node(node: &Node) -> Tree {
+    match node {
+        Node::Leaf(label) => Tree::leaf(label),
+        Node::Internal(label, children) => Tree {
+            label: label.clone(),
+            children: children.iter().map(from_node).collect(),
+        },
+    }
+}
+
+// Remove ε leaves and every subtree of a non-terminal that derives nothing, keeping the root; two trees
+// equal after this are the same tree up to how the ε productions are spelled out
+pub fn strip_epsilon(grammar: &Grammar, tree: &Tree) -> Tree {
+    fn strip(grammar: &Grammar, tree: &Tree) -> Option<Tree> {
+        if is_epsilon(&tree.label) {
+            return None;
+        }
+        if !grammar.contains_key(&tree.label) {
+            return Some(tree.clone());
+        }
+        let children: Vec<Tree> = tree.children.iter().filter_map(|child| strip(grammar, child)).collect();
+        if children.is_empty() {
+            return None;
+        }
+        Some(Tree {
+            label: tree.label.clone(),
+            children,
+        })
+    }
+
+    strip(grammar, tree).unwrap_or_else(|| Tree::leaf(&tree.label))
+}
+
+// (E (T (F number)) (E' + (T (F number))))
+pub fn tree_to_string(tree: &Tree) -> String {
+    if tree.children.is_empty() {
+        return tree.label.clone();
+    }
+    let children: Vec<String> = tree.children.iter().map(tree_to_string).collect();
+    format!("({} {})", tree.label, children.join(" "))
+}
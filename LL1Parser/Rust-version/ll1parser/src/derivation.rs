@@ -0,0 +1,158 @@
+use crate::display::{escape_symbol, TableFormat};
+use crate::trace::{Action, TraceStep};
+use crate::Grammar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivationFormat {
+    Text,
+    // Typst math block with the forms aligned on =>
+    Typst,
+    // LaTeX aligned environment, for use inside display math
+    Latex,
+}
+
+impl DerivationFormat {
+    pub fn from_name(name: &str) -> Option<DerivationFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Some(DerivationFormat::Text),
+            "typst" | "typ" => Some(DerivationFormat::Typst),
+            "latex" | "tex" => Some(DerivationFormat::Latex),
+            _ => None,
+        }
+    }
+}
+
+// Sentential forms of the leftmost derivation that applies `productions` in order, starting from
+// `start_symbol`; each production has to rewrite the leftmost non-terminal of the previous form
+pub fn sentential_forms(
+    grammar: &Grammar,
+    start_symbol: &str,
+    productions: &[(String, Vec<String>)],
+) -> Result<Vec<Vec<String>>, String> {
+    let mut form = vec![start_symbol.to_string()];
+    let mut forms = vec![form.clone()];
+    for (non_terminal, production) in productions {
+        let index = form
+            .iter()
+            .position(|symbol| grammar.contains_key(symbol))
+            .ok_or_else(|| format!("{} has no non-terminal left to expand", form.join(" ")))?;
+        if form[index] != *non_terminal {
+            return Err(format!("the leftmost non-terminal is {}, not {}", form[index], non_terminal));
+        }
+        let replacement = production.iter().filter(|symbol| !symbol.is_empty()).cloned();
+        form.splice(index..index + 1, replacement);
+        forms.push(form.clone());
+    }
+    Ok(forms)
+}
+
+// One sentential form; the empty form, reached when everything derives ε, is written as ε
+fn form_to_string(form: &[String], format: DerivationFormat) -> String {
+    if form.is_empty() {
+        return match format {
+            DerivationFormat::Text => "ε".to_string(),
+            DerivationFormat::Typst => "epsilon".to_string(),
+            DerivationFormat::Latex => "\\varepsilon".to_string(),
+        };
+    }
+    match format {
+        DerivationFormat::Text => form.join(" "),
+        // A quoted string keeps multi-letter symbols such as `number` from being read as variables
+        DerivationFormat::Typst => format!("\"{}\"", form.join(" ").replace('\\', "\\\\").replace('"', "\\\"")),
+        DerivationFormat::Latex => form
+            .iter()
+            .map(|symbol| format!("\\text{{{}}}", escape_symbol(symbol, TableFormat::Latex)))
+            .collect::<Vec<String>>()
+            .join("\\,"),
+    }
+}
+
+// E => T E' => F T' E' => ..., one step per line with the arrows aligned
+pub fn render_derivation(forms: &[Vec<String>], format: DerivationFormat) -> String {
+    let Some((start, rest)) = forms.split_first() else {
+        return String::new();
+    };
+    let start = form_to_string(start, format);
+    let steps: Vec<String> = rest.iter().map(|form| form_to_string(form, format)).collect();
+    match format {
+        DerivationFormat::Text => {
+            let indent = " ".repeat(start.chars().count());
+            let mut out = start.clone();
+            for (idx, step) in steps.iter().enumerate() {
+                if idx > 0 {
+                    out.push('\n');
+                    out.push_str(&indent);
+                }
+                out.push_str(&format!(" => {}", step));
+            }
+            out.push('\n');
+            out
+        }
+        DerivationFormat::Typst => {
+            let lines: Vec<String> = steps.iter().map(|step| format!("&=> {}", step)).collect();
+            format!("$ {} {} $\n", start, lines.join(" \\\n  "))
+        }
+        DerivationFormat::Latex => {
+            let lines: Vec<String> = steps.iter().map(|step| format!("&\\Rightarrow {}", step)).collect();
+            format!("\\begin{{aligned}}\n{} {}\n\\end{{aligned}}\n", start, lines.join(" \\\\\n  "))
+        }
+    }
+}
+
+// Productions expanded by the table-driven parser; they are applied to the leftmost non-terminal on
+// the stack, so in trace order they already form a leftmost derivation
+pub fn productions_from_trace(trace: &[TraceStep]) -> Vec<(String, Vec<String>)> {
+    trace
+        .iter()
+        .filter_map(|step| match &step.action {
+            Action::Expand(non_terminal, production) => Some((non_terminal.clone(), production.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn production(lhs: &str, rhs: &[&str]) -> (String, Vec<String>) {
+        (lhs.to_string(), rhs.iter().map(|symbol| symbol.to_string()).collect())
+    }
+
+    #[test]
+    fn test_sentential_forms() {
+        let grammar: Grammar = [
+            ("S".to_string(), vec![vec!["A".to_string(), "B".to_string()]]),
+            ("A".to_string(), vec![vec!["a".to_string()]]),
+            ("B".to_string(), vec![vec!["b".to_string()], vec!["".to_string()]]),
+        ]
+        .into_iter()
+        .collect();
+
+        let forms = sentential_forms(&grammar, "S", &[production("S", &["A", "B"]), production("A", &["a"]), production("B", &[""])]).unwrap();
+        assert_eq!(render_derivation(&forms, DerivationFormat::Text), "S => A B\n  => a B\n  => a\n");
+
+        // B is not the leftmost non-terminal of A B
+        assert!(sentential_forms(&grammar, "S", &[production("S", &["A", "B"]), production("B", &["b"])]).is_err());
+    }
+
+    #[test]
+    fn test_typst_and_latex_derivations() {
+        let grammar: Grammar = [
+            ("E".to_string(), vec![vec!["<".to_string(), "id_1".to_string(), "\"".to_string(), ">".to_string()]]),
+        ]
+        .into_iter()
+        .collect();
+        let forms = sentential_forms(&grammar, "E", &[production("E", &["<", "id_1", "\"", ">"])]).unwrap();
+
+        assert_eq!(render_derivation(&forms, DerivationFormat::Typst), "$ \"E\" &=> \"< id_1 \\\" >\" $\n");
+        assert_eq!(
+            render_derivation(&forms, DerivationFormat::Latex),
+            "\\begin{aligned}\n\\text{E} &\\Rightarrow \\text{\\textless{}}\\,\\text{id\\_1}\\,\\text{\"}\\,\\text{\\textgreater{}}\n\\end{aligned}\n"
+        );
+        // Everything derived ε
+        let forms = vec![vec!["B".to_string()], vec![]];
+        assert_eq!(render_derivation(&forms, DerivationFormat::Typst), "$ \"B\" &=> epsilon $\n");
+        assert_eq!(render_derivation(&forms, DerivationFormat::Latex), "\\begin{aligned}\n\\text{B} &\\Rightarrow \\varepsilon\n\\end{aligned}\n");
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+
+use crate::transform::{ordered_non_terminals, production_to_string};
+use crate::Grammar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Text,
+    Markdown,
+    Latex,
+    Csv,
+    Html,
+}
+
+impl TableFormat {
+    pub fn from_name(name: &str) -> Option<TableFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Some(TableFormat::Text),
+            "markdown" | "md" => Some(TableFormat::Markdown),
+            "latex" | "tex" => Some(TableFormat::Latex),
+            "csv" => Some(TableFormat::Csv),
+            "html" | "htm" => Some(TableFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+// A rendered cell and whether it should be highlighted (an LL(1) conflict or a parse error)
+pub type Cell = (String, bool);
+
+// Terminals used as table columns: sorted, with the end marker `$` last
+pub fn table_terminals(grammar: &Grammar) -> Vec<String> {
+    let mut terminals: Vec<String> = grammar
+        .values()
+        .flatten()
+        .flatten()
+        .filter(|symbol| !symbol.is_empty() && !grammar.contains_key(*symbol))
+        .cloned()
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect();
+    terminals.sort();
+    terminals.push("$".to_string());
+    terminals
+}
+
+pub fn escape_symbol(symbol: &str, format: TableFormat) -> String {
+    match format {
+        TableFormat::Latex => {
+            if symbol == "ε" {
+                return "$\\varepsilon$".to_string();
+            }
+            symbol
+                .chars()
+                .map(|c| match c {
+                    '$' | '&' | '%' | '#' | '_' | '{' | '}' => format!("\\{}", c),
+                    '~' => "\\textasciitilde{}".to_string(),
+                    '^' => "\\textasciicircum{}".to_string(),
+                    '\\' => "\\textbackslash{}".to_string(),
+                    '<' => "\\textless{}".to_string(),
+                    '>' => "\\textgreater{}".to_string(),
+                    _ => c.to_string(),
+                })
+                .collect()
+        }
+        TableFormat::Markdown => symbol
+            .chars()
+            .map(|c| match c {
+                '|' | '*' | '_' | '`' => format!("\\{}", c),
+                _ => c.to_string(),
+            })
+            .collect(),
+        TableFormat::Html => symbol
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;"),
+        // `|` separates the columns of a text table, so it is escaped as in Markdown
+        TableFormat::Text => symbol.replace('|', "\\|"),
+        TableFormat::Csv => symbol.to_string(),
+    }
+}
+
+pub fn format_production(non_terminal: &str, production: &[String], format: TableFormat) -> String {
+    let arrow = match format {
+        TableFormat::Latex => "$\\to$",
+        TableFormat::Html => "&rarr;",
+        _ => "->",
+    };
+    let rhs: Vec<String> = production_to_string(production)
+        .split(' ')
+        .map(|symbol| escape_symbol(symbol, format))
+        .collect();
+    format!("{} {} {}", escape_symbol(non_terminal, format), arrow, rhs.join(" "))
+}
+
+fn format_set(set: &HashSet<String>, format: TableFormat) -> String {
+    let mut symbols: Vec<&String> = set.iter().collect();
+    // ε and `$` go last so the ordinary terminals read first
+    symbols.sort_by_key(|symbol| (symbol.is_empty(), symbol.as_str() == "$", symbol.as_str()));
+    let items: Vec<String> = symbols
+        .into_iter()
+        .map(|symbol| escape_symbol(if symbol.is_empty() { "ε" } else { symbol }, format))
+        .collect();
+    if format == TableFormat::Latex {
+        format!("\\{{{}\\}}", items.join(", "))
+    } else {
+        format!("{{{}}}", items.join(", "))
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// Lay out a header row plus body rows in the requested format; text is only coloured when it goes
+// straight to a terminal, so piped or redirected output stays free of escape codes
+pub fn render_grid(rows: &[Vec<Cell>], format: TableFormat) -> String {
+    let colour = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    lay_out(rows, format, colour)
+}
+
+fn lay_out(rows: &[Vec<Cell>], format: TableFormat, colour: bool) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|(text, _)| text.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let pad = |text: &str, width: usize| format!("{}{}", text, " ".repeat(width - text.chars().count()));
+
+    let mut out = String::new();
+    match format {
+        TableFormat::Text => {
+            for (idx, row) in rows.iter().enumerate() {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|((text, conflict), &width)| {
+                        // Pad before colouring so the escape codes do not break the alignment
+                        let padded = pad(text, width);
+                        if *conflict && colour {
+                            format!("\x1b[1;31m{}\x1b[0m", padded)
+                        } else {
+                            padded
+                        }
+                    })
+                    .collect();
+                out.push_str(cells.join(" | ").trim_end());
+                out.push('\n');
+                if idx == 0 {
+                    let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+                    out.push_str(&rule.join("-+-"));
+                    out.push('\n');
+                }
+            }
+        }
+        TableFormat::Markdown => {
+            for (idx, row) in rows.iter().enumerate() {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|((text, conflict), &width)| {
+                        if *conflict {
+                            pad(&format!("**{}**", text), width + 4)
+                        } else {
+                            pad(text, width + 4)
+                        }
+                    })
+                    .collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                if idx == 0 {
+                    let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width + 4)).collect();
+                    out.push_str(&format!("| {} |\n", rule.join(" | ")));
+                }
+            }
+        }
+        TableFormat::Latex => {
+            // Conflicts are coloured with \textcolor, which needs \usepackage{xcolor}
+            out.push_str(&format!("\\begin{{tabular}}{{|{}}}\n\\hline\n", "l|".repeat(columns)));
+            for row in rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|(text, conflict)| {
+                        if *conflict {
+                            format!("\\textcolor{{red}}{{{}}}", text)
+                        } else {
+                            text.clone()
+                        }
+                    })
+                    .collect();
+                out.push_str(&format!("{} \\\\\n\\hline\n", cells.join(" & ")));
+            }
+            out.push_str("\\end{tabular}\n");
+        }
+        TableFormat::Csv => {
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|(text, _)| csv_field(text)).collect();
+                out.push_str(&cells.join(","));
+                out.push('\n');
+            }
+        }
+        TableFormat::Html => {
+            out.push_str("<table border=\"1\">\n");
+            for (idx, row) in rows.iter().enumerate() {
+                let tag = if idx == 0 { "th" } else { "td" };
+                out.push_str("  <tr>");
+                for (text, highlighted) in row {
+                    if *highlighted {
+                        out.push_str(&format!("<{} style=\"color: red\">{}</{}>", tag, text, tag));
+                    } else {
+                        out.push_str(&format!("<{}>{}</{}>", tag, text, tag));
+                    }
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</table>\n");
+        }
+    }
+    out
+}
+
+// FIRST and FOLLOW side by side, one row per non-terminal
+pub fn render_first_follow(
+    grammar: &Grammar,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>,
+    start_symbol: &str,
+    format: TableFormat,
+) -> String {
+    let empty = HashSet::new();
+    let mut rows: Vec<Vec<Cell>> = vec![vec![
+        ("Non-terminal".to_string(), false),
+        ("FIRST".to_string(), false),
+        ("FOLLOW".to_string(), false),
+    ]];
+    for non_terminal in ordered_non_terminals(grammar, start_symbol) {
+        rows.push(vec![
+            (escape_symbol(&non_terminal, format), false),
+            (format_set(first.get(&non_terminal).unwrap_or(&empty), format), false),
+            (format_set(follow.get(&non_terminal).unwrap_or(&empty), format), false),
+        ]);
+    }
+    render_grid(&rows, format)
+}
+
+// The parsing table with non-terminals as rows and terminals as columns; cells holding more
+// than one production are LL(1) conflicts and get highlighted
+pub fn render_parsing_table(
+    grammar: &Grammar,
+    entries: &HashMap<(String, String), Vec<Vec<String>>>,
+    start_symbol: &str,
+    format: TableFormat,
+) -> String {
+    let terminals = table_terminals(grammar);
+    let mut header: Vec<Cell> = vec![(String::new(), false)];
+    header.extend(terminals.iter().map(|terminal| (escape_symbol(terminal, format), false)));
+
+    let mut rows = vec![header];
+    for non_terminal in ordered_non_terminals(grammar, start_symbol) {
+        let mut row: Vec<Cell> = vec![(escape_symbol(&non_terminal, format), false)];
+        for terminal in &terminals {
+            let productions = entries
+                .get(&(non_terminal.clone(), terminal.clone()))
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            let text: Vec<String> = productions
+                .iter()
+                .map(|production| format_production(&non_terminal, production, format))
+                .collect();
+            row.push((text.join(" / "), productions.len() > 1));
+        }
+        rows.push(row);
+    }
+    render_grid(&rows, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collect_table_entries, compute_first, compute_follow};
+
+    // S -> < S | < | ε: a conflict under `<` and a symbol every format has to escape
+    fn render(format: TableFormat) -> String {
+        let grammar: Grammar = [(
+            "S".to_string(),
+            vec![vec!["<".to_string(), "S".to_string()], vec!["<".to_string()], vec!["".to_string()]],
+        )]
+        .into_iter()
+        .collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "S");
+        let entries = collect_table_entries(&grammar, &first, &follow);
+        render_parsing_table(&grammar, &entries, "S", format)
+    }
+
+    #[test]
+    fn test_text_grid() {
+        let rows: Vec<Vec<Cell>> = vec![
+            vec![(String::new(), false), ("<".to_string(), false), ("$".to_string(), false)],
+            vec![("S".to_string(), false), ("S -> < S / S -> <".to_string(), true), ("S -> ε".to_string(), false)],
+        ];
+        assert_eq!(
+            lay_out(&rows, TableFormat::Text, false),
+            "  | <                 | $\n\
+             --+-------------------+-------\n\
+             S | S -> < S / S -> < | S -> ε\n"
+        );
+        assert_eq!(
+            lay_out(&rows, TableFormat::Text, true).lines().nth(2),
+            Some("S | \x1b[1;31mS -> < S / S -> <\x1b[0m | S -> ε")
+        );
+    }
+
+    #[test]
+    fn test_parsing_table_formats() {
+        assert_eq!(
+            render(TableFormat::Markdown),
+            "|       | <                     | $          |\n\
+             | ----- | --------------------- | ---------- |\n\
+             | S     | **S -> < S / S -> <** | S -> ε     |\n"
+        );
+        assert_eq!(
+            render(TableFormat::Latex),
+            "\\begin{tabular}{|l|l|l|}\n\\hline\n \
+             & \\textless{} & \\$ \\\\\n\\hline\n\
+             S & \\textcolor{red}{S $\\to$ \\textless{} S / S $\\to$ \\textless{}} & S $\\to$ $\\varepsilon$ \\\\\n\\hline\n\
+             \\end{tabular}\n"
+        );
+        assert_eq!(render(TableFormat::Csv), ",<,$\nS,S -> < S / S -> <,S -> ε\n");
+        assert_eq!(
+            render(TableFormat::Html),
+            "<table border=\"1\">\n  \
+             <tr><th></th><th>&lt;</th><th>$</th></tr>\n  \
+             <tr><td>S</td><td style=\"color: red\">S &rarr; &lt; S / S &rarr; &lt;</td><td>S &rarr; ε</td></tr>\n\
+             </table>\n"
+        );
+        // Piped output, as under `cargo test`, is never coloured
+        if !std::io::stdout().is_terminal() {
+            assert!(!render(TableFormat::Text).contains('\x1b'));
+        }
+    }
+
+    #[test]
+    fn test_csv_quotes_sets() {
+        let grammar: Grammar = [("S".to_string(), vec![vec!["a".to_string()], vec!["".to_string()]])].into_iter().collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "S");
+        assert_eq!(
+            render_first_follow(&grammar, &first, &follow, "S", TableFormat::Csv),
+            "Non-terminal,FIRST,FOLLOW\nS,\"{a, ε}\",{$}\n"
+        );
+    }
+}
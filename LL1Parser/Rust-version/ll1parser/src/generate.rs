@@ -0,0 +1,348 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+
+use crate::Grammar;
+
+// ε is written "" in the LL(1) grammars and "ε" in parseTreeGenerator; both are accepted here
+fn is_epsilon(symbol: &str) -> bool {
+    symbol.is_empty() || symbol == "ε"
+}
+
+fn terminals(grammar: &Grammar) -> Vec<String> {
+    let terminals: BTreeSet<&String> = grammar
+        .values()
+        .flatten()
+        .flatten()
+        .filter(|symbol| !is_epsilon(symbol) && !grammar.contains_key(*symbol))
+        .collect();
+    terminals.into_iter().cloned().collect()
+}
+
+// Length of the shortest sentence each non-terminal derives; unproductive non-terminals are missing
+pub fn min_lengths(grammar: &Grammar) -> HashMap<String, usize> {
+    let mut lengths: HashMap<String, usize> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                let Some(length) = form_length(grammar, &lengths, production) else {
+                    continue;
+                };
+                if lengths.get(non_terminal).is_none_or(|&current| length < current) {
+                    lengths.insert(non_terminal.clone(), length);
+                    changed = true;
+                }
+            }
+        }
+    }
+    lengths
+}
+
+// Height of the lowest derivation tree of each non-terminal; always taking a production of minimal
+// height finishes a derivation, which taking the shortest yield does not when there are rules like A -> A
+fn min_heights(grammar: &Grammar) -> HashMap<String, usize> {
+    let mut heights: HashMap<String, usize> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                let Some(height) = production_height(grammar, &heights, production) else {
+                    continue;
+                };
+                if heights.get(non_terminal).is_none_or(|&current| height < current) {
+                    heights.insert(non_terminal.clone(), height);
+                    changed = true;
+                }
+            }
+        }
+    }
+    heights
+}
+
+fn production_height(grammar: &Grammar, heights: &HashMap<String, usize>, production: &[String]) -> Option<usize> {
+    let mut height = 0;
+    for symbol in production.iter().filter(|symbol| grammar.contains_key(*symbol)) {
+        height = height.max(*heights.get(symbol)?);
+    }
+    Some(height + 1)
+}
+
+// Shortest sentence a sentential form can still derive, None if it contains an unproductive non-terminal
+fn form_length(grammar: &Grammar, lengths: &HashMap<String, usize>, form: &[String]) -> Option<usize> {
+    let mut total = 0;
+    for symbol in form.iter().filter(|symbol| !is_epsilon(symbol)) {
+        total += if grammar.contains_key(symbol) { *lengths.get(symbol)? } else { 1 };
+    }
+    Some(total)
+}
+
+// Sentences of the grammar in order of length, at most `limit` of them and none longer than `max_length`.
+// Sentential forms are expanded at their leftmost non-terminal, cheapest first, where the cost of a form
+// is the length of the shortest sentence it can still derive; so a complete sentence comes out only
+// after every shorter one. Nullable symbols can pile up without raising the cost (S -> S A, A -> ε), so
+// forms are also cut off at twice `max_length` symbols, and the search gives up after a fixed number of forms.
+pub fn enumerate_sentences(grammar: &Grammar, start_symbol: &str, max_length: usize, limit: usize) -> Vec<Vec<String>> {
+    const MAX_FORMS: usize = 20_000;
+    let lengths = min_lengths(grammar);
+    let mut expanded = 0;
+    let mut sentences = Vec::new();
+    let mut seen: HashSet<Vec<String>> = HashSet::new();
+    let mut queue = BinaryHeap::new();
+
+    let start = vec![start_symbol.to_string()];
+    if let Some(length) = form_length(grammar, &lengths, &start) {
+        queue.push(Reverse((length, start)));
+    }
+
+    while let Some(Reverse((_, form))) = queue.pop() {
+        expanded += 1;
+        if sentences.len() >= limit || expanded > MAX_FORMS {
+            break;
+        }
+        let Some(index) = form.iter().position(|symbol| grammar.contains_key(symbol)) else {
+            sentences.push(form);
+            continue;
+        };
+        for production in &grammar[&form[index]] {
+            let mut next: Vec<String> = form[..index].to_vec();
+            next.extend(production.iter().filter(|symbol| !is_epsilon(symbol)).cloned());
+            next.extend(form[index + 1..].iter().cloned());
+            let Some(length) = form_length(grammar, &lengths, &next) else {
+                continue;
+            };
+            if length <= max_length && next.len() <= 2 * max_length + 2 && seen.insert(next.clone()) {
+                queue.push(Reverse((length, next)));
+            }
+        }
+    }
+
+    sentences
+}
+
+// xorshift64*, enough to make the sampling reproducible from a seed without pulling in a crate
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Uniform in 0..bound
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub struct SampleOptions {
+    // Below this depth productions are picked by weight; from it on, only productions of minimal height
+    // are used, so the derivation is forced to finish
+    pub max_depth: usize,
+    // Weight of the i-th production of a non-terminal; productions without one weigh 1
+    pub weights: HashMap<(String, usize), f64>,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            weights: HashMap::new(),
+        }
+    }
+}
+
+// A random sentence, or None when the start symbol derives no sentence at all
+pub fn sample_sentence(grammar: &Grammar, start_symbol: &str, options: &SampleOptions, rng: &mut Rng) -> Option<Vec<String>> {
+    fn expand(
+        grammar: &Grammar,
+        heights: &HashMap<String, usize>,
+        symbol: &str,
+        depth: usize,
+        options: &SampleOptions,
+        rng: &mut Rng,
+        out: &mut Vec<String>,
+    ) {
+        if is_epsilon(symbol) {
+            return;
+        }
+        let Some(productions) = grammar.get(symbol) else {
+            out.push(symbol.to_string());
+            return;
+        };
+
+        // Productions that can finish, with their height
+        let candidates: Vec<(usize, usize)> = productions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, production)| production_height(grammar, heights, production).map(|height| (index, height)))
+            .collect();
+        let chosen = if depth >= options.max_depth {
+            candidates.iter().min_by_key(|(_, height)| *height).map(|(index, _)| *index)
+        } else {
+            let weight = |index: usize| options.weights.get(&(symbol.to_string(), index)).copied().unwrap_or(1.0);
+            let total: f64 = candidates.iter().map(|(index, _)| weight(*index)).sum();
+            let mut target = rng.unit() * total;
+            candidates
+                .iter()
+                .find(|(index, _)| {
+                    target -= weight(*index);
+                    target < 0.0
+                })
+                .or(candidates.last())
+                .map(|(index, _)| *index)
+        };
+
+        if let Some(index) = chosen {
+            for child in &productions[index] {
+                expand(grammar, heights, child, depth + 1, options, rng, out);
+            }
+        }
+    }
+
+    let heights = min_heights(grammar);
+    heights.get(start_symbol)?;
+    let mut sentence = Vec::new();
+    expand(grammar, &heights, start_symbol, 0, options, rng, &mut sentence);
+    Some(sentence)
+}
+
+// One random edit: delete, insert, replace or swap a token
+pub fn mutate(sentence: &[String], terminals: &[String], rng: &mut Rng) -> Vec<String> {
+    let mut mutated = sentence.to_vec();
+    // Without terminals nothing can be inserted or replaced, so only deletions and swaps are left
+    if terminals.is_empty() {
+        if mutated.len() >= 2 && rng.below(2) == 1 {
+            let index = rng.below(mutated.len() - 1);
+            mutated.swap(index, index + 1);
+        } else if !mutated.is_empty() {
+            mutated.remove(rng.below(mutated.len()));
+        }
+        return mutated;
+    }
+    let random_terminal = |rng: &mut Rng| terminals[rng.below(terminals.len())].clone();
+    match rng.below(4) {
+        0 if !mutated.is_empty() => {
+            mutated.remove(rng.below(mutated.len()));
+        }
+        2 if !mutated.is_empty() => {
+            let index = rng.below(mutated.len());
+            mutated[index] = random_terminal(rng);
+        }
+        3 if mutated.len() >= 2 => {
+            let index = rng.below(mutated.len() - 1);
+            mutated.swap(index, index + 1);
+        }
+        _ => {
+            let index = rng.below(mutated.len() + 1);
+            mutated.insert(index, random_terminal(rng));
+        }
+    }
+    mutated
+}
+
+// Invalid sentences one edit away from valid ones; `accepts` is the membership test, typically one of the
+// parsers, and decides which mutants are really outside the language
+pub fn near_misses(
+    grammar: &Grammar,
+    valid: &[Vec<String>],
+    count: usize,
+    rng: &mut Rng,
+    accepts: &mut dyn FnMut(&[String]) -> bool,
+) -> Vec<Vec<String>> {
+    let terminals = terminals(grammar);
+    let mut found: Vec<Vec<String>> = Vec::new();
+    if valid.is_empty() || terminals.is_empty() {
+        return found;
+    }
+    // Give up after a bounded number of tries, in case almost every mutant is still valid
+    for _ in 0..count * 20 {
+        if found.len() >= count {
+            break;
+        }
+        let mutant = mutate(&valid[rng.below(valid.len())], &terminals, rng);
+        if !found.contains(&mutant) && !accepts(&mutant) {
+            found.push(mutant);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(rules: &[(&str, &[&[&str]])]) -> Grammar {
+        rules
+            .iter()
+            .map(|(lhs, alternatives)| {
+                let productions = alternatives
+                    .iter()
+                    .map(|alt| alt.iter().map(|symbol| symbol.to_string()).collect())
+                    .collect();
+                (lhs.to_string(), productions)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enumerate_shortest_first() {
+        // Balanced parentheses, written the parseTreeGenerator way with ε
+        let g = grammar(&[("S", &[&["(", "S", ")", "S"], &["ε"]])]);
+        let sentences: Vec<String> = enumerate_sentences(&g, "S", 6, 100).iter().map(|s| s.concat()).collect();
+        assert_eq!(sentences[0], "");
+        assert_eq!(sentences[1], "()");
+        assert_eq!(&sentences[2..4], &["(())".to_string(), "()()".to_string()]);
+        assert_eq!(sentences.len(), 1 + 1 + 2 + 5);
+        assert!(sentences.windows(2).all(|pair| pair[0].len() <= pair[1].len()));
+    }
+
+    #[test]
+    fn test_sample_and_mutate() {
+        let g = grammar(&[("S", &[&["a", "S", "b"], &[""]])]);
+        let in_language = |s: &[String]| {
+            let n = s.len() / 2;
+            s.len().is_multiple_of(2) && s[..n].iter().all(|x| x == "a") && s[n..].iter().all(|x| x == "b")
+        };
+
+        let mut rng = Rng::new(7);
+        let options = SampleOptions { max_depth: 4, ..SampleOptions::default() };
+        let samples: Vec<Vec<String>> = (0..20).map(|_| sample_sentence(&g, "S", &options, &mut rng).unwrap()).collect();
+        assert!(samples.iter().all(|s| in_language(s) && s.len() <= 2 * 5));
+
+        let misses = near_misses(&g, &samples, 5, &mut rng, &mut |s| in_language(s));
+        assert_eq!(misses.len(), 5);
+        assert!(misses.iter().all(|s| !in_language(s)));
+    }
+
+    #[test]
+    fn test_enumerate_nullable_left_recursion() {
+        // Every S A ... A form still costs one token; the search must stop anyway
+        let g = grammar(&[("S", &[&["S", "A"], &["a"]]), ("A", &[&["ε"]])]);
+        assert_eq!(enumerate_sentences(&g, "S", 5, 5), vec![vec!["a".to_string()]]);
+    }
+
+    #[test]
+    fn test_mutate_without_terminals() {
+        let mut rng = Rng::new(3);
+        let sentence = vec!["a".to_string(), "b".to_string()];
+        for _ in 0..20 {
+            let mutated = mutate(&sentence, &[], &mut rng);
+            assert!(mutated == ["b", "a"] || mutated.len() == 1);
+        }
+        assert!(mutate(&[], &[], &mut rng).is_empty());
+    }
+}
@@ -0,0 +1,387 @@
+pub mod derivation;
+pub mod display;
+pub mod generate;
+pub mod llk;
+pub mod recovery;
+pub mod source;
+pub mod trace;
+pub mod transform;
+
+use std::collections::{HashMap, HashSet};
+
+use recovery::{expected_terminals, Recovery, RecoveryOptions, RecoveryStrategy, SyntaxError};
+use trace::{Action, TraceStep};
+
+pub type Grammar = HashMap<String, Vec<Vec<String>>>;
+
+// Compute the First set for the grammar
+pub fn compute_first(grammar: &Grammar) -> HashMap<String, HashSet<String>> {
+    let mut first: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for non_terminal in grammar.keys() {
+        first.insert(non_terminal.clone(), HashSet::new());
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                if update_first_set(grammar, &mut first, non_terminal, production) {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    first
+}
+
+// Update the First set for a given production
+pub fn update_first_set(
+    grammar: &Grammar,
+    first: &mut HashMap<String, HashSet<String>>,
+    non_terminal: &String,
+    production: &[String],
+) -> bool {
+    let mut changed = false;
+    for symbol in production {
+        if grammar.contains_key(symbol) {
+            let first_set = first.get(symbol).unwrap().clone();
+            let len_before = first.get(non_terminal).unwrap().len();
+            first.get_mut(non_terminal).unwrap().extend(first_set.clone());
+            let len_after = first.get(non_terminal).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+            if !first_set.contains("") {
+                break;
+            }
+        } else {
+            let len_before = first.get(non_terminal).unwrap().len();
+            first.get_mut(non_terminal).unwrap().insert(symbol.clone());
+            let len_after = first.get(non_terminal).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+            break;
+        }
+    }
+    changed
+}
+
+// Compute the Follow set for the grammar
+pub fn compute_follow(grammar: &Grammar, start_symbol: &str) -> HashMap<String, HashSet<String>> {
+    let mut follow: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for non_terminal in grammar.keys() {
+        follow.insert(non_terminal.clone(), HashSet::new());
+    }
+
+    follow.get_mut(start_symbol).unwrap().insert("$".to_owned());
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                if update_follow_set(grammar, &mut follow, non_terminal, production) {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    follow
+}
+
+// Update the Follow set for a given production
+pub fn update_follow_set(
+    grammar: &Grammar,
+    follow: &mut HashMap<String, HashSet<String>>,
+    non_terminal: &String,
+    production: &[String],
+) -> bool {
+    let mut changed = false;
+    for (i, symbol) in production.iter().enumerate() {
+        if grammar.contains_key(symbol) {
+            let mut follow_set = follow.get(symbol).unwrap().clone();
+            if i + 1 < production.len() {
+                let next_symbol = &production[i + 1];
+                if grammar.contains_key(next_symbol) {
+                    let first_set = compute_first(grammar).get(next_symbol).unwrap().clone();
+                    follow_set.extend(first_set.iter().filter(|x| !x.is_empty()).cloned());
+                    if first_set.contains("") {
+                        follow_set.extend(follow.get(non_terminal).unwrap().clone());
+                    }
+                } else {
+                    follow_set.insert(next_symbol.clone());
+                }
+            } else {
+                follow_set.extend(follow.get(non_terminal).unwrap().clone());
+            }
+            let len_before = follow.get(symbol).unwrap().len();
+            follow.get_mut(symbol).unwrap().extend(follow_set.clone());
+            let len_after = follow.get(symbol).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+// Build the parsing table for the grammar; in a cell with several productions the last one wins
+pub fn build_parsing_table(
+    grammar: &Grammar,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>
+) -> HashMap<(String, String), Vec<String>> {
+    table_from_entries(&collect_table_entries(grammar, first, follow))
+}
+
+// Collect every production that lands in each table cell, so cells with more than one are LL(1) conflicts
+pub fn collect_table_entries(
+    grammar: &Grammar,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>
+) -> HashMap<(String, String), Vec<Vec<String>>> {
+    let mut entries: HashMap<(String, String), Vec<Vec<String>>> = HashMap::new();
+
+    for (non_terminal, productions) in grammar.iter() {
+        for production in productions {
+            let first_set = compute_first_for_production(production, first);
+            let mut lookaheads: HashSet<&String> = first_set.iter().filter(|&x| !x.is_empty()).collect();
+            if first_set.contains("") {
+                lookaheads.extend(follow.get(non_terminal).unwrap());
+            }
+            for terminal in lookaheads {
+                entries.entry((non_terminal.clone(), terminal.clone())).or_default().push(production.clone());
+            }
+        }
+    }
+
+    entries
+}
+
+// The LL(1) table from the collected entries, one production per cell
+pub fn table_from_entries(entries: &HashMap<(String, String), Vec<Vec<String>>>) -> HashMap<(String, String), Vec<String>> {
+    entries
+        .iter()
+        .filter_map(|(cell, productions)| Some((cell.clone(), productions.last()?.clone())))
+        .collect()
+}
+
+// The cells of the collected entries that hold more than one production, sorted
+pub fn table_conflicts(entries: &HashMap<(String, String), Vec<Vec<String>>>) -> Vec<(String, String)> {
+    let mut conflicts: Vec<(String, String)> =
+        entries.iter().filter(|(_, productions)| productions.len() > 1).map(|(cell, _)| cell.clone()).collect();
+    conflicts.sort();
+    conflicts
+}
+
+// Compute the First set for a given production
+pub fn compute_first_for_production(
+    production: &[String],
+    first: &HashMap<String, HashSet<String>>,
+) -> HashSet<String> {
+    let mut result = HashSet::new();
+    for symbol in production {
+        if first.contains_key(symbol) {
+            let first_set = first.get(symbol).unwrap();
+            result.extend(first_set.clone());
+            if !first_set.contains("") {
+                break;
+            }
+        } else {
+            result.insert(symbol.clone());
+            break;
+        }
+    }
+    result
+}
+
+pub fn record_step(trace: &mut Vec<TraceStep>, stack: &[String], input: &[String], action: Action) {
+    trace.push(TraceStep {
+        step: trace.len() + 1,
+        stack: stack.to_vec(),
+        input: input.to_vec(),
+        action,
+    });
+}
+
+// LL(1) parser with configurable error recovery, recording every step it takes and every syntax error
+// it runs into; the input is accepted when no errors were reported. `input` holds the terminals only,
+// the end marker is appended here, so empty input is simply `&[]`
+pub fn parse(
+    grammar: &Grammar,
+    parsing_table: &HashMap<(String, String), Vec<String>>,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>,
+    start_symbol: &str,
+    input: &[&str],
+    options: &RecoveryOptions,
+) -> (Vec<SyntaxError>, Vec<TraceStep>) {
+    let mut stack = vec!["$".to_owned(), start_symbol.to_owned()];
+    let mut tokens: Vec<String> = input.iter().map(|token| token.to_string()).collect();
+    tokens.push("$".to_owned());
+    let mut position = 0;
+    let mut trace: Vec<TraceStep> = Vec::new();
+    let mut errors: Vec<SyntaxError> = Vec::new();
+
+    if let Some(error) = SyntaxError::end_marker_in_input(input) {
+        record_step(&mut trace, &stack, &tokens, Action::Error(error.message.clone()));
+        return (vec![error], trace);
+    }
+
+    loop {
+        let top = stack.last().unwrap().clone();
+        let lookahead = tokens[position].clone();
+        let error = if !grammar.contains_key(&top) {
+            // The top of the stack is a terminal (or the end marker)
+            if top == "$" && lookahead == "$" {
+                let action = if errors.is_empty() { Action::Accept } else { Action::Reject };
+                record_step(&mut trace, &stack, &tokens[position..], action);
+                break;
+            } else if top == lookahead {
+                record_step(&mut trace, &stack, &tokens[position..], Action::Match(lookahead));
+                stack.pop();
+                position += 1;
+                continue;
+            } else if top == "$" {
+                SyntaxError::trailing(position, &lookahead)
+            } else {
+                SyntaxError::unexpected(position, &lookahead, &top)
+            }
+        } else if let Some(production) = parsing_table.get(&(top.clone(), lookahead.clone())) {
+            // Push the production in reverse order onto the stack, skipping ε
+            record_step(&mut trace, &stack, &tokens[position..], Action::Expand(top.clone(), production.clone()));
+            stack.pop();
+            for symbol in production.iter().rev() {
+                if !symbol.is_empty() {
+                    stack.push(symbol.clone());
+                }
+            }
+            continue;
+        } else {
+            SyntaxError::unexpected(position, &lookahead, &expected_terminals(&top, parsing_table))
+        };
+
+        record_step(&mut trace, &stack, &tokens[position..], Action::Error(error.message.clone()));
+        errors.push(error);
+        if options.strategy == RecoveryStrategy::Abort {
+            break;
+        }
+
+        if top == "$" {
+            // Trailing input: nothing is left to parse it with, so it is all discarded
+            while tokens[position] != "$" {
+                record_step(&mut trace, &stack, &tokens[position..], Action::Skip(tokens[position].clone()));
+                position += 1;
+            }
+        } else if !grammar.contains_key(&top) {
+            if options.delete_instead_of_insert(&top, input, position) {
+                record_step(&mut trace, &stack, &tokens[position..], Action::Skip(lookahead));
+                position += 1;
+            } else {
+                record_step(&mut trace, &stack, &tokens[position..], Action::Insert(top));
+                stack.pop();
+            }
+        } else {
+            match options.recover_non_terminal(&top, input, position, parsing_table, first, follow) {
+                Recovery::Delete => {
+                    record_step(&mut trace, &stack, &tokens[position..], Action::Skip(lookahead));
+                    position += 1;
+                }
+                Recovery::Resume(skip_to) => {
+                    while position < skip_to {
+                        record_step(&mut trace, &stack, &tokens[position..], Action::Skip(tokens[position].clone()));
+                        position += 1;
+                    }
+                }
+                Recovery::Pop(skip_to) => {
+                    while position < skip_to {
+                        record_step(&mut trace, &stack, &tokens[position..], Action::Skip(tokens[position].clone()));
+                        position += 1;
+                    }
+                    record_step(&mut trace, &stack, &tokens[position..], Action::Pop(top));
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    (errors, trace)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use recovery::ErrorKind;
+
+    fn parse_errors(grammar: &Grammar, input: &[&str]) -> Vec<SyntaxError> {
+        let first = compute_first(grammar);
+        let follow = compute_follow(grammar, "S");
+        let table = build_parsing_table(grammar, &first, &follow);
+        parse(grammar, &table, &first, &follow, "S", input, &RecoveryOptions::default()).0
+    }
+
+    #[test]
+    fn test_end_of_input_diagnostics() {
+        let grammar: Grammar = [
+            ("S".to_string(), vec![vec!["a".to_string(), "B".to_string()]]),
+            ("B".to_string(), vec![vec!["b".to_string()], vec!["".to_string()]]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert!(parse_errors(&grammar, &["a"]).is_empty());
+
+        let errors = parse_errors(&grammar, &[]);
+        assert_eq!(errors[0].kind, ErrorKind::UnexpectedEnd);
+        assert_eq!(errors[0].message, "unexpected end of input, expected a");
+
+        let errors = parse_errors(&grammar, &["a", "b", "b"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::TrailingInput);
+        assert_eq!(errors[0].position, 2);
+
+        // The trace of recovered input ends in reject, not accept
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "S");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let last_action = |input: &[&str]| {
+            let (_, trace) = parse(&grammar, &table, &first, &follow, "S", input, &RecoveryOptions::default());
+            trace.last().unwrap().action.clone()
+        };
+        assert_eq!(last_action(&["a", "b", "b"]), Action::Reject);
+        assert_eq!(last_action(&["a", "b"]), Action::Accept);
+
+        // A nullable start symbol accepts the empty input
+        let nullable: Grammar = [("S".to_string(), vec![vec!["a".to_string(), "S".to_string()], vec!["".to_string()]])]
+            .into_iter()
+            .collect();
+        assert!(parse_errors(&nullable, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_literal_end_marker() {
+        let grammar: Grammar = [(
+            "S".to_string(),
+            vec![vec!["a".to_string(), "S".to_string()], vec!["b".to_string()]],
+        )]
+        .into_iter()
+        .collect();
+
+        // `b` alone is a sentence, but the `$` in the input must not end it early
+        let errors = parse_errors(&grammar, &["b", "$", "a"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::EndMarkerInInput);
+        assert_eq!(errors[0].position, 1);
+        assert_eq!(errors[0].to_string(), "error at token 2 ('$'): $ is reserved for the end of input");
+
+        let errors = parse_errors(&grammar, &["b", "a"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::TrailingInput);
+    }
+}
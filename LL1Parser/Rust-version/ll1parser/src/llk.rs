@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::transform::production_to_string;
+use crate::Grammar;
+
+// Sets of terminal strings of length at most k; a shorter string either ends in `$` or is
+// the complete yield of a symbol, the empty string standing for ε
+pub type KSets = HashMap<String, BTreeSet<Vec<String>>>;
+
+// Table of a strong LL(k) parser, keyed by non-terminal and k-token lookahead. The lookahead alone picks
+// the production, whatever was parsed before; a full LL(k) parser would also take the left context into
+// account, so a grammar can be LL(k) without being strong LL(k) once k > 1
+pub type KTable = HashMap<(String, Vec<String>), Vec<Vec<String>>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KConflict {
+    pub non_terminal: String,
+    pub lookahead: Vec<String>,
+    pub productions: Vec<Vec<String>>,
+}
+
+// k-bounded concatenation: every string of `left` followed by every string of `right`, cut to k symbols
+fn concat_k(left: &BTreeSet<Vec<String>>, right: &BTreeSet<Vec<String>>, k: usize) -> BTreeSet<Vec<String>> {
+    let mut result = BTreeSet::new();
+    for l in left {
+        // Strings that are already complete, or that ran into the end marker, cannot grow
+        if l.len() >= k || l.last().map(String::as_str) == Some("$") {
+            result.insert(l[..l.len().min(k)].to_vec());
+            continue;
+        }
+        for r in right {
+            let mut joined = l.clone();
+            joined.extend(r.iter().take(k - l.len()).cloned());
+            result.insert(joined);
+        }
+    }
+    result
+}
+
+fn epsilon_set() -> BTreeSet<Vec<String>> {
+    BTreeSet::from([Vec::new()])
+}
+
+// FIRST_k of a sentential form given FIRST_k of the non-terminals
+pub fn first_k_of(symbols: &[String], first_k: &KSets, k: usize) -> BTreeSet<Vec<String>> {
+    let mut result = epsilon_set();
+    for symbol in symbols.iter().filter(|symbol| !symbol.is_empty()) {
+        let symbol_set = match first_k.get(symbol) {
+            Some(set) => set.clone(),
+            None => BTreeSet::from([vec![symbol.clone()]]),
+        };
+        result = concat_k(&result, &symbol_set, k);
+        if result.iter().all(|s| s.len() >= k) {
+            break;
+        }
+    }
+    result
+}
+
+pub fn compute_first_k(grammar: &Grammar, k: usize) -> KSets {
+    let mut first_k: KSets = grammar.keys().map(|nt| (nt.clone(), BTreeSet::new())).collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                let set = first_k_of(production, &first_k, k);
+                let entry = first_k.get_mut(non_terminal).unwrap();
+                let len_before = entry.len();
+                entry.extend(set);
+                if entry.len() != len_before {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    first_k
+}
+
+pub fn compute_follow_k(grammar: &Grammar, first_k: &KSets, start_symbol: &str, k: usize) -> KSets {
+    let mut follow_k: KSets = grammar.keys().map(|nt| (nt.clone(), BTreeSet::new())).collect();
+    follow_k.get_mut(start_symbol).unwrap().insert(vec!["$".to_string()]);
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                for (i, symbol) in production.iter().enumerate() {
+                    if !grammar.contains_key(symbol) {
+                        continue;
+                    }
+                    let rest = first_k_of(&production[i + 1..], first_k, k);
+                    let set = concat_k(&rest, &follow_k[non_terminal], k);
+                    let entry = follow_k.get_mut(symbol).unwrap();
+                    let len_before = entry.len();
+                    entry.extend(set);
+                    if entry.len() != len_before {
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    follow_k
+}
+
+// Build the strong LL(k) table: A -> α goes under every string of FIRST_k(α FOLLOW_k(A))
+pub fn build_parsing_table_k(grammar: &Grammar, start_symbol: &str, k: usize) -> (KTable, Vec<KConflict>) {
+    let first_k = compute_first_k(grammar, k);
+    let follow_k = compute_follow_k(grammar, &first_k, start_symbol, k);
+    let mut table: KTable = HashMap::new();
+
+    for (non_terminal, productions) in grammar.iter() {
+        for production in productions {
+            let lookaheads = concat_k(&first_k_of(production, &first_k, k), &follow_k[non_terminal], k);
+            for lookahead in lookaheads {
+                let entry = table.entry((non_terminal.clone(), lookahead)).or_default();
+                if !entry.contains(production) {
+                    entry.push(production.clone());
+                }
+            }
+        }
+    }
+
+    let mut conflicts: Vec<KConflict> = table
+        .iter()
+        .filter(|(_, productions)| productions.len() > 1)
+        .map(|((non_terminal, lookahead), productions)| KConflict {
+            non_terminal: non_terminal.clone(),
+            lookahead: lookahead.clone(),
+            productions: productions.clone(),
+        })
+        .collect();
+    conflicts.sort_by(|a, b| (&a.non_terminal, &a.lookahead).cmp(&(&b.non_terminal, &b.lookahead)));
+
+    (table, conflicts)
+}
+
+// Local FOLLOW_k sets in the sense of Aho and Ullman: every lookahead set L that can follow an occurrence of a
+// non-terminal in some leftmost derivation, starting from {$} after the start symbol. There are finitely many,
+// since each is a set of strings of at most k terminals
+pub fn local_follow_k(grammar: &Grammar, first_k: &KSets, start_symbol: &str, k: usize) -> BTreeSet<(String, BTreeSet<Vec<String>>)> {
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![(start_symbol.to_string(), BTreeSet::from([vec!["$".to_string()]]))];
+    while let Some((non_terminal, follow)) = pending.pop() {
+        if !seen.insert((non_terminal.clone(), follow.clone())) {
+            continue;
+        }
+        for production in &grammar[&non_terminal] {
+            for (i, symbol) in production.iter().enumerate() {
+                if grammar.contains_key(symbol) {
+                    pending.push((symbol.clone(), concat_k(&first_k_of(&production[i + 1..], first_k, k), &follow, k)));
+                }
+            }
+        }
+    }
+    seen
+}
+
+// Conflicts of the LL(k) tables T[A, L], one per non-terminal and local follow set: the grammar is LL(k) when,
+// for every such pair, the alternatives of A have disjoint FIRST_k(α L)
+pub fn find_conflicts_k(grammar: &Grammar, start_symbol: &str, k: usize) -> Vec<KConflict> {
+    let first_k = compute_first_k(grammar, k);
+    let mut conflicts = Vec::new();
+
+    for (non_terminal, follow) in local_follow_k(grammar, &first_k, start_symbol, k) {
+        let mut table: BTreeMap<Vec<String>, Vec<Vec<String>>> = BTreeMap::new();
+        for production in &grammar[&non_terminal] {
+            for lookahead in concat_k(&first_k_of(production, &first_k, k), &follow, k) {
+                let entry = table.entry(lookahead).or_default();
+                if !entry.contains(production) {
+                    entry.push(production.clone());
+                }
+            }
+        }
+        for (lookahead, productions) in table.into_iter().filter(|(_, productions)| productions.len() > 1) {
+            let conflict = KConflict { non_terminal: non_terminal.clone(), lookahead, productions };
+            if !conflicts.contains(&conflict) {
+                conflicts.push(conflict);
+            }
+        }
+    }
+    conflicts.sort_by(|a, b| (&a.non_terminal, &a.lookahead).cmp(&(&b.non_terminal, &b.lookahead)));
+
+    conflicts
+}
+
+// Smallest k up to `max_k` for which the grammar is strong LL(k) and LL(k) respectively, along with the
+// conflicts found for each smaller k. Strong LL(k) implies LL(k), so `full` is never larger than `strong`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimalK {
+    pub strong: Option<usize>,
+    pub full: Option<usize>,
+    pub strong_failures: Vec<(usize, Vec<KConflict>)>,
+    pub full_failures: Vec<(usize, Vec<KConflict>)>,
+}
+
+pub fn find_minimal_k(grammar: &Grammar, start_symbol: &str, max_k: usize) -> MinimalK {
+    let mut minimal = MinimalK { strong: None, full: None, strong_failures: Vec::new(), full_failures: Vec::new() };
+    for k in 1..=max_k {
+        if minimal.strong.is_none() {
+            let (_, conflicts) = build_parsing_table_k(grammar, start_symbol, k);
+            if conflicts.is_empty() {
+                minimal.strong = Some(k);
+            } else {
+                minimal.strong_failures.push((k, conflicts));
+            }
+        }
+        if minimal.full.is_none() {
+            let conflicts = find_conflicts_k(grammar, start_symbol, k);
+            if conflicts.is_empty() {
+                minimal.full = Some(k);
+            } else {
+                minimal.full_failures.push((k, conflicts));
+            }
+        }
+        if minimal.strong.is_some() && minimal.full.is_some() {
+            break;
+        }
+    }
+    minimal
+}
+
+pub fn conflict_to_string(conflict: &KConflict) -> String {
+    let productions: Vec<String> = conflict
+        .productions
+        .iter()
+        .map(|production| format!("{} -> {}", conflict.non_terminal, production_to_string(production)))
+        .collect();
+    format!(
+        "M[{}, {}]: {}",
+        conflict.non_terminal,
+        conflict.lookahead.join(" "),
+        productions.join(" / ")
+    )
+}
+
+// Next k tokens of the input, stopping after the end marker
+fn lookahead_k(tokens: &[String], position: usize, k: usize) -> Vec<String> {
+    let mut lookahead = Vec::new();
+    for token in tokens[position..].iter().take(k) {
+        lookahead.push(token.clone());
+        if token == "$" {
+            break;
+        }
+    }
+    lookahead
+}
+
+// Table-driven strong LL(k) parser without error recovery
+pub fn parse_k(grammar: &Grammar, table: &KTable, start_symbol: &str, k: usize, input: &[&str]) -> Result<(), String> {
+    let mut stack = vec!["$".to_string(), start_symbol.to_string()];
+    let mut tokens: Vec<String> = input.iter().map(|token| token.to_string()).collect();
+    tokens.push("$".to_string());
+    let mut position = 0;
+
+    while let Some(top) = stack.pop() {
+        let lookahead = lookahead_k(&tokens, position, k);
+        if grammar.contains_key(&top) {
+            let production = table
+                .get(&(top.clone(), lookahead.clone()))
+                .and_then(|productions| productions.first())
+                .ok_or_else(|| format!("no production for ({}, {})", top, lookahead.join(" ")))?;
+            for symbol in production.iter().rev().filter(|symbol| !symbol.is_empty()) {
+                stack.push(symbol.clone());
+            }
+        } else if top == tokens[position] {
+            position += 1;
+        } else {
+            return Err(format!("expected {}, found {}", top, tokens[position]));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(rules: &[(&str, &[&[&str]])]) -> Grammar {
+        rules
+            .iter()
+            .map(|(lhs, alternatives)| {
+                let productions = alternatives
+                    .iter()
+                    .map(|alt| alt.iter().map(|symbol| symbol.to_string()).collect())
+                    .collect();
+                (lhs.to_string(), productions)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_first_k_stops_at_end_marker() {
+        let g = grammar(&[("S", &[&["a", "A"]]), ("A", &[&["b"], &[""]])]);
+        let first_k = compute_first_k(&g, 3);
+        let follow_k = compute_follow_k(&g, &first_k, "S", 3);
+
+        let expected: BTreeSet<Vec<String>> = [vec!["a", "b"], vec!["a"]]
+            .iter()
+            .map(|s| s.iter().map(|x| x.to_string()).collect())
+            .collect();
+        assert_eq!(first_k["S"], expected);
+        assert_eq!(follow_k["A"], BTreeSet::from([vec!["$".to_string()]]));
+    }
+
+    #[test]
+    fn test_minimal_k() {
+        let g = grammar(&[
+            ("S", &[&["A", "x"], &["A", "y"]]),
+            ("A", &[&["a", "A"], &["b"]]),
+        ]);
+        // A can be arbitrarily long, so no fixed lookahead separates the two alternatives of S
+        let minimal = find_minimal_k(&g, "S", 3);
+        assert_eq!((minimal.strong, minimal.full), (None, None));
+        assert_eq!((minimal.strong_failures.len(), minimal.full_failures.len()), (3, 3));
+
+        let g = grammar(&[("S", &[&["a", "b", "c"], &["a", "b", "d"]])]);
+        let minimal = find_minimal_k(&g, "S", 3);
+        assert_eq!((minimal.strong, minimal.full), (Some(3), Some(3)));
+        assert_eq!(minimal.strong_failures[0].1[0].lookahead, vec!["a".to_string()]);
+
+        // LL(2), but not strong LL(k) for any k: after `a` the lookahead `b a` means A -> b, after `b` it means
+        // A -> ε, and a strong table cannot tell the two apart
+        let g = grammar(&[("S", &[&["a", "A", "a"], &["b", "A", "b", "a"]]), ("A", &[&["b"], &[""]])]);
+        let minimal = find_minimal_k(&g, "S", 3);
+        assert_eq!((minimal.strong, minimal.full), (None, Some(2)));
+        assert_eq!(minimal.strong_failures[1].1[0].lookahead, vec!["b".to_string(), "a".to_string()]);
+        // With one token, `b` follows A after S -> b A b a and also starts A -> b
+        let to_strings = |symbols: &[&str]| symbols.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            minimal.full_failures,
+            vec![(
+                1,
+                vec![KConflict {
+                    non_terminal: "A".to_string(),
+                    lookahead: to_strings(&["b"]),
+                    productions: vec![to_strings(&["b"]), to_strings(&[""])],
+                }]
+            )]
+        );
+    }
+}
@@ -1,10 +1,31 @@
-
-
-use std::collections::{HashMap, HashSet};
-
-type Grammar = HashMap<String, Vec<Vec<String>>>;
+use ll1parser::derivation::{productions_from_trace, render_derivation, sentential_forms, DerivationFormat};
+use ll1parser::display::{render_first_follow, render_parsing_table, TableFormat};
+use ll1parser::generate::{enumerate_sentences, near_misses, sample_sentence, Rng, SampleOptions};
+use ll1parser::llk::{build_parsing_table_k, conflict_to_string, find_minimal_k, parse_k};
+use ll1parser::recovery::{RecoveryOptions, RecoveryStrategy};
+use ll1parser::source::{tokenize, TerminalMap};
+use ll1parser::trace::{check_trace, render_trace};
+use ll1parser::transform::{grammar_to_string, prepare_for_ll1};
+use ll1parser::{build_parsing_table, collect_table_entries, compute_first, compute_follow, parse, table_from_entries, Grammar};
 
 fn main() {
+    // Output format for the sets, the table and the trace: text (default), markdown, latex, csv or html
+    let format = match std::env::args().nth(1) {
+        Some(name) => TableFormat::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown format '{}', expected text, markdown, latex, csv or html", name);
+            std::process::exit(1);
+        }),
+        None => TableFormat::Text,
+    };
+    // How derivations are printed: text (default), typst or latex
+    let derivation_format = match std::env::args().nth(2) {
+        Some(name) => DerivationFormat::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown derivation format '{}', expected text, typst or latex", name);
+            std::process::exit(1);
+        }),
+        None => DerivationFormat::Text,
+    };
+
     let grammar: Grammar = [
         ("E".to_string(), vec![vec!["T".to_string(), "E'".to_string()]]),
         ("E'".to_string(), vec![vec!["ADD_SUB".to_string(), "T".to_string(), "E'".to_string()], vec!["".to_string()]]),
@@ -18,256 +39,133 @@ fn main() {
     let first = compute_first(&grammar);
     let follow = compute_follow(&grammar, "E");
 
-    println!("First set: {:?}", first);
-    println!("Follow set: {:?}", follow);
+    println!("First and Follow sets:\n{}", render_first_follow(&grammar, &first, &follow, "E", format));
 
-    let parsing_table = build_parsing_table(&grammar, &first, &follow);
-    println!("Parsing table: {:?}", parsing_table);
+    let table_entries = collect_table_entries(&grammar, &first, &follow);
+    let parsing_table = table_from_entries(&table_entries);
+    println!("Parsing table:\n{}", render_parsing_table(&grammar, &table_entries, "E", format));
 
     let input_str = "number mod number div number * number";
-    let input: Vec<&str> = input_str.trim().split_whitespace().collect();
-    let success = parse(&grammar, &parsing_table, &follow, "E", &input);
-    
-    println!("Parsing result: {}", if success { "Success" } else { "Failure" });
-}
-
-// Compute the First set for the grammar
-fn compute_first(grammar: &Grammar) -> HashMap<String, HashSet<String>> {
-    let mut first: HashMap<String, HashSet<String>> = HashMap::new();
-
-    for non_terminal in grammar.keys() {
-        first.insert(non_terminal.clone(), HashSet::new());
+    let input: Vec<&str> = input_str.split_whitespace().collect();
+    let options = RecoveryOptions::default();
+    let (errors, trace) = parse(&grammar, &parsing_table, &first, &follow, "E", &input, &options);
+    println!("Parsing trace:\n{}", render_trace(&trace, format));
+    println!("Parsing result: {}", if errors.is_empty() { "Success" } else { "Failure" });
+    if errors.is_empty() {
+        match sentential_forms(&grammar, "E", &productions_from_trace(&trace)) {
+            Ok(forms) => println!("Leftmost derivation:\n{}", render_derivation(&forms, derivation_format)),
+            Err(message) => println!("Invalid derivation: {}", message),
+        }
     }
 
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for (non_terminal, productions) in grammar.iter() {
-            for production in productions {
-                if update_first_set(grammar, &mut first, non_terminal, production) {
-                    changed = true;
-                }
-            }
+    // An optional third argument names a file holding a hand-simulated trace of the same input
+    if let Some(path) = std::env::args().nth(3) {
+        match std::fs::read_to_string(&path) {
+            Ok(text) => match check_trace(&trace, &text) {
+                Ok(()) => println!("Trace in {} is correct", path),
+                Err(message) => println!("Trace in {} is wrong: {}", path, message),
+            },
+            Err(err) => eprintln!("Failed to read {}: {}", path, err),
         }
     }
 
-    first
-}
-
-// Update the First set for a given production
-fn update_first_set(
-    grammar: &Grammar,
-    first: &mut HashMap<String, HashSet<String>>,
-    non_terminal: &String,
-    production: &Vec<String>,
-) -> bool {
-    let mut changed = false;
-    for symbol in production {
-        if grammar.contains_key(symbol) {
-            let first_set = first.get(symbol).unwrap().clone();
-            let len_before = first.get(non_terminal).unwrap().len();
-            first.get_mut(non_terminal).unwrap().extend(first_set.clone());
-            let len_after = first.get(non_terminal).unwrap().len();
-            if len_before != len_after {
-                changed = true;
-            }
-            if !first_set.contains(&"".to_owned()) {
-                break;
-            }
-        } else {
-            let len_before = first.get(non_terminal).unwrap().len();
-            first.get_mut(non_terminal).unwrap().insert(symbol.clone());
-            let len_after = first.get(non_terminal).unwrap().len();
-            if len_before != len_after {
-                changed = true;
-            }
-            break;
+    // Real source text through Newlexer, mapped to the terminals of the grammar
+    let source = "12 + (3 % 4) * 5 / 2";
+    match tokenize(source, &TerminalMap::expression()) {
+        Ok(tokens) => {
+            let input: Vec<&str> = tokens.iter().map(|token| token.terminal.as_str()).collect();
+            let (errors, trace) = parse(&grammar, &parsing_table, &first, &follow, "E", &input, &options);
+            println!("Parsing trace for {}:\n{}", source, render_trace(&trace, format));
+            println!("Parsing result: {}", if errors.is_empty() { "Success" } else { "Failure" });
         }
+        Err(message) => println!("Lexical error: {}", message),
     }
-    changed
-}
 
-// Compute the Follow set for the grammar
-fn compute_follow(grammar: &Grammar, start_symbol: &str) -> HashMap<String, HashSet<String>> {
-    let mut follow: HashMap<String, HashSet<String>> = HashMap::new();
+    // A left-recursive grammar has to be transformed before an LL(1) table can be built for it
+    let left_recursive: Grammar = [
+        ("E".to_string(), vec![vec!["E".to_string(), "+".to_string(), "T".to_string()], vec!["T".to_string()]]),
+        ("T".to_string(), vec![vec!["T".to_string(), "*".to_string(), "F".to_string()], vec!["F".to_string()]]),
+        ("F".to_string(), vec![vec!["(".to_string(), "E".to_string(), ")".to_string()], vec!["number".to_string()]]),
+    ].iter().cloned().collect();
 
-    for non_terminal in grammar.keys() {
-        follow.insert(non_terminal.clone(), HashSet::new());
+    let (transformed, introduced) = prepare_for_ll1(&left_recursive, "E");
+    println!("Original grammar:\n{}", grammar_to_string(&left_recursive, "E"));
+    println!("Transformed grammar:\n{}", grammar_to_string(&transformed, "E"));
+    for (new_non_terminal, origin) in &introduced {
+        println!("Introduced {} (from {})", new_non_terminal, origin);
     }
 
-    follow.get_mut(start_symbol).unwrap().insert("$".to_owned());
-
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for (non_terminal, productions) in grammar.iter() {
-            for production in productions {
-                if update_follow_set(grammar, &mut follow, non_terminal, production) {
-                    changed = true;
-                }
-            }
+    let first = compute_first(&transformed);
+    let follow = compute_follow(&transformed, "E");
+    let parsing_table = build_parsing_table(&transformed, &first, &follow);
+    let input: Vec<&str> = "number + number * number".split_whitespace().collect();
+    let (errors, trace) = parse(&transformed, &parsing_table, &first, &follow, "E", &input, &options);
+    println!("Parsing trace:\n{}", render_trace(&trace, format));
+
+    println!("Parsing result: {}", if errors.is_empty() { "Success" } else { "Failure" });
+
+    // Erroneous input: every syntax error is reported, not just the first one
+    let input: Vec<&str> = "number + * number ) ( number number".split_whitespace().collect();
+    for strategy in [RecoveryStrategy::PanicMode, RecoveryStrategy::PhraseLevel] {
+        let options = RecoveryOptions { strategy, ..RecoveryOptions::default() };
+        let (errors, trace) = parse(&transformed, &parsing_table, &first, &follow, "E", &input, &options);
+        println!("Parsing trace ({:?}):\n{}", strategy, render_trace(&trace, format));
+        for error in &errors {
+            println!("{}", error);
         }
     }
 
-    follow
-}
-
-// Update the Follow set for a given production
-fn update_follow_set(
-    grammar: &Grammar,
-    follow: &mut HashMap<String, HashSet<String>>,
-    non_terminal: &String,
-    production: &Vec<String>,
-) -> bool {
-    let mut changed = false;
-    for (i, symbol) in production.iter().enumerate() {
-        if grammar.contains_key(symbol) {
-            let mut follow_set = follow.get(symbol).unwrap().clone();
-            if i + 1 < production.len() {
-                let next_symbol = &production[i + 1];
-                if grammar.contains_key(next_symbol) {
-                    let first_set = compute_first(grammar).get(next_symbol).unwrap().clone();
-                    follow_set.extend(first_set.iter().filter(|x| **x != "").cloned());
-                    if first_set.contains(&"".to_owned()) {
-                        follow_set.extend(follow.get(non_terminal).unwrap().clone());
-                    }
-                } else {
-                    follow_set.insert(next_symbol.clone());
-                }
-            } else {
-                follow_set.extend(follow.get(non_terminal).unwrap().clone());
-            }
-            let len_before = follow.get(symbol).unwrap().len();
-            follow.get_mut(symbol).unwrap().extend(follow_set.clone());
-            let len_after = follow.get(symbol).unwrap().len();
-            if len_before != len_after {
-                changed = true;
-            }
-        }
+    // Test inputs generated from the grammar: the shortest sentences, random ones, and near misses that the
+    // parser must reject
+    let sentences = enumerate_sentences(&grammar, "E", 3, 8);
+    for sentence in &sentences {
+        println!("Sentence: {}", sentence.join(" "));
     }
-    changed
-}
-
-// Build the parsing table for the grammar
-fn build_parsing_table(
-    grammar: &Grammar,
-    first: &HashMap<String, HashSet<String>>,
-    follow: &HashMap<String, HashSet<String>>
-) -> HashMap<(String, String), Vec<String>> {
-    let mut table: HashMap<(String, String), Vec<String>> = HashMap::new();
-
-    for (non_terminal, productions) in grammar.iter() {
-        for production in productions {
-            let first_set = compute_first_for_production(production, first);
-            for terminal in first_set.iter().filter(|&&ref x| x != "") {
-                table.insert((non_terminal.clone(), terminal.clone()), production.clone());
-            }
-            if first_set.contains(&"".to_owned()) {
-                for terminal in follow.get(non_terminal).unwrap() {
-                    table.insert((non_terminal.clone(), terminal.clone()), production.clone());
-                }
-            }
-        }
+    let mut rng = Rng::new(2024);
+    let sample_options = SampleOptions { max_depth: 6, ..SampleOptions::default() };
+    let samples: Vec<Vec<String>> = (0..5).filter_map(|_| sample_sentence(&grammar, "E", &sample_options, &mut rng)).collect();
+    let first = compute_first(&grammar);
+    let follow = compute_follow(&grammar, "E");
+    let parsing_table = build_parsing_table(&grammar, &first, &follow);
+    let quiet = RecoveryOptions { strategy: RecoveryStrategy::Abort, ..RecoveryOptions::default() };
+    let mut accepts = |sentence: &[String]| {
+        let input: Vec<&str> = sentence.iter().map(String::as_str).collect();
+        parse(&grammar, &parsing_table, &first, &follow, "E", &input, &quiet).0.is_empty()
+    };
+    for sample in &samples {
+        println!("Random sentence: {} ({})", sample.join(" "), if accepts(sample) { "accepted" } else { "rejected" });
+    }
+    for miss in near_misses(&grammar, &samples, 5, &mut rng, &mut accepts) {
+        println!("Near miss: {}", miss.join(" "));
     }
 
-    table
-}
+    // Assignment and call both start with `id`, so one token of lookahead is not enough
+    let statement: Grammar = [
+        ("S".to_string(), vec![vec!["id".to_string(), ":=".to_string(), "E".to_string()], vec!["id".to_string(), "(".to_string(), ")".to_string()]]),
+        ("E".to_string(), vec![vec!["id".to_string()], vec!["number".to_string()]]),
+    ].iter().cloned().collect();
 
-// Compute the First set for a given production
-fn compute_first_for_production(
-    production: &Vec<String>,
-    first: &HashMap<String, HashSet<String>>,
-) -> HashSet<String> {
-    let mut result = HashSet::new();
-    for symbol in production {
-        if first.contains_key(symbol) {
-            let first_set = first.get(symbol).unwrap();
-            result.extend(first_set.clone());
-            if !first_set.contains(&"".to_owned()) {
-                break;
-            }
-        } else {
-            result.insert(symbol.clone());
-            break;
+    let minimal = find_minimal_k(&statement, "S", 3);
+    for (k, conflicts) in &minimal.strong_failures {
+        println!("Not strong LL({}):", k);
+        for conflict in conflicts {
+            println!("  {}", conflict_to_string(conflict));
         }
     }
-    result
-}
-
-// LL(1) parser with panic mode error recovery
-fn parse(
-    grammar: &Grammar,
-    parsing_table: &HashMap<(String, String), Vec<String>>,
-    follow: &HashMap<String, HashSet<String>>,
-    start_symbol: &str,
-    input: &Vec<&str>
-) -> bool {
-    let mut stack = vec!["$".to_owned(), start_symbol.to_owned()];
-    let mut input_iter = input.iter();
-    let mut lookahead = input_iter.next().unwrap(); // Initialize with the first input symbol
-
-    println!("Initial stack: {:?}", stack);
-    println!("Initial input: {:?}", input);
-
-    while let Some(top) = stack.pop() {
-        println!("Stack top: {}", top);
-        
-        // If the top of the stack is a terminal
-        if !grammar.contains_key(&top) {
-            if &top == lookahead {
-                println!("Matched terminal: {}", lookahead);  // Stack top matches input symbol
-                
-                // Move to the next input symbol
-                if let Some(next_symbol) = input_iter.next() {
-                    lookahead = next_symbol;
-                } else {
-                    lookahead = &"$";  // End of input
-                }
-            } else {
-                println!("Error: Stack top does not match input ({} != {})", top, lookahead);
-                return false;
-            }
-        } else {
-            // If the top of the stack is a non-terminal, look up the parsing table
-            if let Some(production) = parsing_table.get(&(top.clone(), lookahead.to_string())) {
-                println!("Using production: {:?} -> {:?}", top, production);
-                if production == &vec!["".to_owned()] {
-                    println!("Empty production, skipping");
-                    continue;
-                }
-                // Push the production in reverse order onto the stack
-                for symbol in production.iter().rev() {
-                    if !symbol.is_empty() {
-                        stack.push(symbol.clone());
-                    }
-                }
-            } else {
-                println!("Error: No matching production for ({}, {})", top, lookahead);
-                // Panic mode error recovery: skip symbols until a synchronization point is found
-                while let Some(next_symbol) = input_iter.next() {
-                    lookahead = next_symbol;
-                    if follow.get(&top).unwrap().contains(*lookahead) {
-                        println!("Recovered at synchronization point: {}", lookahead);
-                        break;
-                    }
-                }
-                if !follow.get(&top).unwrap().contains(*lookahead) {
-                    println!("Error: Unable to recover, no synchronization point found");
-                    return false;
-                }
+    match minimal.full {
+        Some(k) => println!("Grammar is LL({})", k),
+        None => println!("Grammar is not LL(k) for any k up to 3"),
+    }
+    match minimal.strong {
+        Some(k) => {
+            println!("Grammar is strong LL({})", k);
+            let (table, _) = build_parsing_table_k(&statement, "S", k);
+            let input: Vec<&str> = "id ( )".split_whitespace().collect();
+            match parse_k(&statement, &table, "S", k, &input) {
+                Ok(()) => println!("Strong LL({}) parsing result: Success", k),
+                Err(message) => println!("Strong LL({}) parsing result: Failure ({})", k, message),
             }
         }
-        
-        println!("Current stack: {:?}", stack);
+        None => println!("Grammar is not strong LL(k) for any k up to 3"),
     }
-
-    // Parsing is successful if the stack is empty and the input symbol is the end symbol `$`
-    let success = lookahead == &"$";
-    if success {
-        println!("Parsing successful");
-    } else {
-        println!("Parsing failed");
-    }
-
-    success
 }
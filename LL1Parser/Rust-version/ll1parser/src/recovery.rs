@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// How the parser reacts to a syntax error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryStrategy {
+    // Stop at the first error
+    Abort,
+    // Discard input until a synchronising token, then pop (return from) or resume the non-terminal
+    PanicMode,
+    // First try to repair the input by deleting or inserting a single token, otherwise fall back to panic mode
+    PhraseLevel,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecoveryOptions {
+    pub strategy: RecoveryStrategy,
+    // Use FOLLOW(A) as synchronising tokens for A: seeing one of them pops A
+    pub synch_on_follow: bool,
+    // Use FIRST(A) as synchronising tokens for A: seeing one of them resumes parsing A
+    pub synch_on_first: bool,
+    // Additional tokens that always pop the non-terminal, e.g. statement terminators
+    pub extra_synch: HashSet<String>,
+    // Print each production, match and repair as the recursive parsers make them; the table-driven
+    // parser records a trace instead
+    pub verbose: bool,
+}
+
+impl Default for RecoveryOptions {
+    fn default() -> Self {
+        Self {
+            strategy: RecoveryStrategy::PanicMode,
+            synch_on_follow: true,
+            synch_on_first: true,
+            extra_synch: HashSet::new(),
+            verbose: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // A token that cannot appear where it is
+    UnexpectedToken,
+    // The input stopped while more was expected
+    UnexpectedEnd,
+    // Input left over once the start symbol has been parsed
+    TrailingInput,
+    // A `$` token in the input, which could not be told apart from the end marker
+    EndMarkerInInput,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    // Index of the offending token in the input; the end marker `$` is at index `input.len()`
+    pub position: usize,
+    pub token: String,
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl SyntaxError {
+    // `expected` describes what would have been accepted; running into `$` is an unexpected end of input
+    pub fn unexpected(position: usize, token: &str, expected: &str) -> Self {
+        let (kind, message) = if token == "$" {
+            (ErrorKind::UnexpectedEnd, format!("unexpected end of input, expected {}", expected))
+        } else {
+            (ErrorKind::UnexpectedToken, format!("expected {}, found {}", expected, token))
+        };
+        Self { position, token: token.to_string(), kind, message }
+    }
+
+    pub fn trailing(position: usize, token: &str) -> Self {
+        Self {
+            position,
+            token: token.to_string(),
+            kind: ErrorKind::TrailingInput,
+            message: format!("trailing input starting at {}", token),
+        }
+    }
+
+    // The input is checked for `$` before parsing starts, so a literal `$` is never read as the end of input
+    pub fn end_marker_in_input(input: &[&str]) -> Option<Self> {
+        input.iter().position(|token| *token == "$").map(|position| Self {
+            position,
+            token: "$".to_string(),
+            kind: ErrorKind::EndMarkerInInput,
+            message: "$ is reserved for the end of input".to_string(),
+        })
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::UnexpectedEnd => write!(f, "error at end of input: {}", self.message),
+            _ => write!(f, "error at token {} ('{}'): {}", self.position + 1, self.token, self.message),
+        }
+    }
+}
+
+// The lookaheads M[A, ·] has an entry for, to say what was expected instead of an erroneous token
+pub fn expected_terminals(non_terminal: &str, parsing_table: &HashMap<(String, String), Vec<String>>) -> String {
+    let mut terminals: Vec<&str> = parsing_table
+        .keys()
+        .filter(|(nt, _)| nt == non_terminal)
+        .map(|(_, terminal)| terminal.as_str())
+        .collect();
+    terminals.sort();
+    // The end marker goes last, spelled out
+    if let Some(index) = terminals.iter().position(|terminal| *terminal == "$") {
+        terminals.remove(index);
+        terminals.push("end of input");
+    }
+    match terminals.as_slice() {
+        [terminal] => terminal.to_string(),
+        _ => format!("one of {}", terminals.join(", ")),
+    }
+}
+
+// What to do with a non-terminal that has no table entry for the lookahead
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    // Delete the lookahead token and try again
+    Delete,
+    // Discard tokens until the lookahead is in FIRST(A), then keep parsing A
+    Resume(usize),
+    // Discard tokens until the lookahead is a synchronising token, then pop A
+    Pop(usize),
+}
+
+// Token at `position`, reading past the end of the input as the end marker
+pub fn token_at<'a>(tokens: &[&'a str], position: usize) -> &'a str {
+    tokens.get(position).copied().unwrap_or("$")
+}
+
+impl RecoveryOptions {
+    // Synchronising tokens on which A is popped
+    fn pop_set(&self, non_terminal: &str, follow: &HashMap<String, HashSet<String>>) -> HashSet<String> {
+        let mut synch = self.extra_synch.clone();
+        if self.synch_on_follow {
+            synch.extend(follow.get(non_terminal).cloned().unwrap_or_default());
+        }
+        synch
+    }
+
+    // Synchronising tokens on which parsing of A is resumed
+    fn resume_set(&self, non_terminal: &str, first: &HashMap<String, HashSet<String>>) -> HashSet<String> {
+        if !self.synch_on_first {
+            return HashSet::new();
+        }
+        first
+            .get(non_terminal)
+            .map(|set| set.iter().filter(|x| !x.is_empty()).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Decide how to recover from a missing entry M[A, tokens[position]]
+    pub fn recover_non_terminal(
+        &self,
+        non_terminal: &str,
+        tokens: &[&str],
+        position: usize,
+        parsing_table: &HashMap<(String, String), Vec<String>>,
+        first: &HashMap<String, HashSet<String>>,
+        follow: &HashMap<String, HashSet<String>>,
+    ) -> Recovery {
+        // Phrase level: a single stray token is deleted if A can start right after it
+        if self.strategy == RecoveryStrategy::PhraseLevel && token_at(tokens, position) != "$" {
+            let next = (non_terminal.to_string(), token_at(tokens, position + 1).to_string());
+            if parsing_table.contains_key(&next) {
+                return Recovery::Delete;
+            }
+        }
+
+        let pop_set = self.pop_set(non_terminal, follow);
+        let resume_set = self.resume_set(non_terminal, first);
+        let mut skip_to = position;
+        while token_at(tokens, skip_to) != "$" {
+            if pop_set.contains(token_at(tokens, skip_to)) {
+                return Recovery::Pop(skip_to);
+            }
+            // Resuming on the very token that failed would loop forever
+            if skip_to > position && resume_set.contains(token_at(tokens, skip_to)) {
+                return Recovery::Resume(skip_to);
+            }
+            skip_to += 1;
+        }
+        // Out of input: A can only be popped
+        Recovery::Pop(skip_to)
+    }
+
+    // Decide whether a terminal `expected` on top of the stack that does not match the lookahead is
+    // repaired by deleting the lookahead (true) or by assuming `expected` was left out (false)
+    pub fn delete_instead_of_insert(&self, expected: &str, tokens: &[&str], position: usize) -> bool {
+        self.strategy == RecoveryStrategy::PhraseLevel
+            && token_at(tokens, position) != "$"
+            && token_at(tokens, position + 1) == expected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::Action;
+    use crate::{build_parsing_table, compute_first, compute_follow, parse, Grammar};
+
+    fn expression_grammar() -> Grammar {
+        let symbols = |text: &str| text.split_whitespace().map(String::from).collect::<Vec<String>>();
+        [
+            ("E".to_string(), vec![symbols("T E'")]),
+            ("E'".to_string(), vec![symbols("+ T E'"), vec!["".to_string()]]),
+            ("T".to_string(), vec![symbols("F T'")]),
+            ("T'".to_string(), vec![symbols("* F T'"), vec!["".to_string()]]),
+            ("F".to_string(), vec![symbols("( E )"), symbols("id")]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    // The errors reported, then every recovery action with the input position it was taken at
+    fn recover(input: &str, options: &RecoveryOptions) -> (Vec<String>, Vec<String>) {
+        let grammar = expression_grammar();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "E");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let input: Vec<&str> = input.split_whitespace().collect();
+        let (errors, trace) = parse(&grammar, &table, &first, &follow, "E", &input, options);
+        let actions = trace
+            .iter()
+            .filter_map(|step| {
+                let position = input.len() + 1 - step.input.len();
+                match &step.action {
+                    Action::Skip(token) => Some(format!("skip {} at {}", token, position)),
+                    Action::Pop(non_terminal) => Some(format!("pop {} at {}", non_terminal, position)),
+                    Action::Insert(terminal) => Some(format!("insert {} at {}", terminal, position)),
+                    _ => None,
+                }
+            })
+            .collect();
+        (errors.iter().map(ToString::to_string).collect(), actions)
+    }
+
+    fn options(strategy: RecoveryStrategy) -> RecoveryOptions {
+        RecoveryOptions { strategy, ..RecoveryOptions::default() }
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_missing_close_paren() {
+        let error = strings(&["error at end of input: unexpected end of input, expected )"]);
+        assert_eq!(recover("( id + id", &options(RecoveryStrategy::Abort)), (error.clone(), vec![]));
+        for strategy in [RecoveryStrategy::PanicMode, RecoveryStrategy::PhraseLevel] {
+            assert_eq!(recover("( id + id", &options(strategy)), (error.clone(), strings(&["insert ) at 4"])));
+        }
+    }
+
+    #[test]
+    fn test_extra_operator() {
+        let error = strings(&["error at token 3 ('*'): expected one of (, id, found *"]);
+        assert_eq!(recover("id + * id", &options(RecoveryStrategy::Abort)), (error.clone(), vec![]));
+        // T resumes on the `id` in FIRST(T); without FIRST as synchronising set it is only popped at the end
+        assert_eq!(recover("id + * id", &options(RecoveryStrategy::PanicMode)), (error.clone(), strings(&["skip * at 2"])));
+        let no_first = RecoveryOptions { synch_on_first: false, ..RecoveryOptions::default() };
+        assert_eq!(recover("id + * id", &no_first), (error, strings(&["skip * at 2", "skip id at 3", "pop T at 4"])));
+
+        // `)` is in FOLLOW(F), so panic mode pops F there and the rest of the input is trailing; phrase level
+        // deletes the `)` because F can start with the `id` after it, and so does panic mode without FOLLOW
+        let expected = "error at token 3 (')'): expected one of (, id, found )";
+        assert_eq!(
+            recover("id * ) id + id", &options(RecoveryStrategy::PanicMode)),
+            (
+                strings(&[expected, "error at token 3 (')'): trailing input starting at )"]),
+                strings(&["pop F at 2", "skip ) at 2", "skip id at 3", "skip + at 4", "skip id at 5"])
+            )
+        );
+        assert_eq!(recover("id * ) id + id", &options(RecoveryStrategy::PhraseLevel)), (strings(&[expected]), strings(&["skip ) at 2"])));
+        let no_follow = RecoveryOptions { synch_on_follow: false, ..RecoveryOptions::default() };
+        assert_eq!(recover("id * ) id + id", &no_follow), (strings(&[expected]), strings(&["skip ) at 2"])));
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let error = strings(&["error at end of input: unexpected end of input, expected one of (, id"]);
+        assert_eq!(recover("id +", &options(RecoveryStrategy::Abort)), (error.clone(), vec![]));
+        for strategy in [RecoveryStrategy::PanicMode, RecoveryStrategy::PhraseLevel] {
+            assert_eq!(recover("id +", &options(strategy)), (error.clone(), strings(&["pop T at 2"])));
+        }
+    }
+
+    #[test]
+    fn test_extra_synchronising_tokens() {
+        let error = strings(&["error at token 2 ('id'): expected one of ), *, +, end of input, found id"]);
+        // Without FOLLOW(T') nothing stops the skipping before the end of the input
+        let no_follow = RecoveryOptions { synch_on_follow: false, ..RecoveryOptions::default() };
+        assert_eq!(
+            recover("id id + id", &no_follow),
+            (error.clone(), strings(&["skip id at 1", "skip + at 2", "skip id at 3", "pop T' at 4"]))
+        );
+        let extra = RecoveryOptions { extra_synch: HashSet::from(["+".to_string()]), ..no_follow };
+        assert_eq!(recover("id id + id", &extra), (error, strings(&["skip id at 1", "pop T' at 2"])));
+    }
+}
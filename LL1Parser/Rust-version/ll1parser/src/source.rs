@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use newlexer::lexer::Lexer;
+use newlexer::tokens::{Span, Token};
+
+// A token of real source text: the grammar terminal it stands for, the text it was read from and
+// where that text is
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceToken {
+    pub terminal: String,
+    pub lexeme: String,
+    pub span: Span,
+}
+
+// Which grammar terminal each kind of lexer token becomes, e.g. IntegerLiteral -> number
+#[derive(Default)]
+pub struct TerminalMap {
+    terminals: HashMap<String, String>,
+}
+
+impl TerminalMap {
+    pub fn new() -> Self {
+        Self {
+            terminals: HashMap::new(),
+        }
+    }
+
+    // `kind` is a Newlexer token kind as returned by `Token::kind`
+    pub fn map(&mut self, kind: &str, terminal: &str) -> &mut Self {
+        self.terminals.insert(kind.to_string(), terminal.to_string());
+        self
+    }
+
+    // Terminals of the built-in expression grammar; `/` and `%` stand for div and mod. Operands are
+    // integers, so a float literal has no terminal and is reported as a lexical error
+    pub fn expression() -> Self {
+        let mut map = Self::new();
+        map.map("IntegerLiteral", "number")
+            .map("Plus", "+")
+            .map("Minus", "-")
+            .map("Star", "*")
+            .map("Slash", "div")
+            .map("Percent", "mod")
+            .map("OpenParen", "(")
+            .map("CloseParen", ")");
+        map
+    }
+}
+
+// Text of `source` covered by `span`; lines and columns count from 1, columns in bytes as the lexer does
+fn slice_span(source: &str, span: &Span) -> String {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let offset = |(line, column): (usize, usize)| {
+        line_starts
+            .get(line - 1)
+            .map_or(source.len(), |start| (start + column - 1).min(source.len()))
+    };
+    source
+        .get(offset(span.start)..offset(span.end))
+        .unwrap_or_default()
+        .to_string()
+}
+
+// Run Newlexer over `source` and map its tokens to grammar terminals, dropping comments; the first
+// lexical error or unmapped token kind is reported with its position
+pub fn tokenize(source: &str, terminals: &TerminalMap) -> Result<Vec<SourceToken>, String> {
+    let mut lexer = Lexer::new(source.as_bytes());
+    let mut tokens = Vec::new();
+    while let Some((token, span)) = lexer.next_token_with_span() {
+        let (line, column) = span.start;
+        let kind = token.kind();
+        match token {
+            Token::Comment(_) => continue,
+            Token::Error(message) => return Err(format!("{}:{}: {}", line, column, message)),
+            _ => {}
+        }
+        let terminal = terminals
+            .terminals
+            .get(kind)
+            .ok_or_else(|| format!("{}:{}: no terminal for token {}", line, column, kind))?;
+        tokens.push(SourceToken {
+            terminal: terminal.clone(),
+            lexeme: slice_span(source, &span),
+            span,
+        });
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_keeps_lexemes_and_spans() {
+        let tokens = tokenize("12 + (3 % 4)\n  / 56", &TerminalMap::expression()).unwrap();
+        let terminals: Vec<&str> = tokens.iter().map(|token| token.terminal.as_str()).collect();
+        assert_eq!(terminals, vec!["number", "+", "(", "number", "mod", "number", ")", "div", "number"]);
+
+        assert_eq!(tokens[0].lexeme, "12");
+        assert_eq!(tokens[0].span, Span { start: (1, 1), end: (1, 3) });
+        assert_eq!(tokens[8].lexeme, "56");
+        assert_eq!(tokens[8].span, Span { start: (2, 5), end: (2, 7) });
+
+        assert_eq!(
+            tokenize("1 + x", &TerminalMap::expression()),
+            Err("1:5: no terminal for token Identifier".to_string())
+        );
+        // Operands are integers, so a float literal is not mapped to `number`
+        assert_eq!(
+            tokenize("1.5 * 2", &TerminalMap::expression()),
+            Err("1:1: no terminal for token FloatLiteral".to_string())
+        );
+    }
+}
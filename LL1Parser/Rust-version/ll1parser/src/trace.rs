@@ -0,0 +1,219 @@
+use crate::display::{escape_symbol, format_production, render_grid, Cell, TableFormat};
+
+// What the table-driven parser did in one step
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    // Replace the non-terminal on top of the stack by the right-hand side of a production
+    Expand(String, Vec<String>),
+    // Pop a terminal that matches the lookahead and advance the input
+    Match(String),
+    // Panic mode: discard the lookahead token
+    Skip(String),
+    // Panic mode: pop the non-terminal after the input has been synchronised
+    Pop(String),
+    // Pop a terminal that does not match, as if it had been inserted into the input
+    Insert(String),
+    Error(String),
+    Accept,
+    // The stack and the input both reached `$`, but errors were recovered from on the way
+    Reject,
+}
+
+// One row of the classic stack / input / action table, recorded before the action is taken
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    pub step: usize,
+    // Bottom of the stack first, so the top is the rightmost symbol
+    pub stack: Vec<String>,
+    // Remaining input including the end marker `$`
+    pub input: Vec<String>,
+    pub action: Action,
+}
+
+fn format_action(action: &Action, format: TableFormat) -> String {
+    match action {
+        Action::Expand(non_terminal, production) => format_production(non_terminal, production, format),
+        Action::Match(terminal) => format!("match {}", escape_symbol(terminal, format)),
+        Action::Skip(terminal) => format!("skip {}", escape_symbol(terminal, format)),
+        Action::Pop(non_terminal) => format!("pop {}", escape_symbol(non_terminal, format)),
+        Action::Insert(terminal) => format!("insert {}", escape_symbol(terminal, format)),
+        Action::Error(message) => format!("error: {}", escape_symbol(message, format)),
+        Action::Accept => "accept".to_string(),
+        Action::Reject => "reject".to_string(),
+    }
+}
+
+fn format_symbols(symbols: &[String], format: TableFormat) -> String {
+    symbols
+        .iter()
+        .map(|symbol| escape_symbol(symbol, format))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Render the trace as a step / stack / input / action table
+pub fn render_trace(trace: &[TraceStep], format: TableFormat) -> String {
+    let mut rows: Vec<Vec<Cell>> = vec![vec![
+        ("Step".to_string(), false),
+        ("Stack".to_string(), false),
+        ("Input".to_string(), false),
+        ("Action".to_string(), false),
+    ]];
+    for step in trace {
+        let is_error = !matches!(step.action, Action::Expand(..) | Action::Match(_) | Action::Accept);
+        rows.push(vec![
+            (step.step.to_string(), false),
+            (format_symbols(&step.stack, format), false),
+            (format_symbols(&step.input, format), false),
+            (format_action(&step.action, format), is_error),
+        ]);
+    }
+    render_grid(&rows, format)
+}
+
+// Collapse whitespace, drop Markdown escapes and emphasis and accept the usual ways of writing an arrow,
+// so cosmetic differences do not count
+fn normalise(cell: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = cell.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek().is_some_and(char::is_ascii_punctuation) => unescaped.extend(chars.next()),
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+        .replace('→', "->")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+// Split a row at the `|` that separate its cells; an escaped `\|` is part of a cell, as `render_trace`
+// writes a `|` terminal in text and Markdown
+fn split_row(line: &str) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                cells.push(&line[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    cells.push(&line[start..]);
+    cells
+}
+
+// Read the stack / input / action columns of a hand-written trace. Rows are `|`-separated as in the
+// text and Markdown renderings; a leading step number, the header and separator rows are ignored.
+pub fn read_trace(text: &str) -> Vec<[String; 3]> {
+    let mut rows = Vec::new();
+    for line in text.lines() {
+        let mut cells: Vec<String> = split_row(line.trim()).into_iter().map(normalise).collect();
+        // The outer pipes of a Markdown row leave an empty cell at either end
+        if line.trim().starts_with('|') {
+            cells.remove(0);
+        }
+        if cells.len() > 3 && cells.last().is_some_and(String::is_empty) {
+            cells.pop();
+        }
+        if cells.len() == 4 {
+            cells.remove(0);
+        }
+        if cells.len() != 3 || cells[0].eq_ignore_ascii_case("stack") || cells[0].starts_with('-') {
+            continue;
+        }
+        rows.push([cells[0].clone(), cells[1].clone(), cells[2].clone()]);
+    }
+    rows
+}
+
+// Check a hand-simulated trace against the parser's own, reporting the first step that differs
+pub fn check_trace(expected: &[TraceStep], text: &str) -> Result<(), String> {
+    let actual = read_trace(text);
+    for (idx, step) in expected.iter().enumerate() {
+        // Rendered and then read back the way a hand-written row is, so escapes compare as what they stand for
+        let wanted = [
+            normalise(&format_symbols(&step.stack, TableFormat::Markdown)),
+            normalise(&format_symbols(&step.input, TableFormat::Markdown)),
+            normalise(&format_action(&step.action, TableFormat::Markdown)),
+        ];
+        match actual.get(idx) {
+            None => return Err(format!("Trace stops before step {} ({})", step.step, wanted[2])),
+            Some(row) => {
+                for (column, (got, want)) in ["stack", "input", "action"].iter().zip(row.iter().zip(wanted.iter())) {
+                    if got != want {
+                        return Err(format!(
+                            "Step {}: {} is '{}', expected '{}'",
+                            step.step, column, got, want
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    if actual.len() > expected.len() {
+        return Err(format!("Trace has {} extra step(s) after the last one", actual.len() - expected.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::RecoveryOptions;
+    use crate::{build_parsing_table, compute_first, compute_follow, parse, Grammar};
+
+    // E -> id T, T -> | id T | * id T | ε: `|` and `*` are terminals both renderings have to escape
+    fn trace(input: &str) -> Vec<TraceStep> {
+        let symbols = |text: &str| text.split_whitespace().map(String::from).collect::<Vec<String>>();
+        let grammar: Grammar = [
+            ("E".to_string(), vec![symbols("id T")]),
+            ("T".to_string(), vec![symbols("| id T"), symbols("* id T"), vec!["".to_string()]]),
+        ]
+        .into_iter()
+        .collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "E");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let input: Vec<&str> = input.split_whitespace().collect();
+        parse(&grammar, &table, &first, &follow, "E", &input, &RecoveryOptions::default()).1
+    }
+
+    #[test]
+    fn test_rendered_trace_checks() {
+        for input in ["id | id * id", "id | | id id"] {
+            let steps = trace(input);
+            for format in [TableFormat::Text, TableFormat::Markdown] {
+                assert_eq!(check_trace(&steps, &render_trace(&steps, format)), Ok(()), "{} as {:?}", input, format);
+            }
+        }
+    }
+
+    #[test]
+    fn test_escaped_pipes_stay_in_their_cell() {
+        assert_eq!(
+            read_trace("| 3 | $ T id \\| | \\| id \\* id $ | match \\| |"),
+            vec![["$ T id |".to_string(), "| id * id $".to_string(), "match |".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_wrong_trace() {
+        let steps = trace("id | id");
+        let text = render_trace(&steps, TableFormat::Text);
+        let wrong = text.replacen("match \\|", "match id", 1);
+        assert_eq!(check_trace(&steps, &wrong), Err("Step 4: action is 'match id', expected 'match |'".to_string()));
+        let short: Vec<&str> = text.lines().take(4).collect();
+        assert!(check_trace(&steps, &short.join("\n")).unwrap_err().starts_with("Trace stops before step 3"));
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{compute_first, Grammar};
+
+// Pick a name for a new non-terminal derived from `base` that is not used yet (E -> E', E'' ...)
+fn fresh_non_terminal(grammar: &Grammar, base: &str) -> String {
+    let mut name = format!("{}'", base);
+    while grammar.contains_key(&name) {
+        name.push('\'');
+    }
+    name
+}
+
+fn is_epsilon(production: &[String]) -> bool {
+    production.iter().all(|symbol| symbol.is_empty())
+}
+
+fn epsilon() -> Vec<String> {
+    vec!["".to_string()]
+}
+
+// Append `tail` to `head`, treating ε on either side as the empty string
+fn concat(head: &[String], tail: &[String]) -> Vec<String> {
+    let result: Vec<String> = head
+        .iter()
+        .chain(tail.iter())
+        .filter(|symbol| !symbol.is_empty())
+        .cloned()
+        .collect();
+    if result.is_empty() {
+        epsilon()
+    } else {
+        result
+    }
+}
+
+// Order in which non-terminals are processed: start symbol first, the rest alphabetically
+pub fn ordered_non_terminals(grammar: &Grammar, start_symbol: &str) -> Vec<String> {
+    let mut order: Vec<String> = grammar
+        .keys()
+        .filter(|nt| nt.as_str() != start_symbol)
+        .cloned()
+        .collect();
+    order.sort();
+    if grammar.contains_key(start_symbol) {
+        order.insert(0, start_symbol.to_string());
+    }
+    order
+}
+
+fn nullable_non_terminals(grammar: &Grammar) -> HashSet<String> {
+    compute_first(grammar)
+        .into_iter()
+        .filter(|(_, first)| first.contains(""))
+        .map(|(nt, _)| nt)
+        .collect()
+}
+
+// Non-terminals a sentential form starting with `symbols` can start with once nullable ones have vanished
+fn leading_non_terminals<'a>(grammar: &Grammar, nullable: &HashSet<String>, symbols: &'a [String]) -> Vec<&'a String> {
+    let mut leading = Vec::new();
+    for symbol in symbols.iter().filter(|symbol| !symbol.is_empty()) {
+        if !grammar.contains_key(symbol) {
+            break;
+        }
+        leading.push(symbol);
+        if !nullable.contains(symbol) {
+            break;
+        }
+    }
+    leading
+}
+
+// Non-terminals that can appear as the leftmost symbol of a sentential form derived from each non-terminal,
+// looking past nullable prefixes: with B -> ε, A -> B A x is left recursive too
+fn left_corners(grammar: &Grammar) -> HashMap<String, HashSet<String>> {
+    let nullable = nullable_non_terminals(grammar);
+    let mut corners: HashMap<String, HashSet<String>> = grammar
+        .iter()
+        .map(|(nt, productions)| {
+            let direct = productions
+                .iter()
+                .flat_map(|production| leading_non_terminals(grammar, &nullable, production))
+                .cloned()
+                .collect();
+            (nt.clone(), direct)
+        })
+        .collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for nt in grammar.keys() {
+            let reachable: HashSet<String> = corners[nt]
+                .iter()
+                .flat_map(|corner| corners[corner].iter().cloned())
+                .collect();
+            let entry = corners.get_mut(nt).unwrap();
+            let len_before = entry.len();
+            entry.extend(reachable);
+            if entry.len() != len_before {
+                changed = true;
+            }
+        }
+    }
+
+    corners
+}
+
+// Whether some non-terminal can derive a sentential form starting with itself
+pub fn is_left_recursive(grammar: &Grammar) -> bool {
+    left_corners(grammar).iter().any(|(nt, corners)| corners.contains(nt))
+}
+
+// The non-empty alternatives of `production` when its nullable symbols may each vanish
+fn without_nullable(production: &[String], nullable: &HashSet<String>) -> Vec<Vec<String>> {
+    let mut results: Vec<Vec<String>> = vec![vec![]];
+    for symbol in production.iter().filter(|symbol| !symbol.is_empty()) {
+        let mut next = Vec::new();
+        for result in &results {
+            let mut kept = result.clone();
+            kept.push(symbol.clone());
+            next.push(kept);
+            if nullable.contains(symbol) {
+                next.push(result.clone());
+            }
+        }
+        results = next;
+    }
+    let mut unique: Vec<Vec<String>> = Vec::new();
+    for result in results.into_iter().filter(|result| !result.is_empty()) {
+        if !unique.contains(&result) {
+            unique.push(result);
+        }
+    }
+    unique
+}
+
+// Make left recursion hidden behind a nullable prefix visible: A -> B γ with B nullable and A a left corner
+// of γ becomes A -> B' γ | γ, where B' derives what B does except ε. Returns whether anything changed.
+fn expose_hidden_left_recursion(
+    grammar: &mut Grammar,
+    non_terminal: &str,
+    non_empty: &mut HashMap<String, Option<String>>,
+    introduced: &mut Vec<(String, String)>,
+) -> bool {
+    let mut changed = false;
+    loop {
+        let nullable = nullable_non_terminals(grammar);
+        let corners = left_corners(grammar);
+        let productions = grammar[non_terminal].clone();
+        let hidden = productions.iter().position(|production| {
+            production.len() > 1
+                && nullable.contains(&production[0])
+                && production[0] != non_terminal
+                && leading_non_terminals(grammar, &nullable, &production[1..])
+                    .iter()
+                    .any(|symbol| *symbol == non_terminal || corners[*symbol].contains(non_terminal))
+        });
+        let Some(index) = hidden else {
+            return changed;
+        };
+
+        let prefix = productions[index][0].clone();
+        // A prefix that only ever derives ε has no non-empty variant
+        let variant = non_empty
+            .entry(prefix.clone())
+            .or_insert_with(|| {
+                let variant_productions: Vec<Vec<String>> = grammar[&prefix]
+                    .iter()
+                    .flat_map(|production| without_nullable(production, &nullable))
+                    .collect();
+                if variant_productions.is_empty() {
+                    return None;
+                }
+                let variant = fresh_non_terminal(grammar, &prefix);
+                grammar.insert(variant.clone(), variant_productions);
+                introduced.push((variant.clone(), prefix.clone()));
+                Some(variant)
+            })
+            .clone();
+
+        let rest = productions[index][1..].to_vec();
+        let mut new_productions = productions.clone();
+        new_productions.remove(index);
+        if let Some(variant) = variant {
+            new_productions.insert(index, concat(std::slice::from_ref(&variant), &rest));
+        }
+        if !new_productions.contains(&rest) {
+            new_productions.push(rest);
+        }
+        grammar.insert(non_terminal.to_string(), new_productions);
+        changed = true;
+    }
+}
+
+// Remove immediate left recursion A -> A α | β  =>  A -> β A', A' -> α A' | ε
+fn eliminate_immediate_left_recursion(
+    grammar: &mut Grammar,
+    non_terminal: &str,
+    introduced: &mut Vec<(String, String)>,
+) {
+    let productions = grammar.get(non_terminal).cloned().unwrap_or_default();
+    let (recursive, others): (Vec<Vec<String>>, Vec<Vec<String>>) = productions
+        .into_iter()
+        .partition(|production| production.first().map(String::as_str) == Some(non_terminal));
+
+    if recursive.is_empty() {
+        return;
+    }
+
+    let tail = fresh_non_terminal(grammar, non_terminal);
+    let tail_symbol = [tail.clone()];
+
+    let new_productions: Vec<Vec<String>> = others
+        .iter()
+        .map(|beta| concat(beta, &tail_symbol))
+        .collect();
+    let mut tail_productions: Vec<Vec<String>> = recursive
+        .iter()
+        // A -> A contributes nothing but a cycle, so it is dropped
+        .filter(|production| production.len() > 1)
+        .map(|production| concat(&production[1..], &tail_symbol))
+        .collect();
+    tail_productions.push(epsilon());
+
+    grammar.insert(non_terminal.to_string(), new_productions);
+    grammar.insert(tail.clone(), tail_productions);
+    introduced.push((tail, non_terminal.to_string()));
+}
+
+// Eliminate immediate and indirect left recursion, returning the new grammar and the introduced non-terminals
+// together with the non-terminal each of them was split from
+pub fn eliminate_left_recursion(grammar: &Grammar, start_symbol: &str) -> (Grammar, Vec<(String, String)>) {
+    let mut result = grammar.clone();
+    let mut introduced = Vec::new();
+    let order = ordered_non_terminals(grammar, start_symbol);
+    // Non-empty variants B' of nullable prefixes B, made by expose_hidden_left_recursion
+    let mut non_empty: HashMap<String, Option<String>> = HashMap::new();
+
+    for (i, a_i) in order.iter().enumerate() {
+        let mut changed = true;
+        while changed {
+            changed = expose_hidden_left_recursion(&mut result, a_i, &mut non_empty, &mut introduced);
+            let mut earlier: Vec<String> = order[..i].to_vec();
+            earlier.extend(non_empty.values().flatten().cloned());
+            for a_j in &earlier {
+                // Only expand A_i -> A_j γ when A_j can lead back to A_i, otherwise the substitution is pointless
+                if !result[a_i].iter().any(|production| production.first() == Some(a_j))
+                    || !left_corners(&result)[a_j].contains(a_i)
+                {
+                    continue;
+                }
+                let a_j_productions = result[a_j].clone();
+                let mut new_productions = Vec::new();
+                for production in &result[a_i] {
+                    if production.first() == Some(a_j) {
+                        for delta in &a_j_productions {
+                            new_productions.push(concat(delta, &production[1..]));
+                        }
+                    } else {
+                        new_productions.push(production.clone());
+                    }
+                }
+                result.insert(a_i.clone(), new_productions);
+                changed = true;
+            }
+        }
+        eliminate_immediate_left_recursion(&mut result, a_i, &mut introduced);
+    }
+
+    (result, introduced)
+}
+
+fn common_prefix_len(a: &[String], b: &[String]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
+
+// Left factor common prefixes A -> α β1 | α β2  =>  A -> α A', A' -> β1 | β2, repeated until no two
+// alternatives of a non-terminal share their first symbol
+pub fn left_factor(grammar: &Grammar, start_symbol: &str) -> (Grammar, Vec<(String, String)>) {
+    let mut result = grammar.clone();
+    let mut introduced = Vec::new();
+    let mut worklist = ordered_non_terminals(grammar, start_symbol);
+
+    while !worklist.is_empty() {
+        let non_terminal = worklist.remove(0);
+        // Identical alternatives would otherwise be factored into A' -> ε | ε
+        let mut productions: Vec<Vec<String>> = Vec::new();
+        for production in &result[&non_terminal] {
+            if !productions.contains(production) {
+                productions.push(production.clone());
+            }
+        }
+        result.insert(non_terminal.clone(), productions.clone());
+
+        // Group the alternatives by their first symbol and factor the first group with more than one member
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (idx, production) in productions.iter().enumerate() {
+            if is_epsilon(production) {
+                continue;
+            }
+            match groups.iter_mut().find(|(head, _)| *head == production[0]) {
+                Some((_, members)) => members.push(idx),
+                None => groups.push((production[0].clone(), vec![idx])),
+            }
+        }
+        let Some((_, members)) = groups.into_iter().find(|(_, members)| members.len() > 1) else {
+            continue;
+        };
+
+        let prefix_len = members
+            .iter()
+            .map(|&idx| &productions[idx])
+            .fold(usize::MAX, |len, production| {
+                len.min(common_prefix_len(&productions[members[0]], production))
+            });
+        let prefix = productions[members[0]][..prefix_len].to_vec();
+
+        let tail = fresh_non_terminal(&result, &non_terminal);
+        let tail_productions: Vec<Vec<String>> = members
+            .iter()
+            .map(|&idx| concat(&productions[idx][prefix_len..], &[]))
+            .collect();
+
+        let mut new_productions: Vec<Vec<String>> = Vec::new();
+        for (idx, production) in productions.iter().enumerate() {
+            if idx == members[0] {
+                new_productions.push(concat(&prefix, std::slice::from_ref(&tail)));
+            } else if !members.contains(&idx) {
+                new_productions.push(production.clone());
+            }
+        }
+
+        result.insert(non_terminal.clone(), new_productions);
+        result.insert(tail.clone(), tail_productions);
+        introduced.push((tail.clone(), non_terminal.clone()));
+
+        // Both the shortened non-terminal and the new one may still need factoring
+        worklist.insert(0, tail);
+        worklist.insert(0, non_terminal);
+    }
+
+    (result, introduced)
+}
+
+// Remove left recursion, then left factor, so the result can be handed to build_parsing_table
+pub fn prepare_for_ll1(grammar: &Grammar, start_symbol: &str) -> (Grammar, Vec<(String, String)>) {
+    let (without_recursion, mut introduced) = eliminate_left_recursion(grammar, start_symbol);
+    let (factored, factored_introduced) = left_factor(&without_recursion, start_symbol);
+    introduced.extend(factored_introduced);
+    (factored, introduced)
+}
+
+// Render a right-hand side, using ε for the empty alternative
+pub fn production_to_string(production: &[String]) -> String {
+    if is_epsilon(production) {
+        "ε".to_string()
+    } else {
+        production.join(" ")
+    }
+}
+
+// Render the grammar one non-terminal per line, start symbol first
+pub fn grammar_to_string(grammar: &Grammar, start_symbol: &str) -> String {
+    ordered_non_terminals(grammar, start_symbol)
+        .iter()
+        .map(|nt| {
+            let alternatives: Vec<String> = grammar[nt]
+                .iter()
+                .map(|production| production_to_string(production))
+                .collect();
+            format!("{} -> {}\n", nt, alternatives.join(" | "))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(rules: &[(&str, &[&str])]) -> Grammar {
+        let mut grammar = Grammar::new();
+        for (lhs, alternatives) in rules {
+            let productions = alternatives
+                .iter()
+                .map(|alt| {
+                    if *alt == "ε" {
+                        vec!["".to_string()]
+                    } else {
+                        alt.split_whitespace().map(String::from).collect()
+                    }
+                })
+                .collect();
+            grammar.insert(lhs.to_string(), productions);
+        }
+        grammar
+    }
+
+    #[test]
+    fn test_indirect_left_recursion() {
+        let g = grammar(&[("S", &["A a", "b"]), ("A", &["A c", "S d", "ε"])]);
+        let (result, introduced) = eliminate_left_recursion(&g, "S");
+
+        assert_eq!(introduced, vec![("A'".to_string(), "A".to_string())]);
+        assert_eq!(
+            grammar_to_string(&result, "S"),
+            "S -> A a | b\nA -> b d A' | A'\nA' -> c A' | a d A' | ε\n"
+        );
+    }
+
+    #[test]
+    fn test_left_factoring() {
+        let g = grammar(&[("S", &["i E t S", "i E t S e S", "a"]), ("E", &["b"])]);
+        let (result, introduced) = left_factor(&g, "S");
+
+        assert_eq!(introduced, vec![("S'".to_string(), "S".to_string())]);
+        assert_eq!(
+            grammar_to_string(&result, "S"),
+            "S -> i E t S S' | a\nE -> b\nS' -> ε | e S\n"
+        );
+
+        let duplicated = grammar(&[("S", &["a b", "a b", "a c"])]);
+        let (result, _) = left_factor(&duplicated, "S");
+        assert_eq!(grammar_to_string(&result, "S"), "S -> a S'\nS' -> b | c\n");
+    }
+
+    #[test]
+    fn test_hidden_left_recursion() {
+        // B vanishes, so A -> B A x is left recursive just like A -> A x
+        let g = grammar(&[("S", &["A"]), ("A", &["B A x", "y"]), ("B", &["ε"])]);
+        assert!(is_left_recursive(&g));
+        let (result, _) = eliminate_left_recursion(&g, "S");
+        assert!(!is_left_recursive(&result));
+        assert_eq!(grammar_to_string(&result, "S"), "S -> A\nA -> y A'\nA' -> x A' | ε\nB -> ε\n");
+
+        // With a B that derives something, the non-empty part of B stays in front
+        let g = grammar(&[("S", &["B S x", "y"]), ("B", &["b", "ε"])]);
+        let (result, introduced) = prepare_for_ll1(&g, "S");
+        assert!(!is_left_recursive(&result));
+        assert_eq!(introduced[0], ("B'".to_string(), "B".to_string()));
+        assert_eq!(grammar_to_string(&result, "S"), "S -> B' S x S' | y S'\nB -> b | ε\nB' -> b\nS' -> x S' | ε\n");
+    }
+}
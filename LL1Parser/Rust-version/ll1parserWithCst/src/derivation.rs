@@ -0,0 +1,26 @@
+// Sentential forms and their rendering are shared with the other LL(1) parsers
+pub use ll1parser::derivation::{render_derivation, sentential_forms, DerivationFormat};
+
+use crate::ASTNode;
+
+// Productions of the tree in pre-order, which is the order of the leftmost derivation
+pub fn productions_from_tree(tree: &ASTNode) -> Vec<(String, Vec<String>)> {
+    fn walk(tree: &ASTNode, out: &mut Vec<(String, Vec<String>)>) {
+        if let ASTNode::NonTerminal(non_terminal, children) = tree {
+            let production = children
+                .iter()
+                .map(|child| match child {
+                    ASTNode::NonTerminal(label, _) | ASTNode::Terminal(label, _) | ASTNode::Error(label) => label.clone(),
+                })
+                .collect();
+            out.push((non_terminal.clone(), production));
+            for child in children {
+                walk(child, out);
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(tree, &mut out);
+    out
+}
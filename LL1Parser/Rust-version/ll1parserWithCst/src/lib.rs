@@ -0,0 +1,461 @@
+pub mod derivation;
+pub mod lower;
+pub mod render;
+
+pub use ll1parser::{recovery, source};
+
+use std::collections::{HashMap, HashSet};
+
+use recovery::{expected_terminals, token_at, Recovery, RecoveryOptions, RecoveryStrategy, SyntaxError};
+use source::SourceToken;
+
+pub type Grammar = HashMap<String, Vec<Vec<String>>>;
+
+#[derive(Debug, Clone)]
+pub enum ASTNode {
+    NonTerminal(String, Vec<ASTNode>),
+    // A matched terminal, with the lexeme and span it was read from when the input came from source text
+    Terminal(String, Option<SourceToken>),
+    // Marks where a syntax error was recovered from
+    Error(String),
+}
+
+struct Parser<'a> {
+    grammar: &'a Grammar,
+    parsing_table: &'a HashMap<(String, String), Vec<String>>,
+    first: &'a HashMap<String, HashSet<String>>,
+    follow: &'a HashMap<String, HashSet<String>>,
+    options: RecoveryOptions,
+    tokens: &'a [&'a str],
+    source: &'a [SourceToken],
+    position: usize,
+    errors: Vec<SyntaxError>,
+}
+
+// LL(1) parser main function, returning every syntax error found together with the (possibly partial) tree;
+// the tree is only missing when recovery is disabled and an error was found
+pub fn parse(
+    grammar: &Grammar,
+    parsing_table: &HashMap<(String, String), Vec<String>>,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>,
+    start_symbol: &str,
+    input: &[&str],
+    options: RecoveryOptions,
+) -> (Vec<SyntaxError>, Option<ASTNode>) {
+    let mut tokens = input.to_vec();
+    tokens.push("$");
+    let parser = Parser {
+        grammar,
+        parsing_table,
+        first,
+        follow,
+        options,
+        tokens: &tokens,
+        source: &[],
+        position: 0,
+        errors: Vec::new(),
+    };
+    parser.run(start_symbol)
+}
+
+// Same as `parse`, for tokens read from source text; each terminal leaf keeps the token it matched
+pub fn parse_source(
+    grammar: &Grammar,
+    parsing_table: &HashMap<(String, String), Vec<String>>,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>,
+    start_symbol: &str,
+    tokens: &[SourceToken],
+    options: RecoveryOptions,
+) -> (Vec<SyntaxError>, Option<ASTNode>) {
+    let mut input: Vec<&str> = tokens.iter().map(|token| token.terminal.as_str()).collect();
+    input.push("$");
+    let parser = Parser {
+        grammar,
+        parsing_table,
+        first,
+        follow,
+        options,
+        tokens: &input,
+        source: tokens,
+        position: 0,
+        errors: Vec::new(),
+    };
+    parser.run(start_symbol)
+}
+
+impl<'a> Parser<'a> {
+    fn run(mut self, start_symbol: &str) -> (Vec<SyntaxError>, Option<ASTNode>) {
+        // `tokens` ends with the end marker appended by the caller
+        if let Some(error) = SyntaxError::end_marker_in_input(&self.tokens[..self.tokens.len() - 1]) {
+            return (vec![error], None);
+        }
+        let ast = self.parse_non_terminal(start_symbol);
+        if ast.is_some() && self.lookahead() != "$" {
+            let error = SyntaxError::trailing(self.position, self.lookahead());
+            self.report(error);
+        }
+        (self.errors, ast)
+    }
+
+    // Leaf for the terminal just consumed
+    fn terminal_leaf(&self, terminal: &str) -> ASTNode {
+        ASTNode::Terminal(terminal.to_string(), self.source.get(self.position - 1).cloned())
+    }
+
+    fn lookahead(&self) -> &'a str {
+        token_at(self.tokens, self.position)
+    }
+
+    fn log(&self, message: String) {
+        if self.options.verbose {
+            println!("{}", message);
+        }
+    }
+
+    fn report(&mut self, error: SyntaxError) {
+        self.log(format!("Error: {}", error.message));
+        self.errors.push(error);
+    }
+
+    // Error for a lookahead that does not fit; `expected` is what would have been accepted
+    fn unexpected(&self, expected: &str) -> SyntaxError {
+        SyntaxError::unexpected(self.position, self.lookahead(), expected)
+    }
+
+    // Skip input up to `position`, adding an error node for each discarded token
+    fn skip_to(&mut self, position: usize, children: &mut Vec<ASTNode>) {
+        while self.position < position {
+            children.push(ASTNode::Error(format!("unexpected {}", self.lookahead())));
+            self.position += 1;
+        }
+    }
+
+    // Recursive function to parse non-terminal symbols and build AST; returns None only when parsing has to stop
+    fn parse_non_terminal(&mut self, symbol: &str) -> Option<ASTNode> {
+        let mut children = Vec::new();
+
+        loop {
+            let lookahead = self.lookahead();
+            if let Some(production) = self.parsing_table.get(&(symbol.to_string(), lookahead.to_string())) {
+                self.log(format!("Using production: {} -> {:?}", symbol, production));
+                for prod_symbol in production {
+                    if prod_symbol.is_empty() {
+                        self.log(format!("Parsing {} -> ε", symbol));
+                        continue;
+                    }
+
+                    if self.grammar.contains_key(prod_symbol) {
+                        children.push(self.parse_non_terminal(prod_symbol)?);
+                    } else {
+                        self.match_terminal(prod_symbol, &mut children)?;
+                    }
+                }
+                return Some(ASTNode::NonTerminal(symbol.to_string(), children));
+            }
+
+            let error = self.unexpected(&expected_terminals(symbol, self.parsing_table));
+            let message = error.message.clone();
+            self.report(error);
+            if self.options.strategy == RecoveryStrategy::Abort {
+                return None;
+            }
+            children.push(ASTNode::Error(message));
+            match self.options.recover_non_terminal(
+                symbol,
+                self.tokens,
+                self.position,
+                self.parsing_table,
+                self.first,
+                self.follow,
+            ) {
+                Recovery::Delete => self.skip_to(self.position + 1, &mut children),
+                Recovery::Resume(position) => self.skip_to(position, &mut children),
+                Recovery::Pop(position) => {
+                    self.skip_to(position, &mut children);
+                    return Some(ASTNode::NonTerminal(symbol.to_string(), children));
+                }
+            }
+        }
+    }
+
+    // Function to match terminal symbols and add them to `children`; a mismatch is repaired by deleting or
+    // inserting a token and shows up as an error node in the tree
+    fn match_terminal(&mut self, expected: &str, children: &mut Vec<ASTNode>) -> Option<()> {
+        if self.lookahead() == expected {
+            self.log(format!("Matched terminal: {}", expected));
+            self.position += 1; // Consume input
+            children.push(self.terminal_leaf(expected));
+            return Some(());
+        }
+        self.report(self.unexpected(expected));
+        if self.options.strategy == RecoveryStrategy::Abort {
+            return None;
+        }
+        if self.options.delete_instead_of_insert(expected, self.tokens, self.position) {
+            self.skip_to(self.position + 1, children);
+            self.position += 1;
+            children.push(self.terminal_leaf(expected));
+        } else {
+            children.push(ASTNode::Error(format!("missing {}", expected)));
+        }
+        Some(())
+    }
+}
+
+// Compute the First set for the grammar
+pub fn compute_first(grammar: &Grammar) -> HashMap<String, HashSet<String>> {
+    let mut first: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for non_terminal in grammar.keys() {
+        first.insert(non_terminal.clone(), HashSet::new());
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                if update_first_set(grammar, &mut first, non_terminal, production) {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    first
+}
+
+// Update the First set for a given production
+pub fn update_first_set(
+    grammar: &Grammar,
+    first: &mut HashMap<String, HashSet<String>>,
+    non_terminal: &String,
+    production: &[String],
+) -> bool {
+    let mut changed = false;
+    for symbol in production {
+        if grammar.contains_key(symbol) {
+            let first_set = first.get(symbol).unwrap().clone();
+            let len_before = first.get(non_terminal).unwrap().len();
+            first.get_mut(non_terminal).unwrap().extend(first_set.clone());
+            let len_after = first.get(non_terminal).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+            if !first_set.contains("") {
+                break;
+            }
+        } else {
+            let len_before = first.get(non_terminal).unwrap().len();
+            first.get_mut(non_terminal).unwrap().insert(symbol.clone());
+            let len_after = first.get(non_terminal).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+            break;
+        }
+    }
+    changed
+}
+
+// Compute the Follow set for the grammar
+pub fn compute_follow(grammar: &Grammar, start_symbol: &str) -> HashMap<String, HashSet<String>> {
+    let mut follow: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for non_terminal in grammar.keys() {
+        follow.insert(non_terminal.clone(), HashSet::new());
+    }
+
+    follow.get_mut(start_symbol).unwrap().insert("$".to_owned());
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                if update_follow_set(grammar, &mut follow, non_terminal, production) {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    follow
+}
+
+// Update the Follow set for a given production
+pub fn update_follow_set(
+    grammar: &Grammar,
+    follow: &mut HashMap<String, HashSet<String>>,
+    non_terminal: &String,
+    production: &[String],
+) -> bool {
+    let mut changed = false;
+    for (i, symbol) in production.iter().enumerate() {
+        if grammar.contains_key(symbol) {
+            let mut follow_set = follow.get(symbol).unwrap().clone();
+            if i + 1 < production.len() {
+                let next_symbol = &production[i + 1];
+                if grammar.contains_key(next_symbol) {
+                    let first_set = compute_first(grammar).get(next_symbol).unwrap().clone();
+                    follow_set.extend(first_set.iter().filter(|x| !x.is_empty()).cloned());
+                    if first_set.contains("") {
+                        follow_set.extend(follow.get(non_terminal).unwrap().clone());
+                    }
+                } else {
+                    follow_set.insert(next_symbol.clone());
+                }
+            } else {
+                follow_set.extend(follow.get(non_terminal).unwrap().clone());
+            }
+            let len_before = follow.get(symbol).unwrap().len();
+            follow.get_mut(symbol).unwrap().extend(follow_set.clone());
+            let len_after = follow.get(symbol).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+// Build the parsing table for the grammar
+pub fn build_parsing_table(
+    grammar: &Grammar,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>
+) -> HashMap<(String, String), Vec<String>> {
+    let mut table: HashMap<(String, String), Vec<String>> = HashMap::new();
+
+    for (non_terminal, productions) in grammar.iter() {
+        for production in productions {
+            let first_set = compute_first_for_production(production, first);
+            for terminal in first_set.iter().filter(|&x| !x.is_empty()) {
+                table.insert((non_terminal.clone(), terminal.clone()), production.clone());
+            }
+            if first_set.contains("") {
+                for terminal in follow.get(non_terminal).unwrap() {
+                    // Only add ε production if no other production matches
+                    table.entry((non_terminal.clone(), terminal.clone())).or_insert_with(|| production.clone());
+                }
+            }
+        }
+    }
+
+    table
+}
+
+// Compute the First set for a given production
+pub fn compute_first_for_production(
+    production: &[String],
+    first: &HashMap<String, HashSet<String>>,
+) -> HashSet<String> {
+    let mut result = HashSet::new();
+    for symbol in production {
+        if first.contains_key(symbol) {
+            let first_set = first.get(symbol).unwrap();
+            result.extend(first_set.clone());
+            if !first_set.contains("") {
+                break;
+            }
+        } else {
+            result.insert(symbol.clone());
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use recovery::ErrorKind;
+
+    // The tree on one line, ε leaves left out
+    fn brackets(node: &ASTNode) -> String {
+        match node {
+            ASTNode::NonTerminal(name, children) if children.is_empty() => name.clone(),
+            ASTNode::NonTerminal(name, children) => {
+                format!("{}({})", name, children.iter().map(brackets).collect::<Vec<String>>().join(" "))
+            }
+            ASTNode::Terminal(name, _) => name.clone(),
+            ASTNode::Error(message) => format!("<{}>", message),
+        }
+    }
+
+    fn recover(input: &str, strategy: RecoveryStrategy) -> (Vec<String>, String) {
+        let symbols = |text: &str| text.split_whitespace().map(String::from).collect::<Vec<String>>();
+        let grammar: Grammar = [
+            ("E".to_string(), vec![symbols("T E'")]),
+            ("E'".to_string(), vec![symbols("+ T E'"), vec!["".to_string()]]),
+            ("T".to_string(), vec![symbols("F T'")]),
+            ("T'".to_string(), vec![symbols("* F T'"), vec!["".to_string()]]),
+            ("F".to_string(), vec![symbols("( E )"), symbols("id")]),
+        ]
+        .into_iter()
+        .collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "E");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let input: Vec<&str> = input.split_whitespace().collect();
+        let options = RecoveryOptions { strategy, verbose: false, ..RecoveryOptions::default() };
+        let (errors, tree) = parse(&grammar, &table, &first, &follow, "E", &input, options);
+        (errors.iter().map(ToString::to_string).collect(), tree.as_ref().map(brackets).unwrap_or_default())
+    }
+
+    #[test]
+    fn test_recovered_trees() {
+        assert_eq!(
+            recover("( id + id", RecoveryStrategy::PanicMode),
+            (
+                vec!["error at end of input: unexpected end of input, expected )".to_string()],
+                "E(T(F(( E(T(F(id) T') E'(+ T(F(id) T') E')) <missing )>) T') E')".to_string()
+            )
+        );
+        assert_eq!(
+            recover("id + * id", RecoveryStrategy::PanicMode).1,
+            "E(T(F(id) T') E'(+ T(<expected one of (, id, found *> <unexpected *> F(id) T') E'))"
+        );
+
+        // Panic mode gives up on F at `)` and discards the rest; phrase level deletes the `)` inside F
+        let (errors, tree) = recover("id * ) id + id", RecoveryStrategy::PanicMode);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(tree, "E(T(F(id) T'(* F(<expected one of (, id, found )>) T')) E')");
+        let (errors, tree) = recover("id * ) id + id", RecoveryStrategy::PhraseLevel);
+        assert_eq!(errors, vec!["error at token 3 (')'): expected one of (, id, found )".to_string()]);
+        assert_eq!(
+            tree,
+            "E(T(F(id) T'(* F(<expected one of (, id, found )> <unexpected )> id) T')) E'(+ T(F(id) T') E'))"
+        );
+
+        // Without recovery there is no tree at all
+        assert_eq!(recover("id + * id", RecoveryStrategy::Abort).1, "");
+    }
+    #[test]
+    fn test_error_kinds() {
+        let grammar: Grammar = [(
+            "S".to_string(),
+            vec![vec!["a".to_string(), "S".to_string()], vec!["b".to_string()]],
+        )]
+        .into_iter()
+        .collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "S");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let kinds = |input: &str| -> Vec<ErrorKind> {
+            let input: Vec<&str> = input.split_whitespace().collect();
+            let options = RecoveryOptions { verbose: false, ..RecoveryOptions::default() };
+            let (errors, _) = parse(&grammar, &table, &first, &follow, "S", &input, options);
+            errors.into_iter().map(|error| error.kind).collect()
+        };
+
+        assert_eq!(kinds("a a b"), vec![]);
+        assert_eq!(kinds("a c b"), vec![ErrorKind::UnexpectedToken]);
+        assert_eq!(kinds("a a"), vec![ErrorKind::UnexpectedEnd]);
+        assert_eq!(kinds("b a"), vec![ErrorKind::TrailingInput]);
+        // A literal `$` is rejected before parsing instead of ending the input after `b`
+        assert_eq!(kinds("b $ a"), vec![ErrorKind::EndMarkerInInput]);
+    }
+}
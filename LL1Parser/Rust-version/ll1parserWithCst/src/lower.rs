@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use crate::ASTNode;
+
+type Action<T> = Box<dyn Fn(Vec<T>) -> Result<T, String>>;
+
+// Semantic actions keyed by production, run bottom-up over the concrete syntax tree
+pub struct SemanticActions<T> {
+    actions: HashMap<(String, Vec<String>), Action<T>>,
+    terminal: Box<dyn Fn(&str) -> T>,
+}
+
+impl<T> SemanticActions<T> {
+    // `terminal` gives the value of a leaf from its terminal, or from its lexeme when it has one
+    pub fn new(terminal: impl Fn(&str) -> T + 'static) -> Self {
+        Self {
+            actions: HashMap::new(),
+            terminal: Box::new(terminal),
+        }
+    }
+
+    // Register the action for `non_terminal -> production`; it receives the values of the children in order,
+    // so the action of an ε-production is called with no values
+    pub fn add_action(
+        &mut self,
+        non_terminal: &str,
+        production: &[&str],
+        action: impl Fn(Vec<T>) -> Result<T, String> + 'static,
+    ) {
+        let production = if production.is_empty() {
+            vec!["".to_string()]
+        } else {
+            production.iter().map(|symbol| symbol.to_string()).collect()
+        };
+        self.actions.insert((non_terminal.to_string(), production), Box::new(action));
+    }
+
+    // Evaluate the tree bottom-up; trees that still contain error nodes cannot be lowered
+    pub fn lower(&self, node: &ASTNode) -> Result<T, String> {
+        match node {
+            // Leaves read from source text are valued by their lexeme
+            ASTNode::Terminal(symbol, source) => Ok((self.terminal)(source.as_ref().map_or(symbol, |token| &token.lexeme))),
+            ASTNode::Error(message) => Err(format!("cannot lower a tree with errors: {}", message)),
+            ASTNode::NonTerminal(non_terminal, children) => {
+                let mut production: Vec<String> = children.iter().map(node_label).collect();
+                if production.is_empty() {
+                    production.push("".to_string());
+                }
+                // Children first, so an error node below is reported as such rather than as a missing action
+                let values = children.iter().map(|child| self.lower(child)).collect::<Result<Vec<T>, String>>()?;
+                let key = (non_terminal.clone(), production);
+                let action = self.actions.get(&key).ok_or_else(|| {
+                    format!("no semantic action for {} -> {}", key.0, key.1.join(" "))
+                })?;
+                action(values)
+            }
+        }
+    }
+}
+
+fn node_label(node: &ASTNode) -> String {
+    match node {
+        ASTNode::NonTerminal(label, _) | ASTNode::Terminal(label, _) | ASTNode::Error(label) => label.clone(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Operand(String),
+    Binary(String, Box<Expr>, Box<Expr>),
+}
+
+// Intermediate values of the built-in expression lowering: the E'/T' tails are collected as
+// (operator, operand) lists and folded into left-associative binary nodes by E and T
+#[derive(Debug, Clone)]
+pub enum Lowered {
+    Expr(Expr),
+    Operator(String),
+    Tail(Vec<(String, Expr)>),
+}
+
+fn into_expr(value: Lowered) -> Result<Expr, String> {
+    match value {
+        Lowered::Expr(expr) => Ok(expr),
+        other => Err(format!("expected an expression, found {:?}", other)),
+    }
+}
+
+fn fold_left(head: Lowered, tail: Lowered) -> Result<Lowered, String> {
+    let Lowered::Tail(tail) = tail else {
+        return Err(format!("expected an operator tail, found {:?}", tail));
+    };
+    let expr = tail.into_iter().fold(into_expr(head)?, |left, (op, right)| {
+        Expr::Binary(op, Box::new(left), Box::new(right))
+    });
+    Ok(Lowered::Expr(expr))
+}
+
+fn prepend_tail(op: Lowered, operand: Lowered, rest: Lowered) -> Result<Lowered, String> {
+    let (Lowered::Operator(op), Lowered::Tail(rest)) = (op, rest) else {
+        return Err("malformed operator tail".to_string());
+    };
+    let mut tail = vec![(op, into_expr(operand)?)];
+    tail.extend(rest);
+    Ok(Lowered::Tail(tail))
+}
+
+// Actions for the built-in expression grammar (E, E', T, T', F, ADD_SUB, MUL_DIV)
+pub fn expression_actions() -> SemanticActions<Lowered> {
+    let mut actions = SemanticActions::new(|symbol| Lowered::Operator(symbol.to_string()));
+
+    for (head, tail) in [("E", "E'"), ("T", "T'")] {
+        let operand = if head == "E" { "T" } else { "F" };
+        let operator = if head == "E" { "ADD_SUB" } else { "MUL_DIV" };
+        actions.add_action(head, &[operand, tail], |mut values| {
+            let tail = values.pop().unwrap();
+            fold_left(values.pop().unwrap(), tail)
+        });
+        actions.add_action(tail, &[operator, operand, tail], |mut values| {
+            let rest = values.pop().unwrap();
+            let operand = values.pop().unwrap();
+            prepend_tail(values.pop().unwrap(), operand, rest)
+        });
+        actions.add_action(tail, &[], |_| Ok(Lowered::Tail(Vec::new())));
+    }
+    for op in ["+", "-"] {
+        actions.add_action("ADD_SUB", &[op], |mut values| Ok(values.pop().unwrap()));
+    }
+    for op in ["*", "div", "mod"] {
+        actions.add_action("MUL_DIV", &[op], |mut values| Ok(values.pop().unwrap()));
+    }
+    actions.add_action("F", &["number"], |mut values| match values.pop().unwrap() {
+        Lowered::Operator(leaf) => Ok(Lowered::Expr(Expr::Operand(leaf))),
+        other => Err(format!("expected a leaf, found {:?}", other)),
+    });
+    actions.add_action("F", &["(", "E", ")"], |mut values| {
+        values.pop();
+        Ok(values.pop().unwrap())
+    });
+
+    actions
+}
+
+// Lower a tree of the built-in expression grammar to a left-associative expression
+pub fn lower_expression(cst: &ASTNode) -> Result<Expr, String> {
+    into_expr(expression_actions().lower(cst)?)
+}
+
+// Evaluate an expression; `operand` supplies the value of each leaf, visited left to right
+pub fn evaluate(expr: &Expr, operand: &mut dyn FnMut(&str) -> Option<i64>) -> Result<i64, String> {
+    match expr {
+        Expr::Operand(leaf) => operand(leaf).ok_or_else(|| format!("no value for operand {}", leaf)),
+        Expr::Binary(op, left, right) => {
+            let left = evaluate(left, operand)?;
+            let right = evaluate(right, operand)?;
+            let result = match op.as_str() {
+                "+" => left.checked_add(right),
+                "-" => left.checked_sub(right),
+                "*" => left.checked_mul(right),
+                "div" | "/" if right == 0 => return Err("division by zero".to_string()),
+                "mod" | "%" if right == 0 => return Err("modulo by zero".to_string()),
+                "div" | "/" => left.checked_div(right),
+                "mod" | "%" => left.checked_rem(right),
+                _ => return Err(format!("unknown operator {}", op)),
+            };
+            // i64::MIN / -1 and i64::MIN % -1 overflow as well
+            result.ok_or_else(|| format!("overflow in {} {} {}", left, op, right))
+        }
+    }
+}
+
+// Fully parenthesised form, which makes the associativity visible
+pub fn expr_to_string(expr: &Expr) -> String {
+    match expr {
+        Expr::Operand(leaf) => leaf.clone(),
+        Expr::Binary(op, left, right) => format!("({} {} {})", expr_to_string(left), op, expr_to_string(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::RecoveryOptions;
+    use crate::source::{tokenize, TerminalMap};
+    use crate::{build_parsing_table, compute_first, compute_follow, parse, parse_source, Grammar};
+
+    fn expression_grammar() -> Grammar {
+        let symbols = |text: &str| text.split_whitespace().map(String::from).collect::<Vec<String>>();
+        [
+            ("E".to_string(), vec![symbols("T E'")]),
+            ("E'".to_string(), vec![symbols("ADD_SUB T E'"), vec!["".to_string()]]),
+            ("T".to_string(), vec![symbols("F T'")]),
+            ("T'".to_string(), vec![symbols("MUL_DIV F T'"), vec!["".to_string()]]),
+            ("F".to_string(), vec![symbols("number"), symbols("( E )")]),
+            ("ADD_SUB".to_string(), vec![symbols("+"), symbols("-")]),
+            ("MUL_DIV".to_string(), vec![symbols("*"), symbols("div"), symbols("mod")]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn lower_source(source: &str) -> Result<Expr, String> {
+        let grammar = expression_grammar();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "E");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let tokens = tokenize(source, &TerminalMap::expression())?;
+        let options = RecoveryOptions { verbose: false, ..RecoveryOptions::default() };
+        let (_, cst) = parse_source(&grammar, &table, &first, &follow, "E", &tokens, options);
+        lower_expression(&cst.ok_or("no tree")?)
+    }
+
+    fn lower_source_tokens(input: &str) -> Result<Expr, String> {
+        let grammar = expression_grammar();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "E");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let input: Vec<&str> = input.split_whitespace().collect();
+        let options = RecoveryOptions { verbose: false, ..RecoveryOptions::default() };
+        let (_, cst) = parse(&grammar, &table, &first, &follow, "E", &input, options);
+        lower_expression(&cst.ok_or("no tree")?)
+    }
+
+    fn value(source: &str) -> Result<i64, String> {
+        evaluate(&lower_source(source)?, &mut |leaf| leaf.parse().ok())
+    }
+
+    #[test]
+    fn test_operators_associate_to_the_left() {
+        assert_eq!(value("8 - 3 - 2"), Ok(3));
+        assert_eq!(value("8 / 2 / 2"), Ok(2));
+        assert_eq!(value("17 % 5 * 2"), Ok(4));
+        assert_eq!(expr_to_string(&lower_source("8 - 3 - 2").unwrap()), "((8 - 3) - 2)");
+        assert_eq!(expr_to_string(&lower_source("8 / 2 / 2").unwrap()), "((8 / 2) / 2)");
+
+        // Leaves without lexemes get their values in order
+        let expr = lower_source_tokens("number div number div number").unwrap();
+        assert_eq!(expr_to_string(&expr), "((number div number) div number)");
+        let mut values = [8, 2, 2].into_iter();
+        assert_eq!(evaluate(&expr, &mut |_| values.next()), Ok(2));
+    }
+
+    #[test]
+    fn test_tails_fold_into_their_operand() {
+        // Each E' and T' tail belongs to the operand in front of it, however deeply nested
+        assert_eq!(expr_to_string(&lower_source("1 + 2 * 3 - 4").unwrap()), "((1 + (2 * 3)) - 4)");
+        assert_eq!(expr_to_string(&lower_source("(1 - 2) * (3 - 4 - 5)").unwrap()), "((1 - 2) * ((3 - 4) - 5))");
+        assert_eq!(expr_to_string(&lower_source("7").unwrap()), "7");
+        assert_eq!(value("2 * (3 + 4) - 6 / 3"), Ok(12));
+    }
+
+    #[test]
+    fn test_lowering_errors() {
+        assert_eq!(value("1 / (2 - 2)"), Err("division by zero".to_string()));
+        assert_eq!(
+            value("9223372036854775807 + 1"),
+            Err("overflow in 9223372036854775807 + 1".to_string())
+        );
+        assert_eq!(value("4294967296 * 4294967296"), Err("overflow in 4294967296 * 4294967296".to_string()));
+        for op in ["div", "mod"] {
+            let expr = lower_source_tokens(&format!("number {} number", op)).unwrap();
+            let mut values = [i64::MIN, -1].into_iter();
+            assert_eq!(
+                evaluate(&expr, &mut |_| values.next()),
+                Err(format!("overflow in -9223372036854775808 {} -1", op))
+            );
+        }
+        assert_eq!(
+            lower_source("1 + * 2"),
+            Err("cannot lower a tree with errors: expected one of (, number, found *".to_string())
+        );
+    }
+}
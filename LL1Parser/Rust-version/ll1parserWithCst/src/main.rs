@@ -1,16 +1,28 @@
-use std::collections::{HashMap, HashSet};
-use std::iter::Peekable;
-use std::slice::Iter;
-
-type Grammar = HashMap<String, Vec<Vec<String>>>;
-
-#[derive(Debug)]
-enum ASTNode {
-    NonTerminal(String, Vec<ASTNode>),
-    Terminal(String),
-}
+use ll1parser_with_cst::derivation::{productions_from_tree, render_derivation, sentential_forms, DerivationFormat};
+use ll1parser_with_cst::lower::{evaluate, expr_to_string, lower_expression};
+use ll1parser_with_cst::recovery::RecoveryOptions;
+use ll1parser_with_cst::render::{render_tree, TreeFormat};
+use ll1parser_with_cst::source::{tokenize, TerminalMap};
+use ll1parser_with_cst::{build_parsing_table, compute_first, compute_follow, parse, parse_source, Grammar};
 
 fn main() {
+    // How parse trees are printed: text (default), ascii, unicode, dot, typst or mermaid
+    let tree_format = match std::env::args().nth(1) {
+        Some(name) => TreeFormat::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown tree format '{}', expected text, ascii, unicode, dot, typst or mermaid", name);
+            std::process::exit(1);
+        }),
+        None => TreeFormat::Text,
+    };
+    // How derivations are printed: text (default), typst or latex
+    let derivation_format = match std::env::args().nth(2) {
+        Some(name) => DerivationFormat::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown derivation format '{}', expected text, typst or latex", name);
+            std::process::exit(1);
+        }),
+        None => DerivationFormat::Text,
+    };
+
     let grammar: Grammar = [
         ("E".to_string(), vec![vec!["T".to_string(), "E'".to_string()]]),
         ("E'".to_string(), vec![vec!["ADD_SUB".to_string(), "T".to_string(), "E'".to_string()], vec!["".to_string()]]),
@@ -33,247 +45,63 @@ fn main() {
     let parsing_table = build_parsing_table(&grammar, &first, &follow);
     println!("Parsing table: {:?}", parsing_table);
 
-    let input_str = "number + number * number $";
-    let input: Vec<&str> = input_str.trim().split_whitespace().collect();
-    let mut input_iter = input.iter().peekable();
-    let (success, ast) = parse(&grammar, &parsing_table, &follow, "E", &mut input_iter);
-
-    println!("Parsing result: {}", if success { "Success" } else { "Failure" });
-    println!("AST: {:?}", ast);
-}
-
-// LL(1) parser main function
-fn parse<'a>(
-    grammar: &Grammar,
-    parsing_table: &HashMap<(String, String), Vec<String>>,
-    follow: &HashMap<String, HashSet<String>>,
-    start_symbol: &str,
-    input_iter: &mut Peekable<Iter<'a, &'a str>>,
-) -> (bool, Option<ASTNode>) {
-    parse_non_terminal(grammar, parsing_table, follow, start_symbol, input_iter)
-}
-
-// Recursive function to parse non-terminal symbols and build AST
-fn parse_non_terminal<'a>(
-    grammar: &Grammar,
-    parsing_table: &HashMap<(String, String), Vec<String>>,
-    follow: &HashMap<String, HashSet<String>>,
-    symbol: &str,
-    input_iter: &mut Peekable<Iter<'a, &'a str>>,
-) -> (bool, Option<ASTNode>) {
-    let mut children = Vec::new();
-
-    if let Some(&lookahead) = input_iter.peek() {
-        if let Some(production) = parsing_table.get(&(symbol.to_string(), lookahead.to_string())) {
-            println!("Using production: {} -> {:?}", symbol, production);
-            for prod_symbol in production {
-                if prod_symbol == "" {
-                    println!("Parsing {} -> ε", symbol);
-                    continue;
-                }
-
-                if grammar.contains_key(prod_symbol) {
-                    let (success, child) = parse_non_terminal(grammar, parsing_table, follow, prod_symbol, input_iter);
-                    if !success {
-                        return (false, None);
-                    }
-                    if let Some(node) = child {
-                        children.push(node);
-                    }
-                } else {
-                    if !match_terminal(input_iter, prod_symbol) {
-                        return (false, None);
-                    }
-                    children.push(ASTNode::Terminal(prod_symbol.clone()));
-                }
-            }
-            return (true, Some(ASTNode::NonTerminal(symbol.to_string(), children)));
-        } else {
-            println!("Error: No matching production for ({}, {})", symbol, lookahead);
-            return (false, None);
-        }
-    }
-    (false, None)
-}
-
-// Function to match terminal symbols
-fn match_terminal<'a>(
-    input_iter: &mut Peekable<Iter<'a, &'a str>>,
-    expected: &str,
-) -> bool {
-    if let Some(&next_token) = input_iter.peek() {
-        if *next_token == expected {
-            println!("Matched terminal: {}", next_token);
-            input_iter.next(); // Consume input
-            return true;
-        }
-    }
-    println!("Error: Expected '{}', but found '{:?}'", expected, input_iter.peek());
-    false
-}
-
-// Compute the First set for the grammar
-fn compute_first(grammar: &Grammar) -> HashMap<String, HashSet<String>> {
-    let mut first: HashMap<String, HashSet<String>> = HashMap::new();
-
-    for non_terminal in grammar.keys() {
-        first.insert(non_terminal.clone(), HashSet::new());
-    }
-
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for (non_terminal, productions) in grammar.iter() {
-            for production in productions {
-                if update_first_set(grammar, &mut first, non_terminal, production) {
-                    changed = true;
-                }
-            }
-        }
-    }
-
-    first
-}
-
-// Update the First set for a given production
-fn update_first_set(
-    grammar: &Grammar,
-    first: &mut HashMap<String, HashSet<String>>,
-    non_terminal: &String,
-    production: &Vec<String>,
-) -> bool {
-    let mut changed = false;
-    for symbol in production {
-        if grammar.contains_key(symbol) {
-            let first_set = first.get(symbol).unwrap().clone();
-            let len_before = first.get(non_terminal).unwrap().len();
-            first.get_mut(non_terminal).unwrap().extend(first_set.clone());
-            let len_after = first.get(non_terminal).unwrap().len();
-            if len_before != len_after {
-                changed = true;
-            }
-            if !first_set.contains(&"".to_owned()) {
-                break;
-            }
-        } else {
-            let len_before = first.get(non_terminal).unwrap().len();
-            first.get_mut(non_terminal).unwrap().insert(symbol.clone());
-            let len_after = first.get(non_terminal).unwrap().len();
-            if len_before != len_after {
-                changed = true;
+    let input_str = "number + number * number";
+    let input: Vec<&str> = input_str.split_whitespace().collect();
+    let (errors, ast) = parse(&grammar, &parsing_table, &first, &follow, "E", &input, RecoveryOptions::default());
+
+    println!("Parsing result: {}", if errors.is_empty() { "Success" } else { "Failure" });
+    if let Some(ast) = &ast {
+        print!("{}", render_tree(ast, tree_format));
+        if errors.is_empty() {
+            match sentential_forms(&grammar, "E", &productions_from_tree(ast)) {
+                Ok(forms) => println!("Leftmost derivation:\n{}", render_derivation(&forms, derivation_format)),
+                Err(message) => println!("Invalid derivation: {}", message),
             }
-            break;
         }
     }
-    changed
-}
-
-// Compute the Follow set for the grammar
-fn compute_follow(grammar: &Grammar, start_symbol: &str) -> HashMap<String, HashSet<String>> {
-    let mut follow: HashMap<String, HashSet<String>> = HashMap::new();
-
-    for non_terminal in grammar.keys() {
-        follow.insert(non_terminal.clone(), HashSet::new());
-    }
 
-    follow.get_mut(start_symbol).unwrap().insert("$".to_owned());
-
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for (non_terminal, productions) in grammar.iter() {
-            for production in productions {
-                if update_follow_set(grammar, &mut follow, non_terminal, production) {
-                    changed = true;
-                }
+    // Lower the concrete tree to a left-associative expression and evaluate it; the leaves are all
+    // `number`, so their values are supplied in order
+    let input: Vec<&str> = "number - number - number * number".split_whitespace().collect();
+    let (_, cst) = parse(&grammar, &parsing_table, &first, &follow, "E", &input, RecoveryOptions::default());
+    match cst.as_ref().map(lower_expression) {
+        Some(Ok(expr)) => {
+            println!("Expression: {}", expr_to_string(&expr));
+            let mut values = [10, 3, 2, 4].into_iter();
+            match evaluate(&expr, &mut |_| values.next()) {
+                Ok(value) => println!("Value with number = 10, 3, 2, 4: {}", value),
+                Err(message) => println!("Evaluation failed: {}", message),
             }
         }
+        Some(Err(message)) => println!("Lowering failed: {}", message),
+        None => println!("No tree to lower"),
     }
 
-    follow
-}
-
-// Update the Follow set for a given production
-fn update_follow_set(
-    grammar: &Grammar,
-    follow: &mut HashMap<String, HashSet<String>>,
-    non_terminal: &String,
-    production: &Vec<String>,
-) -> bool {
-    let mut changed = false;
-    for (i, symbol) in production.iter().enumerate() {
-        if grammar.contains_key(symbol) {
-            let mut follow_set = follow.get(symbol).unwrap().clone();
-            if i + 1 < production.len() {
-                let next_symbol = &production[i + 1];
-                if grammar.contains_key(next_symbol) {
-                    let first_set = compute_first(grammar).get(next_symbol).unwrap().clone();
-                    follow_set.extend(first_set.iter().filter(|x| **x != "").cloned());
-                    if first_set.contains(&"".to_owned()) {
-                        follow_set.extend(follow.get(non_terminal).unwrap().clone());
-                    }
-                } else {
-                    follow_set.insert(next_symbol.clone());
+    // Real source text through Newlexer: the leaves keep their lexemes, so the tree can be evaluated directly
+    let source = "12 + (3 % 4) * 5 / 2";
+    match tokenize(source, &TerminalMap::expression()) {
+        Ok(tokens) => {
+            let (errors, cst) = parse_source(&grammar, &parsing_table, &first, &follow, "E", &tokens, RecoveryOptions::default());
+            println!("Parsing result: {}", if errors.is_empty() { "Success" } else { "Failure" });
+            if let Some(cst) = &cst {
+                print!("{}", render_tree(cst, tree_format));
+                match lower_expression(cst).and_then(|expr| evaluate(&expr, &mut |leaf| leaf.parse().ok())) {
+                    Ok(value) => println!("{} = {}", source, value),
+                    Err(message) => println!("Evaluation failed: {}", message),
                 }
-            } else {
-                follow_set.extend(follow.get(non_terminal).unwrap().clone());
-            }
-            let len_before = follow.get(symbol).unwrap().len();
-            follow.get_mut(symbol).unwrap().extend(follow_set.clone());
-            let len_after = follow.get(symbol).unwrap().len();
-            if len_before != len_after {
-                changed = true;
             }
         }
+        Err(message) => println!("Lexical error: {}", message),
     }
-    changed
-}
-
-// Build the parsing table for the grammar
-fn build_parsing_table(
-    grammar: &Grammar,
-    first: &HashMap<String, HashSet<String>>,
-    follow: &HashMap<String, HashSet<String>>
-) -> HashMap<(String, String), Vec<String>> {
-    let mut table: HashMap<(String, String), Vec<String>> = HashMap::new();
 
-    for (non_terminal, productions) in grammar.iter() {
-        for production in productions {
-            let first_set = compute_first_for_production(production, first);
-            for terminal in first_set.iter().filter(|&&ref x| x != "") {
-                table.insert((non_terminal.clone(), terminal.clone()), production.clone());
-            }
-            if first_set.contains(&"".to_owned()) {
-                for terminal in follow.get(non_terminal).unwrap() {
-                    // Only add ε production if no other production matches
-                    if !table.contains_key(&(non_terminal.clone(), terminal.clone())) {
-                        table.insert((non_terminal.clone(), terminal.clone()), production.clone());
-                    }
-                }
-            }
-        }
+    // Erroneous input: all errors are reported and the partial tree marks where they occurred
+    let input: Vec<&str> = "number + * number ( number".split_whitespace().collect();
+    let (errors, ast) = parse(&grammar, &parsing_table, &first, &follow, "E", &input, RecoveryOptions::default());
+    for error in &errors {
+        println!("{}", error);
     }
-
-    table
-}
-
-// Compute the First set for a given production
-fn compute_first_for_production(
-    production: &Vec<String>,
-    first: &HashMap<String, HashSet<String>>,
-) -> HashSet<String> {
-    let mut result = HashSet::new();
-    for symbol in production {
-        if first.contains_key(symbol) {
-            let first_set = first.get(symbol).unwrap();
-            result.extend(first_set.clone());
-            if !first_set.contains(&"".to_owned()) {
-                break;
-            }
-        } else {
-            result.insert(symbol.clone());
-            break;
-        }
+    println!("Parsing result: {}", if errors.is_empty() { "Success" } else { "Failure" });
+    if let Some(ast) = &ast {
+        print!("{}", render_tree(ast, tree_format));
     }
-    result
 }
@@ -0,0 +1,245 @@
+use crate::ASTNode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFormat {
+    // Plain indentation
+    Text,
+    // Indented with ASCII branches: |-- and `--
+    Ascii,
+    // Indented with Unicode box-drawing branches: ├── and └──
+    Unicode,
+    Dot,
+    Typst,
+    Mermaid,
+}
+
+impl TreeFormat {
+    pub fn from_name(name: &str) -> Option<TreeFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Some(TreeFormat::Text),
+            "ascii" => Some(TreeFormat::Ascii),
+            "unicode" | "box" => Some(TreeFormat::Unicode),
+            "dot" | "graphviz" => Some(TreeFormat::Dot),
+            "typst" | "typ" => Some(TreeFormat::Typst),
+            "mermaid" | "mmd" => Some(TreeFormat::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+pub fn render_tree(tree: &ASTNode, format: TreeFormat) -> String {
+    match format {
+        TreeFormat::Text => tree_to_string("", tree),
+        TreeFormat::Ascii => tree_to_branches(tree, false),
+        TreeFormat::Unicode => tree_to_branches(tree, true),
+        TreeFormat::Dot => tree_to_dot(tree),
+        TreeFormat::Typst => format!("#{}\n", tree_to_typst("", tree)),
+        TreeFormat::Mermaid => tree_to_mermaid(tree),
+    }
+}
+
+// What a node shows: its label, the lexeme of a leaf read from source text, or the error message
+fn node_text(tree: &ASTNode) -> String {
+    match tree {
+        ASTNode::Terminal(label, Some(token)) if token.lexeme != *label => {
+            format!("{} ({} at {}:{})", label, token.lexeme, token.span.start.0, token.span.start.1)
+        }
+        ASTNode::NonTerminal(label, _) | ASTNode::Terminal(label, _) => label.clone(),
+        ASTNode::Error(message) => format!("<error: {}>", message),
+    }
+}
+
+// Children to draw; an ε-production gets an explicit ε leaf so the derivation stays visible
+fn display_children(tree: &ASTNode) -> Vec<ASTNode> {
+    match tree {
+        ASTNode::NonTerminal(_, children) if children.is_empty() => vec![ASTNode::Terminal("ε".to_string(), None)],
+        ASTNode::NonTerminal(_, children) => children.clone(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn tree_to_string(indent: &str, tree: &ASTNode) -> String {
+    let mut result = format!("{}{}\n", indent, node_text(tree));
+    for child in display_children(tree) {
+        result.push_str(&tree_to_string(&format!("{indent}  "), &child));
+    }
+    result
+}
+
+fn tree_to_branches(tree: &ASTNode, unicode: bool) -> String {
+    fn walk(tree: &ASTNode, prefix: &str, unicode: bool, out: &mut String) {
+        let children = display_children(tree);
+        for (idx, child) in children.iter().enumerate() {
+            let last = idx + 1 == children.len();
+            let (branch, continuation) = match (unicode, last) {
+                (true, false) => ("├── ", "│   "),
+                (true, true) => ("└── ", "    "),
+                (false, false) => ("|-- ", "|   "),
+                (false, true) => ("`-- ", "    "),
+            };
+            out.push_str(&format!("{}{}{}\n", prefix, branch, node_text(child)));
+            walk(child, &format!("{}{}", prefix, continuation), unicode, out);
+        }
+    }
+
+    let mut out = format!("{}\n", node_text(tree));
+    walk(tree, "", unicode, &mut out);
+    out
+}
+
+fn escape_quotes(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn tree_to_dot(tree: &ASTNode) -> String {
+    fn walk(tree: &ASTNode, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let style = match tree {
+            ASTNode::NonTerminal(..) => "",
+            ASTNode::Terminal(..) => ", shape=box",
+            ASTNode::Error(_) => ", shape=box, color=red, fontcolor=red",
+        };
+        out.push_str(&format!("  n{} [label=\"{}\"{}];\n", id, escape_quotes(&node_text(tree)), style));
+        for child in display_children(tree) {
+            let child_id = walk(&child, next_id, out);
+            out.push_str(&format!("  n{} -> n{};\n", id, child_id));
+        }
+        id
+    }
+
+    let mut out = String::from("digraph ParseTree {\n  node [shape=plaintext];\n");
+    walk(tree, &mut 0, &mut out);
+    out.push_str("}\n");
+    out
+}
+
+// Same shape as parseTreeGenerator's tree_to_typst, for a `tree` function defined in the Typst document
+pub fn tree_to_typst(indent: &str, tree: &ASTNode) -> String {
+    let children = display_children(tree);
+    let label = escape_quotes(&node_text(tree));
+    if children.is_empty() {
+        return format!("{indent}tree(\"{}\")", label);
+    }
+    let new_indent = format!("{indent}  ");
+    let children_typst: Vec<String> = children.iter().map(|child| tree_to_typst(&new_indent, child)).collect();
+    format!("{indent}tree(\"{}\",\n{}\n{indent})", label, children_typst.join(",\n"))
+}
+
+fn tree_to_mermaid(tree: &ASTNode) -> String {
+    fn walk(tree: &ASTNode, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        // Mermaid has no string escapes and reads labels as HTML, so quotes and angle brackets are entities
+        let label = node_text(tree).replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;");
+        match tree {
+            ASTNode::NonTerminal(..) => out.push_str(&format!("  n{}([\"{}\"])\n", id, label)),
+            ASTNode::Terminal(..) => out.push_str(&format!("  n{}[\"{}\"]\n", id, label)),
+            ASTNode::Error(_) => {
+                out.push_str(&format!("  n{}[\"{}\"]\n", id, label));
+                out.push_str(&format!("  style n{} stroke:red,color:red\n", id));
+            }
+        }
+        for child in display_children(tree) {
+            let child_id = walk(&child, next_id, out);
+            out.push_str(&format!("  n{} --> n{}\n", id, child_id));
+        }
+        id
+    }
+
+    let mut out = String::from("graph TD\n");
+    walk(tree, &mut 0, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::SourceToken;
+    use newlexer::tokens::Span;
+
+    // F -> ( E' "  with E' -> ε, a leaf read from source and an error whose message has quotes
+    fn tree() -> ASTNode {
+        let quote = SourceToken {
+            terminal: "\"".to_string(),
+            lexeme: "'\"'".to_string(),
+            span: Span { start: (1, 3), end: (1, 6) },
+        };
+        ASTNode::NonTerminal(
+            "F".to_string(),
+            vec![
+                ASTNode::Terminal("(".to_string(), None),
+                ASTNode::NonTerminal("E'".to_string(), vec![]),
+                ASTNode::Terminal("\"".to_string(), Some(quote)),
+                ASTNode::Error("expected \"x\\y\"".to_string()),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_indented_formats() {
+        assert_eq!(
+            render_tree(&tree(), TreeFormat::Text),
+            "F\n  (\n  E'\n    ε\n  \" ('\"' at 1:3)\n  <error: expected \"x\\y\">\n"
+        );
+        assert_eq!(
+            render_tree(&tree(), TreeFormat::Ascii),
+            "F\n|-- (\n|-- E'\n|   `-- ε\n|-- \" ('\"' at 1:3)\n`-- <error: expected \"x\\y\">\n"
+        );
+        assert_eq!(
+            render_tree(&tree(), TreeFormat::Unicode),
+            "F\n├── (\n├── E'\n│   └── ε\n├── \" ('\"' at 1:3)\n└── <error: expected \"x\\y\">\n"
+        );
+    }
+
+    #[test]
+    fn test_graph_formats_escape_labels() {
+        assert_eq!(
+            render_tree(&tree(), TreeFormat::Dot),
+            r#"digraph ParseTree {
+  node [shape=plaintext];
+  n0 [label="F"];
+  n1 [label="(", shape=box];
+  n0 -> n1;
+  n2 [label="E'"];
+  n3 [label="ε", shape=box];
+  n2 -> n3;
+  n0 -> n2;
+  n4 [label="\" ('\"' at 1:3)", shape=box];
+  n0 -> n4;
+  n5 [label="<error: expected \"x\\y\">", shape=box, color=red, fontcolor=red];
+  n0 -> n5;
+}
+"#
+        );
+        assert_eq!(
+            render_tree(&tree(), TreeFormat::Typst),
+            r#"#tree("F",
+  tree("("),
+  tree("E'",
+    tree("ε")
+  ),
+  tree("\" ('\"' at 1:3)"),
+  tree("<error: expected \"x\\y\">")
+)
+"#
+        );
+        assert_eq!(
+            render_tree(&tree(), TreeFormat::Mermaid),
+            r##"graph TD
+  n0(["F"])
+  n1["("]
+  n0 --> n1
+  n2(["E'"])
+  n3["ε"]
+  n2 --> n3
+  n0 --> n2
+  n4["#quot; ('#quot;' at 1:3)"]
+  n0 --> n4
+  n5["#lt;error: expected #quot;x\y#quot;#gt;"]
+  style n5 stroke:red,color:red
+  n0 --> n5
+"##
+        );
+    }
+}
@@ -0,0 +1,398 @@
+pub use ll1parser::{derivation, recovery, source};
+
+use std::collections::{HashMap, HashSet};
+
+use recovery::{expected_terminals, token_at, Recovery, RecoveryOptions, RecoveryStrategy, SyntaxError};
+
+pub type Grammar = HashMap<String, Vec<Vec<String>>>;
+
+struct Parser<'a> {
+    grammar: &'a Grammar,
+    parsing_table: &'a HashMap<(String, String), Vec<String>>,
+    first: &'a HashMap<String, HashSet<String>>,
+    follow: &'a HashMap<String, HashSet<String>>,
+    options: RecoveryOptions,
+    tokens: &'a [&'a str],
+    position: usize,
+    errors: Vec<SyntaxError>,
+    derivation: Vec<(String, Vec<String>)>,
+}
+
+// LL(1) parser main function, returning every syntax error found and the productions used, in the order of
+// a leftmost derivation; the input is accepted when there are no errors
+pub fn parse(
+    grammar: &Grammar,
+    parsing_table: &HashMap<(String, String), Vec<String>>,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>,
+    start_symbol: &str,
+    input: &[&str],
+    options: RecoveryOptions,
+) -> (Vec<SyntaxError>, Vec<(String, Vec<String>)>) {
+    if let Some(error) = SyntaxError::end_marker_in_input(input) {
+        return (vec![error], Vec::new());
+    }
+    let mut tokens = input.to_vec();
+    tokens.push("$");
+    let mut parser = Parser {
+        grammar,
+        parsing_table,
+        first,
+        follow,
+        options,
+        tokens: &tokens,
+        position: 0,
+        errors: Vec::new(),
+        derivation: Vec::new(),
+    };
+    if parser.parse_non_terminal(start_symbol) && parser.lookahead() != "$" {
+        let error = SyntaxError::trailing(parser.position, parser.lookahead());
+        parser.report(error);
+    }
+    (parser.errors, parser.derivation)
+}
+
+impl<'a> Parser<'a> {
+    fn lookahead(&self) -> &'a str {
+        token_at(self.tokens, self.position)
+    }
+
+    fn log(&self, message: String) {
+        if self.options.verbose {
+            println!("{}", message);
+        }
+    }
+
+    fn report(&mut self, error: SyntaxError) {
+        self.log(format!("Error: {}", error.message));
+        self.errors.push(error);
+    }
+
+    // Error for a lookahead that does not fit; `expected` is what would have been accepted
+    fn unexpected(&self, expected: &str) -> SyntaxError {
+        SyntaxError::unexpected(self.position, self.lookahead(), expected)
+    }
+
+    // Skip input up to `position`, reporting each discarded token
+    fn skip_to(&mut self, position: usize) {
+        while self.position < position {
+            self.log(format!("Skipping: {}", self.lookahead()));
+            self.position += 1;
+        }
+    }
+
+    // Recursive function to parse non-terminal symbols; returns false only when parsing has to stop
+    fn parse_non_terminal(&mut self, symbol: &str) -> bool {
+        loop {
+            let lookahead = self.lookahead();
+            if let Some(production) = self.parsing_table.get(&(symbol.to_string(), lookahead.to_string())) {
+                self.log(format!("Using production: {} -> {:?}", symbol, production));
+                self.derivation.push((symbol.to_string(), production.clone()));
+                for prod_symbol in production {
+                    if prod_symbol.is_empty() {
+                        self.log(format!("Parsing {} -> ε", symbol));
+                        continue;
+                    }
+
+                    let keep_going = if self.grammar.contains_key(prod_symbol) {
+                        self.parse_non_terminal(prod_symbol)
+                    } else {
+                        self.match_terminal(prod_symbol)
+                    };
+                    if !keep_going {
+                        return false;
+                    }
+                }
+                return true;
+            }
+
+            self.report(self.unexpected(&expected_terminals(symbol, self.parsing_table)));
+            if self.options.strategy == RecoveryStrategy::Abort {
+                return false;
+            }
+            match self.options.recover_non_terminal(
+                symbol,
+                self.tokens,
+                self.position,
+                self.parsing_table,
+                self.first,
+                self.follow,
+            ) {
+                Recovery::Delete => self.skip_to(self.position + 1),
+                Recovery::Resume(position) => self.skip_to(position),
+                Recovery::Pop(position) => {
+                    self.skip_to(position);
+                    self.log(format!("Giving up on {}", symbol));
+                    return true;
+                }
+            }
+        }
+    }
+
+    // Function to match terminal symbols; a mismatch is repaired by deleting or inserting a token
+    fn match_terminal(&mut self, expected: &str) -> bool {
+        if self.lookahead() == expected {
+            self.log(format!("Matched terminal: {}", expected));
+            self.position += 1; // Consume input
+            return true;
+        }
+        self.report(self.unexpected(expected));
+        if self.options.strategy == RecoveryStrategy::Abort {
+            return false;
+        }
+        if self.options.delete_instead_of_insert(expected, self.tokens, self.position) {
+            self.skip_to(self.position + 2);
+        } else {
+            self.log(format!("Inserting missing: {}", expected));
+        }
+        true
+    }
+}
+
+// Compute the First set for the grammar
+pub fn compute_first(grammar: &Grammar) -> HashMap<String, HashSet<String>> {
+    let mut first: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for non_terminal in grammar.keys() {
+        first.insert(non_terminal.clone(), HashSet::new());
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                if update_first_set(grammar, &mut first, non_terminal, production) {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    first
+}
+
+// Update the First set for a given production
+pub fn update_first_set(
+    grammar: &Grammar,
+    first: &mut HashMap<String, HashSet<String>>,
+    non_terminal: &String,
+    production: &[String],
+) -> bool {
+    let mut changed = false;
+    for symbol in production {
+        if grammar.contains_key(symbol) {
+            let first_set = first.get(symbol).unwrap().clone();
+            let len_before = first.get(non_terminal).unwrap().len();
+            first.get_mut(non_terminal).unwrap().extend(first_set.clone());
+            let len_after = first.get(non_terminal).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+            if !first_set.contains("") {
+                break;
+            }
+        } else {
+            let len_before = first.get(non_terminal).unwrap().len();
+            first.get_mut(non_terminal).unwrap().insert(symbol.clone());
+            let len_after = first.get(non_terminal).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+            break;
+        }
+    }
+    changed
+}
+
+// Compute the Follow set for the grammar
+pub fn compute_follow(grammar: &Grammar, start_symbol: &str) -> HashMap<String, HashSet<String>> {
+    let mut follow: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for non_terminal in grammar.keys() {
+        follow.insert(non_terminal.clone(), HashSet::new());
+    }
+
+    follow.get_mut(start_symbol).unwrap().insert("$".to_string());
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, productions) in grammar.iter() {
+            for production in productions {
+                if update_follow_set(grammar, &mut follow, non_terminal, production) {
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    follow
+}
+
+// Update the Follow set for a given production
+pub fn update_follow_set(
+    grammar: &Grammar,
+    follow: &mut HashMap<String, HashSet<String>>,
+    non_terminal: &String,
+    production: &[String],
+) -> bool {
+    let mut changed = false;
+    for (i, symbol) in production.iter().enumerate() {
+        if grammar.contains_key(symbol) {
+            let mut follow_set = follow.get(symbol).unwrap().clone();
+            if i + 1 < production.len() {
+                let next_symbol = &production[i + 1];
+                if grammar.contains_key(next_symbol) {
+                    let first_set = compute_first(grammar).get(next_symbol).unwrap().clone();
+                    follow_set.extend(first_set.iter().filter(|x| !x.is_empty()).cloned());
+                    if first_set.contains("") {
+                        follow_set.extend(follow.get(non_terminal).unwrap().clone());
+                    }
+                } else {
+                    follow_set.insert(next_symbol.clone());
+                }
+            } else {
+                follow_set.extend(follow.get(non_terminal).unwrap().clone());
+            }
+            let len_before = follow.get(symbol).unwrap().len();
+            follow.get_mut(symbol).unwrap().extend(follow_set.clone());
+            let len_after = follow.get(symbol).unwrap().len();
+            if len_before != len_after {
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+// Build the parsing table for the grammar
+pub fn build_parsing_table(
+    grammar: &Grammar,
+    first: &HashMap<String, HashSet<String>>,
+    follow: &HashMap<String, HashSet<String>>
+) -> HashMap<(String, String), Vec<String>> {
+    let mut table: HashMap<(String, String), Vec<String>> = HashMap::new();
+
+    for (non_terminal, productions) in grammar.iter() {
+        for production in productions {
+            let first_set = compute_first_for_production(production, first);
+            for terminal in first_set.iter().filter(|&x| !x.is_empty()) {
+                table.insert((non_terminal.clone(), terminal.clone()), production.clone());
+            }
+            if first_set.contains("") {
+                for terminal in follow.get(non_terminal).unwrap() {
+                    table.insert((non_terminal.clone(), terminal.clone()), production.clone());
+                }
+            }
+        }
+    }
+
+    table
+}
+
+// Compute the First set for a given production
+pub fn compute_first_for_production(
+    production: &[String],
+    first: &HashMap<String, HashSet<String>>,
+) -> HashSet<String> {
+    let mut result = HashSet::new();
+    for symbol in production {
+        if first.contains_key(symbol) {
+            let first_set = first.get(symbol).unwrap();
+            result.extend(first_set.clone());
+            if !first_set.contains("") {
+                break;
+            }
+        } else {
+            result.insert(symbol.clone());
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use recovery::ErrorKind;
+
+    // The errors reported and the productions of the partial leftmost derivation
+    fn recover(input: &str, strategy: RecoveryStrategy) -> (Vec<String>, Vec<String>) {
+        let symbols = |text: &str| text.split_whitespace().map(String::from).collect::<Vec<String>>();
+        let grammar: Grammar = [
+            ("E".to_string(), vec![symbols("T E'")]),
+            ("E'".to_string(), vec![symbols("+ T E'"), vec!["".to_string()]]),
+            ("T".to_string(), vec![symbols("F T'")]),
+            ("T'".to_string(), vec![symbols("* F T'"), vec!["".to_string()]]),
+            ("F".to_string(), vec![symbols("( E )"), symbols("id")]),
+        ]
+        .into_iter()
+        .collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "E");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let input: Vec<&str> = input.split_whitespace().collect();
+        let options = RecoveryOptions { strategy, verbose: false, ..RecoveryOptions::default() };
+        let (errors, derivation) = parse(&grammar, &table, &first, &follow, "E", &input, options);
+        (
+            errors.iter().map(ToString::to_string).collect(),
+            derivation.iter().map(|(lhs, rhs)| format!("{} -> {}", lhs, rhs.join(" ")).trim_end().to_string()).collect(),
+        )
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_recovered_derivations() {
+        // The missing `)` is assumed, so the derivation is the one for `( id + id )`
+        let (errors, derivation) = recover("( id + id", RecoveryStrategy::PanicMode);
+        assert_eq!(errors, strings(&["error at end of input: unexpected end of input, expected )"]));
+        assert_eq!(derivation, recover("( id + id )", RecoveryStrategy::Abort).1);
+
+        // Skipping the stray `*` leaves the derivation of `id + id`
+        let (errors, derivation) = recover("id + * id", RecoveryStrategy::PanicMode);
+        assert_eq!(errors, strings(&["error at token 3 ('*'): expected one of (, id, found *"]));
+        assert_eq!(derivation, recover("id + id", RecoveryStrategy::Abort).1);
+
+        // Panic mode gives up on F at `)` and the derivation stops there; phrase level deletes the `)`
+        let expected = "error at token 3 (')'): expected one of (, id, found )";
+        assert_eq!(
+            recover("id * ) id + id", RecoveryStrategy::PanicMode),
+            (
+                strings(&[expected, "error at token 3 (')'): trailing input starting at )"]),
+                strings(&["E -> T E'", "T -> F T'", "F -> id", "T' -> * F T'", "T' ->", "E' ->"])
+            )
+        );
+        let (errors, derivation) = recover("id * ) id + id", RecoveryStrategy::PhraseLevel);
+        assert_eq!(errors, strings(&[expected]));
+        assert_eq!(derivation, recover("id * id + id", RecoveryStrategy::Abort).1);
+    }
+    #[test]
+    fn test_error_kinds() {
+        let grammar: Grammar = [(
+            "S".to_string(),
+            vec![vec!["a".to_string(), "S".to_string()], vec!["b".to_string()]],
+        )]
+        .into_iter()
+        .collect();
+        let first = compute_first(&grammar);
+        let follow = compute_follow(&grammar, "S");
+        let table = build_parsing_table(&grammar, &first, &follow);
+        let kinds = |input: &str| -> Vec<ErrorKind> {
+            let input: Vec<&str> = input.split_whitespace().collect();
+            let options = RecoveryOptions { verbose: false, ..RecoveryOptions::default() };
+            let (errors, _) = parse(&grammar, &table, &first, &follow, "S", &input, options);
+            errors.into_iter().map(|error| error.kind).collect()
+        };
+
+        assert_eq!(kinds("a a b"), vec![]);
+        assert_eq!(kinds("a c b"), vec![ErrorKind::UnexpectedToken]);
+        assert_eq!(kinds("a a"), vec![ErrorKind::UnexpectedEnd]);
+        assert_eq!(kinds("b a"), vec![ErrorKind::TrailingInput]);
+        // A literal `$` is rejected before parsing instead of ending the input after `b`
+        assert_eq!(kinds("b $ a"), vec![ErrorKind::EndMarkerInInput]);
+    }
+}
@@ -0,0 +1,143 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::lr0::AugmentedGrammar;
+use crate::table::{conflict_to_string, Conflict, LrAction, LrTable};
+
+// Shortest terminal string derived from each non-terminal; non-terminals that derive no
+// terminal string at all are missing from the map
+pub fn shortest_yields(grammar: &AugmentedGrammar) -> HashMap<String, Vec<String>> {
+    let mut yields: HashMap<String, Vec<String>> = HashMap::new();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (lhs, rhs) in &grammar.productions {
+            let mut candidate = Vec::new();
+            let mut complete = true;
+            for symbol in rhs {
+                if !grammar.is_non_terminal(symbol) {
+                    candidate.push(symbol.clone());
+                } else if let Some(symbol_yield) = yields.get(symbol) {
+                    candidate.extend(symbol_yield.iter().cloned());
+                } else {
+                    complete = false;
+                    break;
+                }
+            }
+            if complete && yields.get(lhs).is_none_or(|current| candidate.len() < current.len()) {
+                yields.insert(lhs.clone(), candidate);
+                changed = true;
+            }
+        }
+    }
+
+    yields
+}
+
+// Symbols on the edges out of a state: shifts on terminals, GOTO on non-terminals
+fn edges(table: &LrTable, state: usize) -> Vec<(String, usize)> {
+    let shifts = table
+        .action
+        .range((state, String::new())..)
+        .take_while(|((from, _), _)| *from == state)
+        .flat_map(|((_, terminal), actions)| {
+            actions.iter().filter_map(move |action| match action {
+                LrAction::Shift(target) => Some((terminal.clone(), *target)),
+                _ => None,
+            })
+        });
+    let gotos = table
+        .goto
+        .range((state, String::new())..)
+        .take_while(|((from, _), _)| *from == state)
+        .map(|((_, non_terminal), target)| (non_terminal.clone(), *target));
+    shifts.chain(gotos).collect()
+}
+
+// Viable prefix leading from state 0 to `target` whose terminal yield is shortest, found with
+// Dijkstra's algorithm where each edge costs the length of the yield of its symbol
+pub fn shortest_prefix(
+    grammar: &AugmentedGrammar,
+    table: &LrTable,
+    yields: &HashMap<String, Vec<String>>,
+    target: usize,
+) -> Option<Vec<String>> {
+    let mut best: HashMap<usize, (usize, Vec<String>)> = HashMap::from([(0, (0, Vec::new()))]);
+    let mut queue = BinaryHeap::from([Reverse((0, 0))]);
+
+    while let Some(Reverse((cost, state))) = queue.pop() {
+        if state == target {
+            return best.remove(&state).map(|(_, prefix)| prefix);
+        }
+        if best[&state].0 < cost {
+            continue;
+        }
+        for (symbol, next) in edges(table, state) {
+            let weight = if grammar.is_non_terminal(&symbol) {
+                match yields.get(&symbol) {
+                    Some(symbol_yield) => symbol_yield.len(),
+                    None => continue,
+                }
+            } else {
+                1
+            };
+            if best.get(&next).is_none_or(|(known, _)| cost + weight < *known) {
+                let mut prefix = best[&state].1.clone();
+                prefix.push(symbol);
+                best.insert(next, (cost + weight, prefix));
+                queue.push(Reverse((cost + weight, next)));
+            }
+        }
+    }
+
+    None
+}
+
+// The conflict, the viable prefix that reaches its state and the shortest terminal string that
+// reaches it too, followed by the conflicting lookahead; the dot marks where the parser has to choose
+// between the conflicting actions. Whether the input can go on to a complete sentence after the
+// lookahead is not checked, so this is a prefix, not a full counterexample.
+pub fn explain_conflict(grammar: &AugmentedGrammar, table: &LrTable, conflict: &Conflict) -> String {
+    let mut out = conflict_to_string(grammar, conflict);
+    let yields = shortest_yields(grammar);
+    let Some(prefix) = shortest_prefix(grammar, table, &yields, conflict.state) else {
+        out.push_str("\n  state is unreachable");
+        return out;
+    };
+
+    let mut input: Vec<String> = Vec::new();
+    for symbol in &prefix {
+        match yields.get(symbol) {
+            Some(symbol_yield) => input.extend(symbol_yield.iter().cloned()),
+            None => input.push(symbol.clone()),
+        }
+    }
+    input.push("·".to_string());
+    input.push(conflict.terminal.clone());
+
+    let prefix = if prefix.is_empty() { "ε".to_string() } else { prefix.join(" ") };
+    out.push_str(&format!("\n  viable prefix: {}", prefix));
+    out.push_str(&format!("\n  input reaching the state: {}", input.join(" ")));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lr0::canonical_collection;
+    use crate::slr::build_slr_table;
+    use crate::spec::parse_spec;
+    use ll1parser::compute_follow;
+
+    #[test]
+    fn test_explain_assignment_conflict() {
+        let spec = parse_spec("S -> L = R | R\nL -> * R | id\nR -> L").unwrap();
+        let grammar = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let table = build_slr_table(&grammar, &canonical_collection(&grammar), &compute_follow(&spec.grammar, "S"));
+        let conflicts = table.conflicts();
+        let explanation = explain_conflict(&grammar, &table, &conflicts[0]);
+        let lines: Vec<&str> = explanation.lines().skip(1).collect();
+        assert_eq!(lines, vec!["  viable prefix: L", "  input reaching the state: id · ="]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::Grammar;
+
+// The grammar with its productions numbered and an extra start production S' -> S as number 0.
+// ε-productions are stored with an empty right-hand side.
+pub struct AugmentedGrammar {
+    pub start: String,
+    pub productions: Vec<(String, Vec<String>)>,
+}
+
+impl AugmentedGrammar {
+    pub fn new(grammar: &Grammar, start_symbol: &str) -> Self {
+        let mut start = format!("{}'", start_symbol);
+        while grammar.contains_key(&start) {
+            start.push('\'');
+        }
+
+        // Start symbol first, the rest alphabetically, so production numbers are stable between runs
+        let mut non_terminals: Vec<&String> = grammar.keys().filter(|nt| nt.as_str() != start_symbol).collect();
+        non_terminals.sort();
+        let start_symbol = start_symbol.to_string();
+        non_terminals.insert(0, &start_symbol);
+
+        let mut productions = vec![(start.clone(), vec![start_symbol.clone()])];
+        for non_terminal in non_terminals {
+            for production in &grammar[non_terminal] {
+                let rhs = production.iter().filter(|symbol| !symbol.is_empty()).cloned().collect();
+                productions.push((non_terminal.clone(), rhs));
+            }
+        }
+
+        Self { start, productions }
+    }
+
+    pub fn is_non_terminal(&self, symbol: &str) -> bool {
+        self.productions.iter().any(|(lhs, _)| lhs == symbol)
+    }
+
+    // Non-terminals in order of first appearance, without the augmented start symbol
+    pub fn non_terminals(&self) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        for (lhs, _) in &self.productions {
+            if *lhs != self.start && !result.contains(lhs) {
+                result.push(lhs.clone());
+            }
+        }
+        result
+    }
+
+    // Terminals sorted, with the end marker `$` last
+    pub fn terminals(&self) -> Vec<String> {
+        let terminals: BTreeSet<&String> = self
+            .productions
+            .iter()
+            .flat_map(|(_, rhs)| rhs.iter())
+            .filter(|symbol| !self.is_non_terminal(symbol))
+            .collect();
+        let mut result: Vec<String> = terminals.into_iter().cloned().collect();
+        result.push("$".to_string());
+        result
+    }
+
+    pub fn production_to_string(&self, index: usize) -> String {
+        let (lhs, rhs) = &self.productions[index];
+        if rhs.is_empty() {
+            format!("{} -> ε", lhs)
+        } else {
+            format!("{} -> {}", lhs, rhs.join(" "))
+        }
+    }
+}
+
+// An LR(0) item: production number and the position of the dot in its right-hand side
+pub type Item = (usize, usize);
+
+pub fn item_to_string(grammar: &AugmentedGrammar, (production, dot): Item) -> String {
+    let (lhs, rhs) = &grammar.productions[production];
+    let mut symbols: Vec<&str> = rhs.iter().map(String::as_str).collect();
+    symbols.insert(dot, "·");
+    format!("{} -> {}", lhs, symbols.join(" "))
+}
+
+// Symbol right after the dot, if the item is not complete
+pub fn next_symbol(grammar: &AugmentedGrammar, (production, dot): Item) -> Option<&String> {
+    grammar.productions[production].1.get(dot)
+}
+
+pub fn closure(grammar: &AugmentedGrammar, items: &BTreeSet<Item>) -> BTreeSet<Item> {
+    let mut result = items.clone();
+    let mut worklist: Vec<Item> = items.iter().cloned().collect();
+    while let Some(item) = worklist.pop() {
+        let Some(symbol) = next_symbol(grammar, item) else {
+            continue;
+        };
+        for (index, (lhs, _)) in grammar.productions.iter().enumerate() {
+            if lhs == symbol && result.insert((index, 0)) {
+                worklist.push((index, 0));
+            }
+        }
+    }
+    result
+}
+
+pub fn goto(grammar: &AugmentedGrammar, items: &BTreeSet<Item>, symbol: &str) -> BTreeSet<Item> {
+    let moved: BTreeSet<Item> = items
+        .iter()
+        .filter(|&&item| next_symbol(grammar, item).map(String::as_str) == Some(symbol))
+        .map(|&(production, dot)| (production, dot + 1))
+        .collect();
+    closure(grammar, &moved)
+}
+
+// The canonical collection of LR(0) item sets and the transitions between them; state 0 holds S' -> · S
+pub struct Lr0Automaton {
+    pub states: Vec<BTreeSet<Item>>,
+    pub transitions: BTreeMap<(usize, String), usize>,
+}
+
+pub fn canonical_collection(grammar: &AugmentedGrammar) -> Lr0Automaton {
+    let mut states = vec![closure(grammar, &BTreeSet::from([(0, 0)]))];
+    let mut transitions = BTreeMap::new();
+
+    let mut current = 0;
+    while current < states.len() {
+        let symbols: BTreeSet<String> = states[current]
+            .iter()
+            .filter_map(|&item| next_symbol(grammar, item).cloned())
+            .collect();
+        for symbol in symbols {
+            let target = goto(grammar, &states[current], &symbol);
+            let index = match states.iter().position(|state| *state == target) {
+                Some(index) => index,
+                None => {
+                    states.push(target);
+                    states.len() - 1
+                }
+            };
+            transitions.insert((current, symbol), index);
+        }
+        current += 1;
+    }
+
+    Lr0Automaton { states, transitions }
+}
+
+pub fn automaton_to_string(grammar: &AugmentedGrammar, automaton: &Lr0Automaton) -> String {
+    let mut out = String::new();
+    for (index, state) in automaton.states.iter().enumerate() {
+        out.push_str(&format!("I{}:\n", index));
+        for &item in state {
+            out.push_str(&format!("  {}\n", item_to_string(grammar, item)));
+        }
+        for ((from, symbol), to) in automaton.transitions.range((index, String::new())..) {
+            if *from != index {
+                break;
+            }
+            out.push_str(&format!("  goto({}) = I{}\n", symbol, to));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::parse_spec;
+
+    #[test]
+    fn test_expression_item_sets() {
+        let spec = parse_spec("E -> E + T | T\nT -> T * F | F\nF -> ( E ) | id").unwrap();
+        let grammar = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let automaton = canonical_collection(&grammar);
+
+        // The twelve item sets of the dragon book, numbered in the order they are discovered
+        assert_eq!(automaton.states.len(), 12);
+        assert_eq!(automaton.transitions.len(), 22);
+        assert_eq!(automaton.states[0].len(), 7);
+
+        let items = |state: usize| -> Vec<String> {
+            automaton.states[state].iter().map(|&item| item_to_string(&grammar, item)).collect()
+        };
+        let after_e = automaton.transitions[&(0, "E".to_string())];
+        assert_eq!(items(after_e), vec!["E' -> E ·", "E -> E · + T"]);
+        let after_t = automaton.transitions[&(after_e, "+".to_string())];
+        let after_t = automaton.transitions[&(after_t, "T".to_string())];
+        assert_eq!(items(after_t), vec!["E -> E + T ·", "T -> T · * F"]);
+        // Both ( E · ) and E · + T continue from the state after `( E`
+        let nested = automaton.transitions[&(automaton.transitions[&(0, "(".to_string())], "E".to_string())];
+        assert_eq!(items(nested), vec!["E -> E · + T", "F -> ( E · )"]);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use ll1parser::{compute_first, compute_first_for_production, Grammar};
+
+use crate::lr0::{item_to_string, next_symbol, AugmentedGrammar, Item};
+use crate::table::{LrAction, LrTable};
+
+// An LR(1) item: production number, position of the dot and the lookahead terminal
+pub type Lr1Item = (usize, usize, String);
+
+// FIRST sets of the non-terminals of the augmented grammar, computed by ll1parser, which writes an empty
+// right-hand side as [""]; "" marks a non-terminal that derives ε
+pub fn compute_first_sets(grammar: &AugmentedGrammar) -> HashMap<String, HashSet<String>> {
+    let mut rules: Grammar = HashMap::new();
+    for (lhs, rhs) in &grammar.productions {
+        let rhs = if rhs.is_empty() { vec![String::new()] } else { rhs.clone() };
+        rules.entry(lhs.clone()).or_default().push(rhs);
+    }
+    compute_first(&rules)
+}
+
+// FIRST(symbols lookahead): the lookahead counts when `symbols` derives ε
+fn first_with_lookahead(first: &HashMap<String, HashSet<String>>, symbols: &[String], lookahead: &str) -> HashSet<String> {
+    let mut result = compute_first_for_production(symbols, first);
+    if symbols.is_empty() || result.remove("") {
+        result.insert(lookahead.to_string());
+    }
+    result
+}
+
+pub fn closure_lr1(
+    grammar: &AugmentedGrammar,
+    first: &HashMap<String, HashSet<String>>,
+    items: &BTreeSet<Lr1Item>,
+) -> BTreeSet<Lr1Item> {
+    let mut result = items.clone();
+    let mut worklist: Vec<Lr1Item> = items.iter().cloned().collect();
+    while let Some((production, dot, lookahead)) = worklist.pop() {
+        let Some(symbol) = next_symbol(grammar, (production, dot)) else {
+            continue;
+        };
+        // [A -> α · B β, a] adds [B -> · γ, b] for every b in FIRST(β a)
+        let rest = &grammar.productions[production].1[dot + 1..];
+        let lookaheads = first_with_lookahead(first, rest, &lookahead);
+        for (index, (lhs, _)) in grammar.productions.iter().enumerate() {
+            if lhs != symbol {
+                continue;
+            }
+            for terminal in &lookaheads {
+                let item = (index, 0, terminal.clone());
+                if result.insert(item.clone()) {
+                    worklist.push(item);
+                }
+            }
+        }
+    }
+    result
+}
+
+pub fn goto_lr1(
+    grammar: &AugmentedGrammar,
+    first: &HashMap<String, HashSet<String>>,
+    items: &BTreeSet<Lr1Item>,
+    symbol: &str,
+) -> BTreeSet<Lr1Item> {
+    let moved: BTreeSet<Lr1Item> = items
+        .iter()
+        .filter(|(production, dot, _)| next_symbol(grammar, (*production, *dot)).map(String::as_str) == Some(symbol))
+        .map(|(production, dot, lookahead)| (*production, dot + 1, lookahead.clone()))
+        .collect();
+    closure_lr1(grammar, first, &moved)
+}
+
+// Item sets with lookaheads, either the canonical LR(1) collection or its LALR(1) merge
+pub struct Lr1Automaton {
+    pub states: Vec<BTreeSet<Lr1Item>>,
+    pub transitions: BTreeMap<(usize, String), usize>,
+}
+
+pub fn canonical_lr1_collection(grammar: &AugmentedGrammar) -> Lr1Automaton {
+    let first = compute_first_sets(grammar);
+    let start = BTreeSet::from([(0, 0, "$".to_string())]);
+    let mut states = vec![closure_lr1(grammar, &first, &start)];
+    let mut transitions = BTreeMap::new();
+
+    let mut current = 0;
+    while current < states.len() {
+        let symbols: BTreeSet<String> = states[current]
+            .iter()
+            .filter_map(|(production, dot, _)| next_symbol(grammar, (*production, *dot)).cloned())
+            .collect();
+        for symbol in symbols {
+            let target = goto_lr1(grammar, &first, &states[current], &symbol);
+            let index = match states.iter().position(|state| *state == target) {
+                Some(index) => index,
+                None => {
+                    states.push(target);
+                    states.len() - 1
+                }
+            };
+            transitions.insert((current, symbol), index);
+        }
+        current += 1;
+    }
+
+    Lr1Automaton { states, transitions }
+}
+
+// The LR(0) items of a state, without lookaheads
+pub fn core(state: &BTreeSet<Lr1Item>) -> BTreeSet<Item> {
+    state.iter().map(|(production, dot, _)| (*production, *dot)).collect()
+}
+
+// LALR(1) by merging the LR(1) states that share a core; merged states are numbered in order of
+// their first member, so state 0 stays the start state
+pub fn merge_cores(automaton: &Lr1Automaton) -> Lr1Automaton {
+    let mut cores: Vec<BTreeSet<Item>> = Vec::new();
+    let mut states: Vec<BTreeSet<Lr1Item>> = Vec::new();
+    let mut merged_index = Vec::new();
+
+    for state in &automaton.states {
+        let state_core = core(state);
+        let index = match cores.iter().position(|c| *c == state_core) {
+            Some(index) => index,
+            None => {
+                cores.push(state_core);
+                states.push(BTreeSet::new());
+                cores.len() - 1
+            }
+        };
+        states[index].extend(state.iter().cloned());
+        merged_index.push(index);
+    }
+
+    // States with the same core have transitions to states with the same core, so the targets agree
+    let transitions = automaton
+        .transitions
+        .iter()
+        .map(|((from, symbol), to)| ((merged_index[*from], symbol.clone()), merged_index[*to]))
+        .collect();
+
+    Lr1Automaton { states, transitions }
+}
+
+// Canonical LR(1) (or LALR(1), given merged states): reduce A -> α· only on the item's own lookaheads
+pub fn build_lr1_table(grammar: &AugmentedGrammar, automaton: &Lr1Automaton) -> LrTable {
+    let mut table = LrTable {
+        states: automaton.states.len(),
+        ..LrTable::default()
+    };
+
+    for ((state, symbol), &target) in &automaton.transitions {
+        if grammar.is_non_terminal(symbol) {
+            table.goto.insert((*state, symbol.clone()), target);
+        } else {
+            table.add_action(*state, symbol, LrAction::Shift(target));
+        }
+    }
+
+    for (state, items) in automaton.states.iter().enumerate() {
+        for (production, dot, lookahead) in items {
+            if next_symbol(grammar, (*production, *dot)).is_some() {
+                continue;
+            }
+            if *production == 0 {
+                table.add_action(state, "$", LrAction::Accept);
+            } else {
+                table.add_action(state, lookahead, LrAction::Reduce(*production));
+            }
+        }
+    }
+
+    table
+}
+
+// Items sharing a core are printed on one line with their lookaheads: E -> E · + T, $/+
+pub fn lr1_automaton_to_string(grammar: &AugmentedGrammar, automaton: &Lr1Automaton) -> String {
+    let mut out = String::new();
+    for (index, state) in automaton.states.iter().enumerate() {
+        out.push_str(&format!("I{}:\n", index));
+        let mut lookaheads: BTreeMap<Item, Vec<&str>> = BTreeMap::new();
+        for (production, dot, lookahead) in state {
+            lookaheads.entry((*production, *dot)).or_default().push(lookahead);
+        }
+        for (item, terminals) in lookaheads {
+            out.push_str(&format!("  {}, {}\n", item_to_string(grammar, item), terminals.join("/")));
+        }
+        for ((from, symbol), to) in automaton.transitions.range((index, String::new())..) {
+            if *from != index {
+                break;
+            }
+            out.push_str(&format!("  goto({}) = I{}\n", symbol, to));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grammar;
+
+    #[test]
+    fn test_assignment_grammar_is_lalr() {
+        // Not SLR(1), but the lookaheads separate `R -> L ·` from `S -> L · = R`
+        let g = grammar(&[
+            ("S", &[&["L", "=", "R"], &["R"]]),
+            ("L", &[&["*", "R"], &["id"]]),
+            ("R", &[&["L"]]),
+        ]);
+        let augmented = AugmentedGrammar::new(&g, "S");
+        let lr1 = canonical_lr1_collection(&augmented);
+        let lalr = merge_cores(&lr1);
+
+        assert_eq!(lr1.states.len(), 14);
+        assert_eq!(lalr.states.len(), 10);
+        assert!(build_lr1_table(&augmented, &lr1).conflicts().is_empty());
+        assert!(build_lr1_table(&augmented, &lalr).conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_merging_cores_can_add_reduce_reduce_conflicts() {
+        let g = grammar(&[
+            ("S", &[&["a", "A", "d"], &["b", "B", "d"], &["a", "B", "e"], &["b", "A", "e"]]),
+            ("A", &[&["c"]]),
+            ("B", &[&["c"]]),
+        ]);
+        let augmented = AugmentedGrammar::new(&g, "S");
+        let lr1 = canonical_lr1_collection(&augmented);
+        assert!(build_lr1_table(&augmented, &lr1).conflicts().is_empty());
+
+        let conflicts = build_lr1_table(&augmented, &merge_cores(&lr1)).conflicts();
+        let terminals: Vec<&str> = conflicts.iter().map(|conflict| conflict.terminal.as_str()).collect();
+        assert_eq!(terminals, vec!["d", "e"]);
+    }
+
+    #[test]
+    fn test_lookaheads_pass_through_nullable_symbols() {
+        // B derives ε, so A -> ε is reduced on `b` as well as on `$`
+        let g = grammar(&[("S", &[&["A", "B"]]), ("A", &[&["a"], &[""]]), ("B", &[&["b"], &[""]])]);
+        let augmented = AugmentedGrammar::new(&g, "S");
+        let first = compute_first_sets(&augmented);
+        let start = closure_lr1(&augmented, &first, &BTreeSet::from([(0, 0, "$".to_string())]));
+
+        let empty_a = augmented.productions.iter().position(|(lhs, rhs)| lhs == "A" && rhs.is_empty()).unwrap();
+        let lookaheads: Vec<&str> = start
+            .iter()
+            .filter(|(production, _, _)| *production == empty_a)
+            .map(|(_, _, lookahead)| lookahead.as_str())
+            .collect();
+        assert_eq!(lookaheads, vec!["$", "b"]);
+        assert!(build_lr1_table(&augmented, &canonical_lr1_collection(&augmented)).conflicts().is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+mod explain;
+mod lr0;
+mod lr1;
+mod precedence;
+mod slr;
+mod spec;
+mod table;
+
+use explain::explain_conflict;
+use ll1parser::{compute_follow, Grammar};
+use lr0::{automaton_to_string, canonical_collection, AugmentedGrammar};
+use lr1::{build_lr1_table, canonical_lr1_collection, lr1_automaton_to_string, merge_cores};
+use precedence::{resolution_to_string, resolve_conflicts};
+use slr::build_slr_table;
+use spec::parse_spec;
+use table::{parse, productions_to_string, render_stats, render_table, render_trace, table_stats, LrTable};
+
+fn main() {
+    let grammar: Grammar = [
+        ("E".to_string(), vec![vec!["E".to_string(), "+".to_string(), "T".to_string()], vec!["T".to_string()]]),
+        ("T".to_string(), vec![vec!["T".to_string(), "*".to_string(), "F".to_string()], vec!["F".to_string()]]),
+        ("F".to_string(), vec![vec!["(".to_string(), "E".to_string(), ")".to_string()], vec!["id".to_string()]]),
+    ].iter().cloned().collect();
+
+    let augmented = AugmentedGrammar::new(&grammar, "E");
+    let automaton = canonical_collection(&augmented);
+    let follow = compute_follow(&grammar, "E");
+
+    println!("Productions:\n{}", productions_to_string(&augmented));
+    println!("LR(0) item sets:\n{}", automaton_to_string(&augmented, &automaton));
+
+    let slr_table = build_slr_table(&augmented, &automaton, &follow);
+    println!("SLR(1) table:\n{}", render_table(&augmented, &slr_table));
+
+    let input: Vec<&str> = "id * id + id".split_whitespace().collect();
+    let (success, trace) = parse(&augmented, &slr_table, &input);
+    println!("Parsing trace:\n{}", render_trace(&trace));
+    println!("Parsing result: {}", if success { "Success" } else { "Failure" });
+
+    // The classic grammar that is LR(1) but not SLR(1): FOLLOW(R) contains `=`, so state 2 cannot decide
+    let assignment: Grammar = [
+        ("S".to_string(), vec![vec!["L".to_string(), "=".to_string(), "R".to_string()], vec!["R".to_string()]]),
+        ("L".to_string(), vec![vec!["*".to_string(), "R".to_string()], vec!["id".to_string()]]),
+        ("R".to_string(), vec![vec!["L".to_string()]]),
+    ].iter().cloned().collect();
+
+    let augmented = AugmentedGrammar::new(&assignment, "S");
+    let automaton = canonical_collection(&augmented);
+    let follow = compute_follow(&assignment, "S");
+    let slr_table = build_slr_table(&augmented, &automaton, &follow);
+    println!("SLR(1) table:\n{}", render_table(&augmented, &slr_table));
+    print_conflicts(&augmented, &slr_table);
+
+    // The lookaheads of the LR(1) items resolve it, and merging the cores to LALR(1) keeps it resolved
+    let lr1 = canonical_lr1_collection(&augmented);
+    let lalr = merge_cores(&lr1);
+    println!("LALR(1) item sets:\n{}", lr1_automaton_to_string(&augmented, &lalr));
+    let lr1_table = build_lr1_table(&augmented, &lr1);
+    let lalr_table = build_lr1_table(&augmented, &lalr);
+    println!("LALR(1) table:\n{}", render_table(&augmented, &lalr_table));
+    println!(
+        "{}",
+        render_stats(&[
+            ("SLR(1)", table_stats(&slr_table)),
+            ("LALR(1)", table_stats(&lalr_table)),
+            ("LR(1)", table_stats(&lr1_table)),
+        ])
+    );
+
+    let input: Vec<&str> = "* id = id".split_whitespace().collect();
+    let (success, trace) = parse(&augmented, &lalr_table, &input);
+    println!("Parsing trace:\n{}", render_trace(&trace));
+    println!("Parsing result: {}", if success { "Success" } else { "Failure" });
+
+    // LR(1) but not LALR(1): the states reached by `a c` and `b c` share a core but not their lookaheads
+    let merged: Grammar = [
+        ("S".to_string(), vec![
+            vec!["a".to_string(), "A".to_string(), "d".to_string()],
+            vec!["b".to_string(), "B".to_string(), "d".to_string()],
+            vec!["a".to_string(), "B".to_string(), "e".to_string()],
+            vec!["b".to_string(), "A".to_string(), "e".to_string()],
+        ]),
+        ("A".to_string(), vec![vec!["c".to_string()]]),
+        ("B".to_string(), vec![vec!["c".to_string()]]),
+    ].iter().cloned().collect();
+
+    let augmented = AugmentedGrammar::new(&merged, "S");
+    let lr1 = canonical_lr1_collection(&augmented);
+    let lr1_table = build_lr1_table(&augmented, &lr1);
+    let lalr_table = build_lr1_table(&augmented, &merge_cores(&lr1));
+    println!(
+        "{}",
+        render_stats(&[("LALR(1)", table_stats(&lalr_table)), ("LR(1)", table_stats(&lr1_table))])
+    );
+    print_conflicts(&augmented, &lalr_table);
+
+    // An ambiguous expression grammar made deterministic by precedence declarations, as in miniParser's mini.y
+    let spec = parse_spec(
+        "%precedence then
+         %precedence else
+         %left + -
+         %left * /
+         %right ^
+         %right UMINUS
+         S -> if E then S | if E then S else S | id = E
+         E -> E + E | E - E | E * E | E / E | E ^ E | - E %prec UMINUS | ( E ) | id",
+    )
+    .unwrap();
+    let augmented = AugmentedGrammar::new(&spec.grammar, &spec.start);
+    let mut lalr_table = build_lr1_table(&augmented, &merge_cores(&canonical_lr1_collection(&augmented)));
+    println!("Before precedence: {} conflicts", lalr_table.conflicts().len());
+    for resolution in resolve_conflicts(&augmented, &mut lalr_table, &spec.precedence) {
+        println!("{}", resolution_to_string(&augmented, &resolution));
+    }
+    println!("After precedence: {} conflicts\n", lalr_table.conflicts().len());
+
+    let input: Vec<&str> = "id = id - id - id * id ^ id ^ id".split_whitespace().collect();
+    let (success, trace) = parse(&augmented, &lalr_table, &input);
+    println!("Parsing trace:\n{}", render_trace(&trace));
+    println!("Parsing result: {}", if success { "Success" } else { "Failure" });
+}
+
+fn print_conflicts(grammar: &AugmentedGrammar, table: &LrTable) {
+    for conflict in table.conflicts() {
+        println!("{}", explain_conflict(grammar, table, &conflict));
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn grammar(rules: &[(&str, &[&[&str]])]) -> Grammar {
+        rules
+            .iter()
+            .map(|(lhs, alternatives)| {
+                let productions = alternatives
+                    .iter()
+                    .map(|alt| alt.iter().map(|symbol| symbol.to_string()).collect())
+                    .collect();
+                (lhs.to_string(), productions)
+            })
+            .collect()
+    }
+}
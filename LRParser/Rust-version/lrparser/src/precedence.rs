@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::lr0::AugmentedGrammar;
+use crate::table::{LrAction, LrTable};
+
+// Associativity of a precedence level, as declared with %left, %right, %nonassoc or %precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+    Nonassoc,
+    // Only orders the level against the others; a tie stays a conflict
+    Precedence,
+}
+
+impl Assoc {
+    pub fn from_directive(directive: &str) -> Option<Assoc> {
+        match directive {
+            "%left" => Some(Assoc::Left),
+            "%right" => Some(Assoc::Right),
+            "%nonassoc" => Some(Assoc::Nonassoc),
+            "%precedence" => Some(Assoc::Precedence),
+            _ => None,
+        }
+    }
+}
+
+// Precedence levels of the terminals, later declarations binding tighter as in yacc, and the
+// productions whose precedence is given explicitly with %prec
+#[derive(Debug, Clone, Default)]
+pub struct Precedence {
+    pub levels: HashMap<String, (usize, Assoc)>,
+    pub production_prec: HashMap<(String, Vec<String>), String>,
+}
+
+impl Precedence {
+    // Declare a new level above all the previous ones
+    pub fn declare(&mut self, assoc: Assoc, terminals: &[&str]) {
+        let level = self.levels.values().map(|(level, _)| level + 1).max().unwrap_or(1);
+        for terminal in terminals {
+            self.levels.insert(terminal.to_string(), (level, assoc));
+        }
+    }
+
+    // The %prec symbol if there is one, otherwise the last terminal of the right-hand side
+    pub fn production_level(&self, grammar: &AugmentedGrammar, production: usize) -> Option<(usize, Assoc)> {
+        let (lhs, rhs) = &grammar.productions[production];
+        let symbol = match self.production_prec.get(&(lhs.clone(), rhs.clone())) {
+            Some(symbol) => symbol,
+            None => rhs.iter().rev().find(|symbol| !grammar.is_non_terminal(symbol))?,
+        };
+        self.levels.get(symbol).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolved {
+    Shift,
+    Reduce,
+    // %nonassoc: the cell becomes an error entry
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    pub state: usize,
+    pub terminal: String,
+    pub production: usize,
+    pub resolved: Resolved,
+}
+
+// Resolve the shift/reduce conflicts whose production and token both have a precedence, the way
+// yacc does: the higher level wins, and on a tie the associativity decides. Conflicts without
+// precedence information, and reduce/reduce conflicts, are left in the table.
+pub fn resolve_conflicts(grammar: &AugmentedGrammar, table: &mut LrTable, precedence: &Precedence) -> Vec<Resolution> {
+    let mut resolutions = Vec::new();
+
+    for ((state, terminal), actions) in table.action.iter_mut() {
+        let Some(&shift) = actions.iter().find(|action| matches!(action, LrAction::Shift(_))) else {
+            continue;
+        };
+        let Some(&(token_level, token_assoc)) = precedence.levels.get(terminal) else {
+            continue;
+        };
+
+        let mut kept = vec![shift];
+        let mut shift_kept = true;
+        for &action in actions.iter() {
+            let LrAction::Reduce(production) = action else {
+                continue;
+            };
+            let Some((production_level, _)) = precedence.production_level(grammar, production) else {
+                kept.push(action);
+                continue;
+            };
+            let resolved = if production_level > token_level {
+                Resolved::Reduce
+            } else if production_level < token_level {
+                Resolved::Shift
+            } else {
+                match token_assoc {
+                    Assoc::Left => Resolved::Reduce,
+                    Assoc::Right => Resolved::Shift,
+                    Assoc::Nonassoc => Resolved::Error,
+                    Assoc::Precedence => {
+                        kept.push(action);
+                        continue;
+                    }
+                }
+            };
+            match resolved {
+                Resolved::Reduce => {
+                    shift_kept = false;
+                    kept.push(action);
+                }
+                Resolved::Error => shift_kept = false,
+                Resolved::Shift => {}
+            }
+            resolutions.push(Resolution {
+                state: *state,
+                terminal: terminal.clone(),
+                production,
+                resolved,
+            });
+        }
+
+        if !shift_kept {
+            kept.retain(|action| *action != shift);
+        }
+        kept.sort();
+        *actions = kept;
+    }
+
+    table.action.retain(|_, actions| !actions.is_empty());
+    resolutions
+}
+
+// Worded like bison's report: Conflict in state 8 between rule E -> E + E and token + resolved as reduce
+pub fn resolution_to_string(grammar: &AugmentedGrammar, resolution: &Resolution) -> String {
+    let resolved = match resolution.resolved {
+        Resolved::Shift => "shift",
+        Resolved::Reduce => "reduce",
+        Resolved::Error => "an error (%nonassoc)",
+    };
+    format!(
+        "Conflict in state {} between rule {} and token {} resolved as {}",
+        resolution.state,
+        grammar.production_to_string(resolution.production),
+        resolution.terminal,
+        resolved
+    )
+}
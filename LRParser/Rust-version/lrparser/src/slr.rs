@@ -0,0 +1,83 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::lr0::{next_symbol, AugmentedGrammar, Lr0Automaton};
+use crate::table::{LrAction, LrTable};
+
+// SLR(1): shift along the LR(0) transitions, reduce A -> α· on every terminal in FOLLOW(A)
+pub fn build_slr_table(
+    grammar: &AugmentedGrammar,
+    automaton: &Lr0Automaton,
+    follow: &HashMap<String, HashSet<String>>,
+) -> LrTable {
+    let mut table = LrTable {
+        states: automaton.states.len(),
+        ..LrTable::default()
+    };
+
+    for ((state, symbol), &target) in &automaton.transitions {
+        if grammar.is_non_terminal(symbol) {
+            table.goto.insert((*state, symbol.clone()), target);
+        } else {
+            table.add_action(*state, symbol, LrAction::Shift(target));
+        }
+    }
+
+    for (state, items) in automaton.states.iter().enumerate() {
+        for &item in items {
+            if next_symbol(grammar, item).is_some() {
+                continue;
+            }
+            let (production, _) = item;
+            if production == 0 {
+                table.add_action(state, "$", LrAction::Accept);
+                continue;
+            }
+            let lhs = &grammar.productions[production].0;
+            for terminal in follow.get(lhs).into_iter().flatten() {
+                table.add_action(state, terminal, LrAction::Reduce(production));
+            }
+        }
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lr0::canonical_collection;
+    use crate::spec::parse_spec;
+    use crate::table::ConflictKind;
+    use ll1parser::compute_follow;
+
+    fn slr_table(text: &str) -> (AugmentedGrammar, LrTable) {
+        let spec = parse_spec(text).unwrap();
+        let grammar = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let automaton = canonical_collection(&grammar);
+        let table = build_slr_table(&grammar, &automaton, &compute_follow(&spec.grammar, &spec.start));
+        (grammar, table)
+    }
+
+    #[test]
+    fn test_expressions_are_slr() {
+        let (_, table) = slr_table("E -> E + T | T\nT -> T * F | F\nF -> ( E ) | id");
+        assert!(table.conflicts().is_empty());
+        assert_eq!(table.states, 12);
+    }
+
+    #[test]
+    fn test_assignment_shift_reduce_conflict() {
+        // `=` is in FOLLOW(R) through S -> L = R and R -> L, so after L the table both shifts `=` and
+        // reduces R -> L on it
+        let (grammar, table) = slr_table("S -> L = R | R\nL -> * R | id\nR -> L");
+        let conflicts = table.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.terminal, "=");
+        assert_eq!(conflict.kind, ConflictKind::ShiftReduce);
+        let [LrAction::Shift(_), LrAction::Reduce(production)] = conflict.actions[..] else {
+            panic!("expected a shift and a reduce, found {:?}", conflict.actions);
+        };
+        assert_eq!(grammar.production_to_string(production), "R -> L");
+    }
+}
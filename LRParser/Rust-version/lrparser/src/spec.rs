@@ -0,0 +1,142 @@
+use crate::precedence::{Assoc, Precedence};
+use crate::Grammar;
+
+// A grammar written in a small yacc-like format:
+//
+//   %left + -
+//   %left * /
+//   E -> E + E | E * E | - E %prec UMINUS | ( E ) | id
+//
+// Declarations come in order of increasing precedence. Symbols are separated by spaces, the
+// left-hand side of the first rule is the start symbol and ε (or an empty alternative) is the
+// empty string. Lines starting with `//` are comments.
+pub struct GrammarSpec {
+    pub grammar: Grammar,
+    pub start: String,
+    pub precedence: Precedence,
+}
+
+pub fn parse_spec(text: &str) -> Result<GrammarSpec, String> {
+    let mut grammar = Grammar::new();
+    let mut start = None;
+    let mut precedence = Precedence::default();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        if line.starts_with('%') {
+            let mut words = line.split_whitespace();
+            let directive = words.next().unwrap();
+            let assoc = Assoc::from_directive(directive)
+                .ok_or_else(|| format!("line {}: unknown directive {}", number + 1, directive))?;
+            let terminals: Vec<&str> = words.collect();
+            if terminals.is_empty() {
+                return Err(format!("line {}: {} without symbols", number + 1, directive));
+            }
+            precedence.declare(assoc, &terminals);
+            continue;
+        }
+
+        let Some((lhs, rhs)) = line.split_once("->") else {
+            return Err(format!("line {}: expected LHS -> RHS", number + 1));
+        };
+        let lhs = lhs.trim();
+        if lhs.is_empty() || lhs.contains(char::is_whitespace) {
+            return Err(format!("line {}: the left-hand side must be a single non-terminal", number + 1));
+        }
+        start.get_or_insert_with(|| lhs.to_string());
+
+        for alternative in rhs.split('|') {
+            let mut symbols: Vec<String> = Vec::new();
+            let mut words = alternative.split_whitespace();
+            while let Some(word) = words.next() {
+                if word == "%prec" {
+                    let symbol = words
+                        .next()
+                        .ok_or_else(|| format!("line {}: %prec without a symbol", number + 1))?;
+                    precedence
+                        .production_prec
+                        .insert((lhs.to_string(), symbols.clone()), symbol.to_string());
+                } else if word != "ε" {
+                    symbols.push(word.to_string());
+                }
+            }
+            if symbols.is_empty() {
+                symbols.push("".to_string());
+            }
+            grammar.entry(lhs.to_string()).or_default().push(symbols);
+        }
+    }
+
+    let start = start.ok_or_else(|| "the grammar has no rules".to_string())?;
+    Ok(GrammarSpec { grammar, start, precedence })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lr0::AugmentedGrammar;
+    use crate::lr1::{build_lr1_table, canonical_lr1_collection, merge_cores};
+    use crate::precedence::{resolve_conflicts, Resolved};
+    use crate::table::parse;
+
+    #[test]
+    fn test_dangling_else_shifts() {
+        let spec = parse_spec(
+            "%precedence then
+             %precedence else
+             S -> if E then S | if E then S else S | a
+             E -> b",
+        )
+        .unwrap();
+        let augmented = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let mut table = build_lr1_table(&augmented, &merge_cores(&canonical_lr1_collection(&augmented)));
+        assert_eq!(table.conflicts().len(), 1);
+
+        let resolutions = resolve_conflicts(&augmented, &mut table, &spec.precedence);
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[0].terminal, "else");
+        assert_eq!(resolutions[0].resolved, Resolved::Shift);
+        assert!(table.conflicts().is_empty());
+
+        // The else is shifted while the inner if is still on the stack, so it belongs to the inner if
+        let input: Vec<&str> = "if b then if b then a else a".split_whitespace().collect();
+        let (success, trace) = parse(&augmented, &table, &input);
+        assert!(success);
+        let at_else = trace.iter().rfind(|step| step.input[0] == "else").unwrap();
+        assert_eq!(at_else.symbols.iter().filter(|symbol| *symbol == "if").count(), 2);
+        assert!(at_else.action.starts_with("shift"));
+    }
+
+    #[test]
+    fn test_left_associative_minus() {
+        let spec = parse_spec(
+            "%left -
+             E -> E - E | id",
+        )
+        .unwrap();
+        let augmented = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let mut table = build_lr1_table(&augmented, &merge_cores(&canonical_lr1_collection(&augmented)));
+        let resolutions = resolve_conflicts(&augmented, &mut table, &spec.precedence);
+        assert_eq!(resolutions[0].resolved, Resolved::Reduce);
+        assert!(table.conflicts().is_empty());
+
+        // a - b is reduced before the second - is shifted: (a - b) - c
+        let (success, trace) = parse(&augmented, &table, &["id", "-", "id", "-", "id"]);
+        assert!(success);
+        let second_minus = trace.iter().filter(|step| step.input.len() == 3).nth(1).unwrap();
+        assert_eq!(second_minus.symbols, vec!["E", "-", "E"]);
+        assert_eq!(second_minus.action, "reduce E -> E - E");
+
+        // With %nonassoc, a - b - c is a syntax error
+        let spec = parse_spec("%nonassoc -\nE -> E - E | id").unwrap();
+        let augmented = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let mut table = build_lr1_table(&augmented, &merge_cores(&canonical_lr1_collection(&augmented)));
+        resolve_conflicts(&augmented, &mut table, &spec.precedence);
+        assert!(parse(&augmented, &table, &["id", "-", "id"]).0);
+        assert!(!parse(&augmented, &table, &["id", "-", "id", "-", "id"]).0);
+    }
+}
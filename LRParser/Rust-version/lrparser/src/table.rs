@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+
+use crate::lr0::AugmentedGrammar;
+
+// Ordered so that, in a conflicting cell, shifts come before reduces and reduces by earlier productions
+// come first, which is how yacc resolves conflicts by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LrAction {
+    Shift(usize),
+    Reduce(usize),
+    Accept,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    ShiftReduce,
+    ReduceReduce,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub state: usize,
+    pub terminal: String,
+    pub kind: ConflictKind,
+    pub actions: Vec<LrAction>,
+}
+
+// ACTION and GOTO tables shared by the SLR(1), LR(1) and LALR(1) constructions; an ACTION cell
+// with more than one entry is a conflict
+#[derive(Debug, Clone, Default)]
+pub struct LrTable {
+    pub states: usize,
+    pub action: BTreeMap<(usize, String), Vec<LrAction>>,
+    pub goto: BTreeMap<(usize, String), usize>,
+}
+
+impl LrTable {
+    pub fn add_action(&mut self, state: usize, terminal: &str, action: LrAction) {
+        let cell = self.action.entry((state, terminal.to_string())).or_default();
+        if !cell.contains(&action) {
+            cell.push(action);
+            cell.sort();
+        }
+    }
+
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        self.action
+            .iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .map(|((state, terminal), actions)| {
+                let kind = if actions.iter().any(|action| matches!(action, LrAction::Shift(_))) {
+                    ConflictKind::ShiftReduce
+                } else {
+                    ConflictKind::ReduceReduce
+                };
+                Conflict {
+                    state: *state,
+                    terminal: terminal.clone(),
+                    kind,
+                    actions: actions.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+// Size of a table: filled ACTION and GOTO cells and the cells that hold a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableStats {
+    pub states: usize,
+    pub action_entries: usize,
+    pub goto_entries: usize,
+    pub shift_reduce: usize,
+    pub reduce_reduce: usize,
+}
+
+pub fn table_stats(table: &LrTable) -> TableStats {
+    let conflicts = table.conflicts();
+    let count = |kind| conflicts.iter().filter(|conflict| conflict.kind == kind).count();
+    TableStats {
+        states: table.states,
+        action_entries: table.action.values().map(Vec::len).sum(),
+        goto_entries: table.goto.len(),
+        shift_reduce: count(ConflictKind::ShiftReduce),
+        reduce_reduce: count(ConflictKind::ReduceReduce),
+    }
+}
+
+// One row per table, e.g. to compare the SLR(1), LALR(1) and LR(1) tables of the same grammar
+pub fn render_stats(stats: &[(&str, TableStats)]) -> String {
+    let mut rows = vec![vec![
+        "Table".to_string(),
+        "States".to_string(),
+        "ACTION".to_string(),
+        "GOTO".to_string(),
+        "S/R".to_string(),
+        "R/R".to_string(),
+    ]];
+    for (name, stats) in stats {
+        rows.push(vec![
+            name.to_string(),
+            stats.states.to_string(),
+            stats.action_entries.to_string(),
+            stats.goto_entries.to_string(),
+            stats.shift_reduce.to_string(),
+            stats.reduce_reduce.to_string(),
+        ]);
+    }
+    render_rows(&rows)
+}
+
+fn action_to_string(action: &LrAction) -> String {
+    match action {
+        LrAction::Shift(state) => format!("s{}", state),
+        LrAction::Reduce(production) => format!("r{}", production),
+        LrAction::Accept => "acc".to_string(),
+    }
+}
+
+pub fn conflict_to_string(grammar: &AugmentedGrammar, conflict: &Conflict) -> String {
+    let kind = match conflict.kind {
+        ConflictKind::ShiftReduce => "shift/reduce",
+        ConflictKind::ReduceReduce => "reduce/reduce",
+    };
+    let actions: Vec<String> = conflict
+        .actions
+        .iter()
+        .map(|action| match action {
+            LrAction::Shift(state) => format!("shift {}", state),
+            LrAction::Reduce(production) => format!("reduce {}", grammar.production_to_string(*production)),
+            LrAction::Accept => "accept".to_string(),
+        })
+        .collect();
+    format!(
+        "{} conflict in state {} on {}: {}",
+        kind,
+        conflict.state,
+        conflict.terminal,
+        actions.join(" vs ")
+    )
+}
+
+// Numbered productions, to read the r<n> entries of the table
+pub fn productions_to_string(grammar: &AugmentedGrammar) -> String {
+    (0..grammar.productions.len())
+        .map(|index| format!("({}) {}\n", index, grammar.production_to_string(index)))
+        .collect()
+}
+
+fn render_rows(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| rows.iter().filter_map(|row| row.get(col)).map(|cell| cell.chars().count()).max().unwrap_or(0))
+        .collect();
+    let mut out = String::new();
+    for (idx, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        out.push_str(cells.join(" | ").trim_end());
+        out.push('\n');
+        if idx == 0 {
+            let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+            out.push_str(&rule.join("-+-"));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+// ACTION columns (terminals) followed by GOTO columns (non-terminals), one row per state
+pub fn render_table(grammar: &AugmentedGrammar, table: &LrTable) -> String {
+    let terminals = grammar.terminals();
+    let non_terminals = grammar.non_terminals();
+
+    let mut header = vec!["State".to_string()];
+    header.extend(terminals.iter().cloned());
+    header.extend(non_terminals.iter().cloned());
+    let mut rows = vec![header];
+
+    for state in 0..table.states {
+        let mut row = vec![state.to_string()];
+        for terminal in &terminals {
+            let cell = table
+                .action
+                .get(&(state, terminal.clone()))
+                .map(|actions| actions.iter().map(action_to_string).collect::<Vec<String>>().join("/"))
+                .unwrap_or_default();
+            row.push(cell);
+        }
+        for non_terminal in &non_terminals {
+            let cell = table
+                .goto
+                .get(&(state, non_terminal.clone()))
+                .map(|target| target.to_string())
+                .unwrap_or_default();
+            row.push(cell);
+        }
+        rows.push(row);
+    }
+
+    render_rows(&rows)
+}
+
+// One row of the shift-reduce trace, recorded before the action is taken
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    pub step: usize,
+    pub states: Vec<usize>,
+    pub symbols: Vec<String>,
+    pub input: Vec<String>,
+    pub action: String,
+}
+
+// Shift-reduce driver; a conflicting cell is resolved by taking its first action
+pub fn parse(grammar: &AugmentedGrammar, table: &LrTable, input: &[&str]) -> (bool, Vec<TraceStep>) {
+    let mut states = vec![0];
+    let mut symbols: Vec<String> = Vec::new();
+    let mut tokens: Vec<String> = input.iter().map(|token| token.to_string()).collect();
+    tokens.push("$".to_string());
+    let mut position = 0;
+    let mut trace = Vec::new();
+
+    loop {
+        let state = *states.last().unwrap();
+        let lookahead = &tokens[position];
+        let action = table.action.get(&(state, lookahead.clone())).and_then(|actions| actions.first());
+        let description = match action {
+            Some(LrAction::Shift(target)) => format!("shift {}", target),
+            Some(LrAction::Reduce(production)) => format!("reduce {}", grammar.production_to_string(*production)),
+            Some(LrAction::Accept) => "accept".to_string(),
+            None => format!("error: unexpected {}", lookahead),
+        };
+        trace.push(TraceStep {
+            step: trace.len() + 1,
+            states: states.clone(),
+            symbols: symbols.clone(),
+            input: tokens[position..].to_vec(),
+            action: description,
+        });
+
+        match action {
+            Some(LrAction::Shift(target)) => {
+                states.push(*target);
+                symbols.push(lookahead.clone());
+                position += 1;
+            }
+            Some(LrAction::Reduce(production)) => {
+                let (lhs, rhs) = &grammar.productions[*production];
+                states.truncate(states.len() - rhs.len());
+                symbols.truncate(symbols.len() - rhs.len());
+                let top = *states.last().unwrap();
+                match table.goto.get(&(top, lhs.clone())) {
+                    Some(&target) => {
+                        states.push(target);
+                        symbols.push(lhs.clone());
+                    }
+                    None => return (false, trace),
+                }
+            }
+            Some(LrAction::Accept) => return (true, trace),
+            None => return (false, trace),
+        }
+    }
+}
+
+// The stack interleaves states and grammar symbols: 0 E 1 + 6
+pub fn render_trace(trace: &[TraceStep]) -> String {
+    let mut rows = vec![vec![
+        "Step".to_string(),
+        "Stack".to_string(),
+        "Input".to_string(),
+        "Action".to_string(),
+    ]];
+    for step in trace {
+        let mut stack = vec![step.states[0].to_string()];
+        for (symbol, state) in step.symbols.iter().zip(step.states.iter().skip(1)) {
+            stack.push(symbol.clone());
+            stack.push(state.to_string());
+        }
+        rows.push(vec![
+            step.step.to_string(),
+            stack.join(" "),
+            step.input.join(" "),
+            step.action.clone(),
+        ]);
+    }
+    render_rows(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lr0::canonical_collection;
+    use crate::slr::build_slr_table;
+    use crate::spec::parse_spec;
+    use ll1parser::compute_follow;
+
+    fn run(input: &str) -> (bool, Vec<String>) {
+        let spec = parse_spec("E -> E + T | T\nT -> T * F | F\nF -> ( E ) | id").unwrap();
+        let grammar = AugmentedGrammar::new(&spec.grammar, &spec.start);
+        let table = build_slr_table(&grammar, &canonical_collection(&grammar), &compute_follow(&spec.grammar, "E"));
+        let input: Vec<&str> = input.split_whitespace().collect();
+        let (success, trace) = parse(&grammar, &table, &input);
+        (success, trace.into_iter().map(|step| step.action).collect())
+    }
+
+    #[test]
+    fn test_driver_accepts() {
+        let (success, actions) = run("id * id + id");
+        assert!(success);
+        assert_eq!(
+            actions,
+            vec![
+                "shift 5", "reduce F -> id", "reduce T -> F", "shift 8", "shift 5", "reduce F -> id",
+                "reduce T -> T * F", "reduce E -> T", "shift 7", "shift 5", "reduce F -> id", "reduce T -> F",
+                "reduce E -> E + T", "accept",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_driver_rejects() {
+        // The error shows up after the `+` has been shifted, with E + on the stack
+        let (success, actions) = run("id + * id");
+        assert!(!success);
+        assert_eq!(
+            actions,
+            vec!["shift 5", "reduce F -> id", "reduce T -> F", "reduce E -> T", "shift 7", "error: unexpected *"]
+        );
+        let (success, actions) = run("id +");
+        assert!(!success);
+        assert_eq!(actions.last().map(String::as_str), Some("error: unexpected $"));
+    }
+}
@@ -1,6 +1,9 @@
+// 保留原有的写法，不按 clippy 的风格建议改写
+#![allow(clippy::char_lit_as_u8, clippy::is_digit_ascii_radix, clippy::let_and_return)]
+
 use std::io::Read;
 
-use crate::tokens::Token;
+use crate::tokens::{Span, Token};
 
 pub struct Lexer<R: Read> {
     reader: R,             // 文件流，减少系统调用次数
@@ -31,6 +34,14 @@ impl<R: Read> Lexer<R> {
         lexer
     }
 
+    // 与 next_token 相同，同时返回 Token 在源文件中的位置
+    pub fn next_token_with_span(&mut self) -> Option<(Token, Span)> {
+        self.skip_whitespace();
+        let start = (self.row, self.col);
+        let token = self.next_token()?;
+        Some((token, Span { start, end: (self.row, self.col) }))
+    }
+
     pub fn next_token(&mut self) -> Option<Token> {
         // 跳过空白符
         self.skip_whitespace();
@@ -20,8 +20,6 @@
 //     println!("End of file");
 // }
 
-mod lexer;
-mod tokens;
 mod utils;
 
 use utils::run_lexer_pipeline;
@@ -33,8 +31,8 @@ fn main() -> std::io::Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use crate::lexer::Lexer;
-    use crate::tokens::Token;
+    use newlexer::lexer::Lexer;
+    use newlexer::tokens::Token;
 
     #[test]
     fn test_lexer_simple_input() {
@@ -182,4 +180,11 @@ mod tests {
         );
         assert_eq!(lexer.next_token(), None);
     }
+
+    #[test]
+    fn test_token_kind() {
+        assert_eq!(Token::SELFVALUE.kind(), "SELFVALUE");
+        assert_eq!(Token::IntegerLiteral("42".to_string()).kind(), "IntegerLiteral");
+        assert_eq!(Token::Unknown('`').kind(), "Unknown");
+    }
 }
@@ -45,3 +45,153 @@ pub enum Token {
     // Unknown (fallback case)
     Unknown(char),
 }
+
+impl Token {
+    // Token 的种类名，即不带内容的变体名，例如 IntegerLiteral("12") 的种类是 "IntegerLiteral"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            // Keywords
+            Token::As => "As",
+            Token::Break => "Break",
+            Token::Const => "Const",
+            Token::Continue => "Continue",
+            Token::Crate => "Crate",
+            Token::Else => "Else",
+            Token::Enum => "Enum",
+            Token::Extern => "Extern",
+            Token::False => "False",
+            Token::Fn => "Fn",
+            Token::For => "For",
+            Token::If => "If",
+            Token::Impl => "Impl",
+            Token::In => "In",
+            Token::Let => "Let",
+            Token::Loop => "Loop",
+            Token::Match => "Match",
+            Token::Mod => "Mod",
+            Token::Move => "Move",
+            Token::Mut => "Mut",
+            Token::Pub => "Pub",
+            Token::Ref => "Ref",
+            Token::Return => "Return",
+            Token::SELFVALUE => "SELFVALUE",
+            Token::SELFTYPE => "SELFTYPE",
+            Token::Static => "Static",
+            Token::Struct => "Struct",
+            Token::Super => "Super",
+            Token::Trait => "Trait",
+            Token::True => "True",
+            Token::Type => "Type",
+            Token::Unsafe => "Unsafe",
+            Token::Use => "Use",
+            Token::Where => "Where",
+            Token::While => "While",
+            Token::Async => "Async",
+            Token::Await => "Await",
+            Token::Dyn => "Dyn",
+            // Reserved keywords
+            Token::Abstract => "Abstract",
+            Token::Become => "Become",
+            Token::Box => "Box",
+            Token::Do => "Do",
+            Token::Final => "Final",
+            Token::Macro => "Macro",
+            Token::Override => "Override",
+            Token::Priv => "Priv",
+            Token::Typeof => "Typeof",
+            Token::Unsized => "Unsized",
+            Token::Virtual => "Virtual",
+            Token::Yield => "Yield",
+            Token::Try => "Try",
+            // Weak keywords
+            Token::MacroRules => "MacroRules",
+            Token::Union => "Union",
+            Token::StaticLifetime => "StaticLifetime",
+
+            // Identifiers
+            Token::Identifier(_) => "Identifier",
+
+            // Literals
+            Token::CharLiteral(_) => "CharLiteral",
+            Token::StringLiteral(_) => "StringLiteral",
+            Token::IntegerLiteral(_) => "IntegerLiteral",
+            Token::FloatLiteral(_) => "FloatLiteral",
+
+            // Lifetimes and Labels
+            Token::LifetimeOrLabel(_) => "LifetimeOrLabel",
+
+            // Comments
+            Token::Comment(_) => "Comment",
+
+            // Punctuation
+            Token::Plus => "Plus",
+            Token::Minus => "Minus",
+            Token::Star => "Star",
+            Token::Slash => "Slash",
+            Token::Percent => "Percent",
+            Token::Caret => "Caret",
+            Token::Not => "Not",
+            Token::And => "And",
+            Token::Or => "Or",
+            Token::AndAnd => "AndAnd",
+            Token::OrOr => "OrOr",
+            Token::Shl => "Shl",
+            Token::Shr => "Shr",
+            Token::PlusEq => "PlusEq",
+            Token::MinusEq => "MinusEq",
+            Token::StarEq => "StarEq",
+            Token::SlashEq => "SlashEq",
+            Token::PercentEq => "PercentEq",
+            Token::CaretEq => "CaretEq",
+            Token::AndEq => "AndEq",
+            Token::OrEq => "OrEq",
+            Token::ShlEq => "ShlEq",
+            Token::ShrEq => "ShrEq",
+            Token::Eq => "Eq",
+            Token::EqEq => "EqEq",
+            Token::Ne => "Ne",
+            Token::Gt => "Gt",
+            Token::Lt => "Lt",
+            Token::Ge => "Ge",
+            Token::Le => "Le",
+            Token::At => "At",
+            Token::Underscore => "Underscore",
+            Token::Dot => "Dot",
+            Token::DotDot => "DotDot",
+            Token::DotDotDot => "DotDotDot",
+            Token::DotDotEq => "DotDotEq",
+            Token::Comma => "Comma",
+            Token::Semi => "Semi",
+            Token::Colon => "Colon",
+            Token::PathSep => "PathSep",
+            Token::RArrow => "RArrow",
+            Token::FatArrow => "FatArrow",
+            Token::LArrow => "LArrow",
+            Token::Pound => "Pound",
+            Token::Dollar => "Dollar",
+            Token::Question => "Question",
+            Token::Tilde => "Tilde",
+
+            // Delimiters
+            Token::OpenBrace => "OpenBrace",
+            Token::CloseBrace => "CloseBrace",
+            Token::OpenBracket => "OpenBracket",
+            Token::CloseBracket => "CloseBracket",
+            Token::OpenParen => "OpenParen",
+            Token::CloseParen => "CloseParen",
+
+            // Error
+            Token::Error(_) => "Error",
+
+            // Unknown (fallback case)
+            Token::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+// Token 在源文件中的位置：起止的 (行, 列)，从 1 开始计数，终点不包含在内
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
@@ -1,5 +1,5 @@
-use crate::lexer::Lexer;
-use crate::tokens::Token;
+use newlexer::lexer::Lexer;
+use newlexer::tokens::Token;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
@@ -1,3 +1,7 @@
+mod pratt;
+
+use pratt::{OperatorTable, PrattParser};
+
 #[derive(Debug, Clone, PartialEq)]
 enum Token {
     Number(i32),
@@ -19,6 +23,7 @@ impl Lexer {
         }
     }
 
+    #[allow(clippy::is_digit_ascii_radix)]
     fn next_token(&mut self) -> Option<Token> {
         if self.position >= self.input_string.len() {
             return None;
@@ -44,7 +49,7 @@ impl Lexer {
         }
 
         // 处理操作符和括号
-        if "+-*/()".contains(current_char) {
+        if "+-*/^()".contains(current_char) {
             self.position += 1;
             return match current_char {
                 '(' => Some(Token::LParen),
@@ -116,11 +121,11 @@ impl Parser {
     }
 
     fn term(&mut self) -> Box<Expr> {
-        let mut node = self.factor();
+        let mut node = self.power();
         while let Some(Token::Operator(op)) = self.current_token() {
             if *op == '*' || *op == '/' {
                 let op = self.consume(&Token::Operator(*op));
-                let right_node = self.factor();
+                let right_node = self.power();
                 node = Box::new(Expr::Binary(op, node, right_node));
             } else {
                 break;
@@ -129,6 +134,17 @@ impl Parser {
         node
     }
 
+    // 乘方是右结合的，并且比乘除优先级高
+    fn power(&mut self) -> Box<Expr> {
+        let node = self.factor();
+        if let Some(Token::Operator('^')) = self.current_token() {
+            let op = self.consume(&Token::Operator('^'));
+            let right_node = self.power();
+            return Box::new(Expr::Binary(op, node, right_node));
+        }
+        node
+    }
+
     fn factor(&mut self) -> Box<Expr> {
         let token = self.current_token().cloned();
         assert!(token.is_some(), "Expected a token, but got None");
@@ -149,25 +165,31 @@ impl Parser {
     }
 }
 
-fn evaluate(node: &Expr) -> i32 {
+fn evaluate(node: &Expr) -> Result<i32, String> {
     match node {
-        Expr::Number(value) => *value,
+        Expr::Number(value) => Ok(*value),
         Expr::Binary(Token::Operator(op), left, right) => {
-            let left_val = evaluate(left);
-            let right_val = evaluate(right);
-            match op {
-                '+' => left_val + right_val,
-                '-' => left_val - right_val,
-                '*' => left_val * right_val,
-                '/' => left_val / right_val,
-                _ => panic!("Unexpected operator"),
-            }
+            let left_val = evaluate(left)?;
+            let right_val = evaluate(right)?;
+            // 每个运算都用 checked 版本，溢出和除零都作为错误返回而不是 panic
+            let result = match op {
+                '+' => left_val.checked_add(right_val),
+                '-' => left_val.checked_sub(right_val),
+                '*' => left_val.checked_mul(right_val),
+                '/' if right_val == 0 => return Err("division by zero".to_string()),
+                '/' => left_val.checked_div(right_val),
+                // 整数运算不支持负指数
+                '^' if right_val < 0 => return Err(format!("negative exponent {}", right_val)),
+                '^' => left_val.checked_pow(right_val as u32),
+                _ => return Err(format!("unknown operator {}", op)),
+            };
+            result.ok_or_else(|| format!("{} {} {} overflows", left_val, op, right_val))
         }
-        _ => panic!("Unexpected expression"),
+        Expr::Binary(token, _, _) => Err(format!("unexpected operator token {:?}", token)),
     }
 }
 
-fn calculate(input_string: String) -> i32 {
+fn calculate(input_string: String) -> Result<i32, String> {
     let mut lexer = Lexer::new(input_string);
     let tokens = lexer.tokenize();
     let mut parser = Parser::new(tokens);
@@ -175,7 +197,54 @@ fn calculate(input_string: String) -> i32 {
     evaluate(&ast)
 }
 
+// 使用运算符优先级表驱动的 Pratt 解析器计算
+fn calculate_with(table: &OperatorTable, input_string: String) -> Result<i32, String> {
+    let mut lexer = Lexer::new(input_string);
+    let tokens = lexer.tokenize();
+    let ast = PrattParser::new(table, tokens).parse()?;
+    evaluate(&ast)
+}
+
 fn main() {
-    let result = calculate("3 + 5 * (2 - 8)".to_string());
-    println!("{}", result); // 输出计算结果
+    match calculate("3 + 5 * (2 - 8)".to_string()) {
+        Ok(result) => println!("{}", result), // 输出计算结果
+        Err(message) => println!("{}", message),
+    }
+
+    let table = OperatorTable::arithmetic();
+    for input in ["3 + 5 * (2 - 8)", "10 - 4 - 3", "2 ^ 3 ^ 2"] {
+        match calculate_with(&table, input.to_string()) {
+            Ok(result) => println!("{} = {}", input, result),
+            Err(message) => println!("{}: {}", input, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power() {
+        assert_eq!(calculate("2^3".to_string()), Ok(8));
+        assert_eq!(calculate("2 ^ 3 ^ 2".to_string()), Ok(512));
+        assert_eq!(calculate("2 * 3 ^ 2".to_string()), Ok(18));
+        assert_eq!(calculate("2 ^ (0 - 1)".to_string()), Err("negative exponent -1".to_string()));
+        assert_eq!(calculate("2 ^ 31".to_string()), Err("2 ^ 31 overflows".to_string()));
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let table = OperatorTable::arithmetic();
+        for (input, expected) in [
+            ("1 / 0", "division by zero"),
+            ("2147483647 + 1", "2147483647 + 1 overflows"),
+            ("0 - 2147483647 - 2", "-2147483647 - 2 overflows"),
+            ("65536 * 65536", "65536 * 65536 overflows"),
+        ] {
+            assert_eq!(calculate(input.to_string()), Err(expected.to_string()));
+            assert_eq!(calculate_with(&table, input.to_string()), Err(expected.to_string()));
+        }
+        assert_eq!(calculate("7 / 2 - 3".to_string()), Ok(0));
+    }
 }
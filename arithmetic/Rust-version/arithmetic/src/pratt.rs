@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use crate::{Expr, Token};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+    // a < b < c is an error
+    Nonassoc,
+}
+
+// 运算符的优先级与结合性，写法与 yacc 相同，后声明的优先级更高：
+//
+//   %left + -
+//   %left * /
+//   %right ^
+pub struct OperatorTable {
+    levels: HashMap<char, (usize, Assoc)>,
+}
+
+impl OperatorTable {
+    pub fn from_declarations(text: &str) -> Result<Self, String> {
+        let mut levels = HashMap::new();
+        for (index, line) in text.lines().map(str::trim).filter(|line| !line.is_empty()).enumerate() {
+            let mut words = line.split_whitespace();
+            let assoc = match words.next() {
+                Some("%left") => Assoc::Left,
+                Some("%right") => Assoc::Right,
+                // %precedence has no associativity, so chaining operators of one level is an error as well
+                Some("%nonassoc") | Some("%precedence") => Assoc::Nonassoc,
+                Some(directive) => return Err(format!("unknown directive {}", directive)),
+                None => continue,
+            };
+            for word in words {
+                let mut chars = word.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(op), None) => {
+                        levels.insert(op, (index + 1, assoc));
+                    }
+                    _ => return Err(format!("operators are single characters, found {}", word)),
+                }
+            }
+        }
+        Ok(Self { levels })
+    }
+
+    // + - below * /, and ^ right-associative above both
+    pub fn arithmetic() -> Self {
+        Self::from_declarations("%left + -\n%left * /\n%right ^").unwrap()
+    }
+}
+
+// Precedence climbing: each loop iteration takes an operator binding at least as tightly as
+// `min_level` and parses its right operand with the level the associativity asks for
+pub struct PrattParser<'a> {
+    table: &'a OperatorTable,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl<'a> PrattParser<'a> {
+    pub fn new(table: &'a OperatorTable, tokens: Vec<Token>) -> Self {
+        Self {
+            table,
+            tokens,
+            position: 0,
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Box<Expr>, String> {
+        let expr = self.expression(1)?;
+        match self.tokens.get(self.position) {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?} after the expression", token)),
+        }
+    }
+
+    fn operator(&self) -> Option<(char, usize, Assoc)> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(op)) => self.table.levels.get(op).map(|&(level, assoc)| (*op, level, assoc)),
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self, min_level: usize) -> Result<Box<Expr>, String> {
+        let mut node = self.primary()?;
+        let mut nonassoc_level = None;
+        while let Some((op, level, assoc)) = self.operator() {
+            if level < min_level {
+                break;
+            }
+            if nonassoc_level == Some(level) {
+                return Err(format!("operator {} is non-associative", op));
+            }
+            self.position += 1;
+            let next_level = match assoc {
+                Assoc::Right => level,
+                Assoc::Left | Assoc::Nonassoc => level + 1,
+            };
+            let right = self.expression(next_level)?;
+            node = Box::new(Expr::Binary(Token::Operator(op), node, right));
+            if assoc == Assoc::Nonassoc {
+                nonassoc_level = Some(level);
+            }
+        }
+        Ok(node)
+    }
+
+    fn primary(&mut self) -> Result<Box<Expr>, String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(Box::new(Expr::Number(value))),
+            Some(Token::LParen) => {
+                let node = self.expression(1)?;
+                match self.tokens.get(self.position) {
+                    Some(Token::RParen) => {
+                        self.position += 1;
+                        Ok(node)
+                    }
+                    other => Err(format!("expected ), found {:?}", other)),
+                }
+            }
+            Some(token) => Err(format!("expected a number or (, found {:?}", token)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{evaluate, Lexer};
+
+    fn parse(table: &OperatorTable, input: &str) -> Result<Box<Expr>, String> {
+        PrattParser::new(table, Lexer::new(input.to_string()).tokenize()).parse()
+    }
+
+    #[test]
+    fn test_associativity() {
+        let table = OperatorTable::arithmetic();
+        // (10 - 4) - 3, not 10 - (4 - 3)
+        assert_eq!(evaluate(&parse(&table, "10 - 4 - 3").unwrap()), Ok(3));
+        // 2 ^ (3 ^ 2)
+        assert_eq!(evaluate(&parse(&table, "2 ^ 3 ^ 2").unwrap()), Ok(512));
+        assert_eq!(evaluate(&parse(&table, "3 + 5 * (2 - 8)").unwrap()), Ok(-27));
+
+        let table = OperatorTable::from_declarations("%right -").unwrap();
+        assert_eq!(evaluate(&parse(&table, "10 - 4 - 3").unwrap()), Ok(9));
+
+        let table = OperatorTable::from_declarations("%nonassoc -").unwrap();
+        assert!(parse(&table, "10 - 4").is_ok());
+        assert!(parse(&table, "10 - 4 - 3").is_err());
+    }
+}
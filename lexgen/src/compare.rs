@@ -0,0 +1,138 @@
+use newlexer::lexer::Lexer;
+
+use crate::scanner::Scanner;
+
+// 一个记号的种类与起止位置，两个词法分析器的结果都化成这个样子来比较
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Located {
+    pub kind: String,
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+// 两边不一致的一段：从同一位置开始、在同一位置结束的最短的几个记号
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    pub generated: Vec<Located>,
+    pub hand_written: Vec<Located>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub agreed: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+pub fn hand_written(input: &str) -> Vec<Located> {
+    let mut lexer = Lexer::new(input.as_bytes());
+    let mut tokens = Vec::new();
+    while let Some((token, span)) = lexer.next_token_with_span() {
+        tokens.push(Located {
+            // Token 的种类名就是规则里的种类名
+            kind: token.kind().to_string(),
+            start: span.start,
+            end: span.end,
+        });
+    }
+    tokens
+}
+
+pub fn generated(scanner: &Scanner, input: &str) -> Vec<Located> {
+    scanner
+        .scan(input)
+        .into_iter()
+        .map(|lexeme| Located {
+            kind: lexeme.kind,
+            start: lexeme.start,
+            end: lexeme.end,
+        })
+        .collect()
+}
+
+// 逐个比较两边的记号。不一致时两边各自向前走，直到某个记号在同一位置结束，再从那里接着比较
+pub fn compare(scanner: &Scanner, input: &str) -> Comparison {
+    let left = generated(scanner, input);
+    let right = hand_written(input);
+    let mut comparison = Comparison {
+        agreed: 0,
+        mismatches: Vec::new(),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() || j < right.len() {
+        if i < left.len() && j < right.len() && left[i] == right[j] {
+            comparison.agreed += 1;
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let (first_left, first_right) = (i, j);
+        loop {
+            match (left.get(i), right.get(j)) {
+                (Some(a), Some(b)) if a.end == b.end => {
+                    i += 1;
+                    j += 1;
+                    break;
+                }
+                (Some(a), Some(b)) if a.end < b.end => i += 1,
+                (Some(_), Some(_)) => j += 1,
+                (Some(_), None) => i += 1,
+                (None, Some(_)) => j += 1,
+                (None, None) => break,
+            }
+        }
+        comparison.mismatches.push(Mismatch {
+            generated: left[first_left..i].to_vec(),
+            hand_written: right[first_right..j].to_vec(),
+        });
+    }
+    comparison
+}
+
+fn located_to_string(tokens: &[Located]) -> String {
+    if tokens.is_empty() {
+        return "(nothing)".to_string();
+    }
+    let tokens: Vec<String> = tokens
+        .iter()
+        .map(|token| format!("{} {}:{}-{}:{}", token.kind, token.start.0, token.start.1, token.end.0, token.end.1))
+        .collect();
+    tokens.join(", ")
+}
+
+pub fn mismatch_to_string(mismatch: &Mismatch) -> String {
+    format!(
+        "  generated:    {}\n  hand-written: {}\n",
+        located_to_string(&mismatch.generated),
+        located_to_string(&mismatch.hand_written)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{build_scanner, read_spec};
+
+    #[test]
+    fn test_agrees_with_newlexer() {
+        let scanner = build_scanner(read_spec(crate::RUST_SPEC).unwrap()).unwrap();
+        let input = "fn main(&self) -> Self {\n    let mut x: u8 = 42_0; // count\n    x <<= 1; /* shift */ loop { break; }\n    \
+                     let s = \"a\\\"b\"; let c = '\\n'; let f = 6.02e-23 + 3.14; x.. ..= ...\n}\n";
+        let comparison = compare(&scanner, input);
+        assert!(comparison.mismatches.is_empty(), "{:?}", comparison.mismatches);
+        assert_eq!(comparison.agreed, 50);
+        let kinds = |tokens: &[Located]| tokens.iter().map(|token| token.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(kinds(&generated(&scanner, "self Self")), vec!["SELFVALUE", "SELFTYPE"]);
+        assert_eq!(kinds(&generated(&scanner, "'static 'a")), vec!["StaticLifetime", "LifetimeOrLabel"]);
+
+        // Newlexer reads _x as _ followed by x
+        let comparison = compare(&scanner, "let _x = 1;");
+        assert_eq!(comparison.mismatches.len(), 1);
+        assert_eq!(kinds(&comparison.mismatches[0].generated), vec!["Identifier"]);
+        assert_eq!(kinds(&comparison.mismatches[0].hand_written), vec!["Underscore", "Identifier"]);
+
+        // and reads 'o of 'outer as an unterminated character literal
+        let comparison = compare(&scanner, "break 'outer;");
+        assert_eq!(kinds(&comparison.mismatches[0].generated), vec!["LifetimeOrLabel"]);
+        assert_eq!(kinds(&comparison.mismatches[0].hand_written), vec!["Error", "Identifier"]);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use crate::regex::Nfa;
+
+// 按字符类建表的 DFA。boundaries 是各字符类的起点（第一个是 0），字符 c 属于最后一个不大于它的起点所在的类；
+// next[状态][类] 为 None 表示没有这条边。初态总是 0
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    pub boundaries: Vec<u32>,
+    pub next: Vec<Vec<Option<usize>>>,
+    pub accepts: Vec<Option<usize>>,
+}
+
+impl Dfa {
+    pub fn class_of(&self, c: char) -> usize {
+        self.boundaries.partition_point(|&boundary| boundary <= c as u32) - 1
+    }
+}
+
+// 所有边上字符集合的端点把码位切成若干类，同一类中的字符在 NFA 里走同样的边
+fn char_classes(nfa: &Nfa) -> Vec<u32> {
+    let mut boundaries = BTreeSet::from([0]);
+    for (set, _) in nfa.edges.iter().flatten() {
+        for &(low, high) in set.iter().flat_map(|set| &set.ranges) {
+            boundaries.insert(low);
+            if high < char::MAX as u32 {
+                boundaries.insert(high + 1);
+            }
+        }
+    }
+    boundaries.into_iter().collect()
+}
+
+fn epsilon_closure(nfa: &Nfa, states: BTreeSet<usize>) -> BTreeSet<usize> {
+    let mut closure = states.clone();
+    let mut stack: Vec<usize> = states.into_iter().collect();
+    while let Some(state) = stack.pop() {
+        for (_, to) in nfa.edges[state].iter().filter(|(label, _)| label.is_none()) {
+            if closure.insert(*to) {
+                stack.push(*to);
+            }
+        }
+    }
+    closure
+}
+
+// 子集构造。一个状态集含有几条规则的接受状态时取最前面的规则，这就是规则的优先级
+pub fn determinise(nfa: &Nfa) -> Dfa {
+    let boundaries = char_classes(nfa);
+    let mut sets = vec![epsilon_closure(nfa, BTreeSet::from([nfa.start]))];
+    let mut index = BTreeMap::from([(sets[0].clone(), 0)]);
+    let mut next = Vec::new();
+    let mut queue = VecDeque::from([0]);
+    while let Some(current) = queue.pop_front() {
+        let mut row = vec![None; boundaries.len()];
+        for (class, &representative) in boundaries.iter().enumerate() {
+            let targets: BTreeSet<usize> = sets[current]
+                .iter()
+                .flat_map(|&state| &nfa.edges[state])
+                .filter(|(label, _)| label.as_ref().is_some_and(|set| set.contains(representative)))
+                .map(|(_, to)| *to)
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            let target = epsilon_closure(nfa, targets);
+            let state = *index.entry(target.clone()).or_insert_with(|| {
+                sets.push(target);
+                queue.push_back(sets.len() - 1);
+                sets.len() - 1
+            });
+            row[class] = Some(state);
+        }
+        next.push(row);
+    }
+
+    let accepts = sets.iter().map(|set| set.iter().filter_map(|state| nfa.accepts.get(state)).min().copied()).collect();
+    Dfa {
+        boundaries,
+        next,
+        accepts,
+    }
+}
+
+// Hopcroft 算法。缺少的边指向隐含的死状态；一开始按接受的规则分块（不接受的与死状态在一块），
+// 再用工作表中的块反复切分，每次只把较小的一半放回工作表。与死状态等价的状态最后删去，
+// 剩下的从初态开始按广度优先重新编号
+pub fn minimise(dfa: &Dfa) -> Dfa {
+    let dead = dfa.next.len();
+    let classes = dfa.boundaries.len();
+    let target = |state: usize, class: usize| if state == dead { dead } else { dfa.next[state][class].unwrap_or(dead) };
+
+    let mut by_accept: BTreeMap<Option<usize>, BTreeSet<usize>> = BTreeMap::new();
+    for state in 0..=dead {
+        let accept = if state == dead { None } else { dfa.accepts[state] };
+        by_accept.entry(accept).or_default().insert(state);
+    }
+    let mut partition: Vec<BTreeSet<usize>> = by_accept.into_values().collect();
+    let mut worklist = partition.clone();
+
+    // 每个状态在各个字符类上的前驱
+    let mut predecessors = vec![vec![Vec::new(); dead + 1]; classes];
+    for state in 0..=dead {
+        for (class, by_target) in predecessors.iter_mut().enumerate() {
+            by_target[target(state, class)].push(state);
+        }
+    }
+
+    while let Some(splitter) = worklist.pop() {
+        for by_target in &predecessors {
+            let into: BTreeSet<usize> = splitter.iter().flat_map(|&state| by_target[state].iter().copied()).collect();
+            if into.is_empty() {
+                continue;
+            }
+            let mut refined = Vec::new();
+            for block in partition {
+                let inside: BTreeSet<usize> = block.intersection(&into).copied().collect();
+                let outside: BTreeSet<usize> = block.difference(&into).copied().collect();
+                if inside.is_empty() || outside.is_empty() {
+                    refined.push(block);
+                    continue;
+                }
+                if let Some(position) = worklist.iter().position(|waiting| *waiting == block) {
+                    worklist.swap_remove(position);
+                    worklist.push(inside.clone());
+                    worklist.push(outside.clone());
+                } else if inside.len() <= outside.len() {
+                    worklist.push(inside.clone());
+                } else {
+                    worklist.push(outside.clone());
+                }
+                refined.push(inside);
+                refined.push(outside);
+            }
+            partition = refined;
+        }
+    }
+
+    let mut block_of = vec![0; dead + 1];
+    for (block, states) in partition.iter().enumerate() {
+        for &state in states {
+            block_of[state] = block;
+        }
+    }
+    let dead_block = block_of[dead];
+
+    let mut number = HashMap::from([(block_of[0], 0)]);
+    let mut order = vec![block_of[0]];
+    let mut next = Vec::new();
+    let mut position = 0;
+    while position < order.len() {
+        let representative = *partition[order[position]].iter().next().unwrap();
+        let mut row = vec![None; classes];
+        for (class, cell) in row.iter_mut().enumerate() {
+            let block = block_of[target(representative, class)];
+            if block == dead_block {
+                continue;
+            }
+            *cell = Some(*number.entry(block).or_insert_with(|| {
+                order.push(block);
+                order.len() - 1
+            }));
+        }
+        next.push(row);
+        position += 1;
+    }
+
+    let accepts = order
+        .iter()
+        .map(|&block| {
+            let representative = *partition[block].iter().next().unwrap();
+            if representative == dead {
+                None
+            } else {
+                dfa.accepts[representative]
+            }
+        })
+        .collect();
+    Dfa {
+        boundaries: dfa.boundaries.clone(),
+        next,
+        accepts,
+    }
+}
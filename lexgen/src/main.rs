@@ -0,0 +1,84 @@
+mod compare;
+mod dfa;
+mod regex;
+mod scanner;
+
+use std::io::Read;
+
+use compare::{compare, mismatch_to_string};
+use scanner::{build_scanner, emit_rust, read_spec, Scanner};
+
+// Rust 的记号，种类与 Newlexer 的 Token 一致
+const RUST_SPEC: &str = include_str!("../rust.lex");
+
+fn read_file(path: &str) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        std::process::exit(1);
+    })
+}
+
+fn read_input(path: Option<&String>) -> String {
+    match path {
+        Some(path) => read_file(path),
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).expect("Failed to read stdin");
+            input
+        }
+    }
+}
+
+fn main() {
+    // lexgen <scan|emit|compare> [--spec spec-file] [input-file]
+    // Without --spec the Rust token rules in rust.lex are used, without an input file stdin is read.
+    //   scan     print the tokens the generated scanner finds
+    //   emit     print the generated table-driven scanner as a Rust module
+    //   compare  run the generated scanner and Newlexer on the same input and report where they disagree
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let spec = match args.iter().position(|arg| arg == "--spec") {
+        Some(position) if position + 1 < args.len() => {
+            args.remove(position);
+            read_file(&args.remove(position))
+        }
+        Some(_) => {
+            eprintln!("--spec needs a file");
+            std::process::exit(1);
+        }
+        None => RUST_SPEC.to_string(),
+    };
+    let scanner: Scanner = read_spec(&spec).and_then(build_scanner).unwrap_or_else(|message| {
+        eprintln!("Invalid spec: {}", message);
+        std::process::exit(1);
+    });
+
+    match args.first().map(String::as_str) {
+        Some("scan") => {
+            for lexeme in scanner.scan(&read_input(args.get(1))) {
+                println!("{}:{} {} {:?}", lexeme.start.0, lexeme.start.1, lexeme.kind, lexeme.text);
+            }
+        }
+        Some("emit") => print!("{}", emit_rust(&scanner)),
+        Some("compare") => {
+            println!(
+                "{} rules, NFA with {} states, DFA with {}, minimal DFA with {}",
+                scanner.rules.len(),
+                scanner.nfa_states,
+                scanner.dfa_states,
+                scanner.dfa.next.len()
+            );
+            let comparison = compare(&scanner, &read_input(args.get(1)));
+            for mismatch in &comparison.mismatches {
+                print!("{}", mismatch_to_string(mismatch));
+            }
+            println!("{} tokens agree, {} mismatches", comparison.agreed, comparison.mismatches.len());
+            if !comparison.mismatches.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            eprintln!("Usage: lexgen <scan|emit|compare> [--spec spec-file] [input-file]");
+            std::process::exit(1);
+        }
+    }
+}
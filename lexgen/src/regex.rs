@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+const MAX_CHAR: u32 = char::MAX as u32;
+
+// 字符集合，由排好序、互不相交也不相邻的闭区间组成，端点是 Unicode 码位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharSet {
+    pub ranges: Vec<(u32, u32)>,
+}
+
+impl CharSet {
+    pub fn from_ranges(mut ranges: Vec<(u32, u32)>) -> CharSet {
+        ranges.sort();
+        let mut merged: Vec<(u32, u32)> = Vec::new();
+        for (low, high) in ranges {
+            match merged.last_mut() {
+                Some((_, last)) if low <= *last + 1 => *last = (*last).max(high),
+                _ => merged.push((low, high)),
+            }
+        }
+        CharSet { ranges: merged }
+    }
+
+    pub fn single(c: char) -> CharSet {
+        CharSet::from_ranges(vec![(c as u32, c as u32)])
+    }
+
+    pub fn negate(&self) -> CharSet {
+        let mut ranges = Vec::new();
+        let mut next = 0;
+        for &(low, high) in &self.ranges {
+            if low > next {
+                ranges.push((next, low - 1));
+            }
+            next = high + 1;
+        }
+        if next <= MAX_CHAR {
+            ranges.push((next, MAX_CHAR));
+        }
+        CharSet { ranges }
+    }
+
+    pub fn contains(&self, c: u32) -> bool {
+        self.ranges.iter().any(|&(low, high)| low <= c && c <= high)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Regex {
+    // 空串 ε
+    Empty,
+    Set(CharSet),
+    Concat(Vec<Regex>),
+    Alternation(Vec<Regex>),
+    Star(Box<Regex>),
+    Plus(Box<Regex>),
+    Optional(Box<Regex>),
+}
+
+// \d \w \s 与 . 只按 ASCII 理解
+fn shorthand(c: char) -> Option<CharSet> {
+    let set = match c {
+        'd' => CharSet::from_ranges(vec![('0' as u32, '9' as u32)]),
+        'w' => CharSet::from_ranges(vec![
+            ('0' as u32, '9' as u32),
+            ('A' as u32, 'Z' as u32),
+            ('_' as u32, '_' as u32),
+            ('a' as u32, 'z' as u32),
+        ]),
+        's' => CharSet::from_ranges(" \t\r\n\x0c".chars().map(|c| (c as u32, c as u32)).collect()),
+        _ => return None,
+    };
+    Some(set)
+}
+
+fn escaped_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        c => c,
+    }
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+        c
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}", message, self.position)
+    }
+
+    // alternation := concat ('|' concat)*
+    fn alternation(&mut self) -> Result<Regex, String> {
+        let mut alternatives = vec![self.concat()?];
+        while self.peek() == Some('|') {
+            self.position += 1;
+            alternatives.push(self.concat()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.pop().unwrap() } else { Regex::Alternation(alternatives) })
+    }
+
+    // concat := repeat*，遇到 | 、) 或结尾为止
+    fn concat(&mut self) -> Result<Regex, String> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            items.push(self.repeat()?);
+        }
+        Ok(match items.len() {
+            0 => Regex::Empty,
+            1 => items.pop().unwrap(),
+            _ => Regex::Concat(items),
+        })
+    }
+
+    // repeat := atom ('*' | '+' | '?')*
+    fn repeat(&mut self) -> Result<Regex, String> {
+        let mut regex = self.atom()?;
+        while let Some(c) = self.peek() {
+            regex = match c {
+                '*' => Regex::Star(Box::new(regex)),
+                '+' => Regex::Plus(Box::new(regex)),
+                '?' => Regex::Optional(Box::new(regex)),
+                _ => break,
+            };
+            self.position += 1;
+        }
+        Ok(regex)
+    }
+
+    fn atom(&mut self) -> Result<Regex, String> {
+        match self.next() {
+            Some('(') => {
+                let regex = self.alternation()?;
+                if self.next() != Some(')') {
+                    return Err(self.error("missing )"));
+                }
+                Ok(regex)
+            }
+            Some('[') => self.class(),
+            Some('.') => Ok(Regex::Set(CharSet::single('\n').negate())),
+            Some('\\') => {
+                let c = self.next().ok_or_else(|| self.error("trailing \\"))?;
+                Ok(Regex::Set(shorthand(c).unwrap_or_else(|| CharSet::single(escaped_char(c)))))
+            }
+            Some(c @ ('*' | '+' | '?')) => Err(self.error(&format!("nothing to repeat before {}", c))),
+            Some(c) => Ok(Regex::Set(CharSet::single(c))),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    // [abc]、[a-z_]、[^"\\]；类中的 \ 转义与类外相同
+    fn class(&mut self) -> Result<Regex, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next().ok_or_else(|| self.error("missing ]"))?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let low = if c == '\\' {
+                let c = self.next().ok_or_else(|| self.error("trailing \\"))?;
+                if let Some(set) = shorthand(c) {
+                    ranges.extend(set.ranges);
+                    continue;
+                }
+                escaped_char(c)
+            } else {
+                c
+            };
+            if self.peek() == Some('-') && self.chars.get(self.position + 1).is_some_and(|&c| c != ']') {
+                self.position += 1;
+                let mut high = self.next().unwrap();
+                if high == '\\' {
+                    high = escaped_char(self.next().ok_or_else(|| self.error("trailing \\"))?);
+                }
+                if high < low {
+                    return Err(self.error(&format!("empty range {}-{}", low, high)));
+                }
+                ranges.push((low as u32, high as u32));
+            } else {
+                ranges.push((low as u32, low as u32));
+            }
+        }
+        let set = CharSet::from_ranges(ranges);
+        Ok(Regex::Set(if negated { set.negate() } else { set }))
+    }
+}
+
+// 字面字符、.、[...] 字符类、\d \w \s 与 \n \t \r \0 转义，其余 \x 表示字符 x 本身；
+// ( ) 分组，| 选择，* + ? 重复
+pub fn parse_regex(pattern: &str) -> Result<Regex, String> {
+    let mut parser = RegexParser {
+        chars: pattern.chars().collect(),
+        position: 0,
+    };
+    let regex = parser.alternation()?;
+    if parser.position < parser.chars.len() {
+        return Err(parser.error("unmatched )"));
+    }
+    Ok(regex)
+}
+
+// ε-NFA，label 为 None 的边是 ε 边；accepts 记录每个接受状态属于哪条规则
+#[derive(Debug, Clone)]
+pub struct Nfa {
+    pub edges: Vec<Vec<(Option<CharSet>, usize)>>,
+    pub start: usize,
+    pub accepts: BTreeMap<usize, usize>,
+}
+
+impl Nfa {
+    fn add_state(&mut self) -> usize {
+        self.edges.push(Vec::new());
+        self.edges.len() - 1
+    }
+
+    fn add_edge(&mut self, from: usize, label: Option<CharSet>, to: usize) {
+        self.edges[from].push((label, to));
+    }
+
+    // Thompson 构造：每个子表达式是一个只有一个入口和一个出口的片段
+    fn fragment(&mut self, regex: &Regex) -> (usize, usize) {
+        let start = self.add_state();
+        let end = self.add_state();
+        match regex {
+            Regex::Empty => self.add_edge(start, None, end),
+            Regex::Set(set) => self.add_edge(start, Some(set.clone()), end),
+            Regex::Concat(items) => {
+                let mut current = start;
+                for item in items {
+                    let (item_start, item_end) = self.fragment(item);
+                    self.add_edge(current, None, item_start);
+                    current = item_end;
+                }
+                self.add_edge(current, None, end);
+            }
+            Regex::Alternation(alternatives) => {
+                for alternative in alternatives {
+                    let (alternative_start, alternative_end) = self.fragment(alternative);
+                    self.add_edge(start, None, alternative_start);
+                    self.add_edge(alternative_end, None, end);
+                }
+            }
+            Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) => {
+                let (inner_start, inner_end) = self.fragment(inner);
+                self.add_edge(start, None, inner_start);
+                self.add_edge(inner_end, None, end);
+                if !matches!(regex, Regex::Plus(_)) {
+                    self.add_edge(start, None, end);
+                }
+                if !matches!(regex, Regex::Optional(_)) {
+                    self.add_edge(inner_end, None, inner_start);
+                }
+            }
+        }
+        (start, end)
+    }
+}
+
+// 所有规则合成一个 NFA：新的初态经 ε 边进入每条规则的片段，第 i 条规则片段的出口接受规则 i
+pub fn rules_to_nfa(regexes: &[Regex]) -> Nfa {
+    let mut nfa = Nfa {
+        edges: Vec::new(),
+        start: 0,
+        accepts: BTreeMap::new(),
+    };
+    nfa.start = nfa.add_state();
+    for (rule, regex) in regexes.iter().enumerate() {
+        let (start, end) = nfa.fragment(regex);
+        nfa.add_edge(nfa.start, None, start);
+        nfa.accepts.insert(end, rule);
+    }
+    nfa
+}
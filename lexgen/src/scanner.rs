@@ -0,0 +1,259 @@
+use crate::dfa::{determinise, minimise, Dfa};
+use crate::regex::{parse_regex, rules_to_nfa};
+
+// 词法规则：记号种类与正则表达式，ignore 的规则匹配到的内容（空白等）不产生记号
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub kind: String,
+    pub pattern: String,
+    pub ignore: bool,
+}
+
+// 每行一条规则 `Kind regex`，正则表达式是种类名之后的整行（去掉两端空白）；`%ignore regex` 匹配到的内容
+// 被跳过；以 # 开头的行是注释。前面的规则优先
+pub fn read_spec(text: &str) -> Result<Vec<Rule>, String> {
+    let mut rules = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (kind, pattern) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("line {}: expected a token kind and a regular expression", number + 1))?;
+        rules.push(Rule {
+            kind: kind.to_string(),
+            pattern: pattern.trim().to_string(),
+            ignore: kind == "%ignore",
+        });
+    }
+    Ok(rules)
+}
+
+// 由规则生成的表驱动扫描器，同时记下各阶段自动机的状态数
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    pub rules: Vec<Rule>,
+    pub dfa: Dfa,
+    pub nfa_states: usize,
+    pub dfa_states: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme {
+    pub kind: String,
+    pub text: String,
+    // 起止的 (行, 列)，从 1 开始计数，终点不包含在内，与 newlexer::tokens::Span 相同
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+// 正则表达式 → Thompson NFA → 子集构造 → Hopcroft 最小化。能匹配空串的规则会让扫描器原地打转，作为错误报告
+pub fn build_scanner(rules: Vec<Rule>) -> Result<Scanner, String> {
+    let regexes = rules
+        .iter()
+        .map(|rule| parse_regex(&rule.pattern).map_err(|message| format!("{} {}: {}", rule.kind, rule.pattern, message)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let nfa = rules_to_nfa(&regexes);
+    let dfa = determinise(&nfa);
+    let minimal = minimise(&dfa);
+    if let Some(rule) = minimal.accepts[0] {
+        return Err(format!("{} {}: matches the empty string", rules[rule].kind, rules[rule].pattern));
+    }
+    Ok(Scanner {
+        rules,
+        nfa_states: nfa.edges.len(),
+        dfa_states: dfa.next.len(),
+        dfa: minimal,
+    })
+}
+
+impl Scanner {
+    // 从 input 开头起的最长匹配 (规则, 字节长度)，一样长时取前面的规则；一个字符也匹配不上时返回 None
+    pub fn longest_match(&self, input: &str) -> Option<(usize, usize)> {
+        let mut state = 0;
+        let mut last = None;
+        for (offset, c) in input.char_indices() {
+            match self.dfa.next[state][self.dfa.class_of(c)] {
+                Some(next) => state = next,
+                None => break,
+            }
+            if let Some(rule) = self.dfa.accepts[state] {
+                last = Some((rule, offset + c.len_utf8()));
+            }
+        }
+        last
+    }
+
+    // 整个输入的记号序列；匹配不上的字符单独成为一个 Unknown 记号，与手写词法分析器的 Token::Unknown 对应
+    pub fn scan(&self, input: &str) -> Vec<Lexeme> {
+        let mut lexemes = Vec::new();
+        let mut offset = 0;
+        let mut position = (1, 1);
+        while offset < input.len() {
+            let rest = &input[offset..];
+            let (kind, length) = match self.longest_match(rest) {
+                Some((rule, length)) => (Some(&self.rules[rule]), length),
+                None => (None, rest.chars().next().unwrap().len_utf8()),
+            };
+            let text = &rest[..length];
+            let start = position;
+            for c in text.chars() {
+                position = if c == '\n' { (position.0 + 1, 1) } else { (position.0, position.1 + 1) };
+            }
+            offset += length;
+            if kind.is_some_and(|rule| rule.ignore) {
+                continue;
+            }
+            lexemes.push(Lexeme {
+                kind: kind.map_or("Unknown".to_string(), |rule| rule.kind.clone()),
+                text: text.to_string(),
+                start,
+                end: position,
+            });
+        }
+        lexemes
+    }
+}
+
+const NONE: &str = "usize::MAX";
+
+fn rust_string(text: &str) -> String {
+    format!("{:?}", text)
+}
+
+// 独立的 Rust 模块：种类名、字符类、转移表与接受表，以及和 Scanner::longest_match、Scanner::scan 相同的驱动程序
+pub fn emit_rust(scanner: &Scanner) -> String {
+    let dfa = &scanner.dfa;
+    let kinds: Vec<String> = scanner.rules.iter().map(|rule| rust_string(&rule.kind)).collect();
+    let ignored: Vec<String> = scanner.rules.iter().map(|rule| rule.ignore.to_string()).collect();
+    let boundaries: Vec<String> = dfa.boundaries.iter().map(|boundary| format!("{:#x}", boundary)).collect();
+    let cell = |cell: &Option<usize>| cell.map_or(NONE.to_string(), |value| value.to_string());
+    let accepts: Vec<String> = dfa.accepts.iter().map(cell).collect();
+    let rows: Vec<String> = dfa
+        .next
+        .iter()
+        .map(|row| format!("    [{}],\n", row.iter().map(cell).collect::<Vec<String>>().join(", ")))
+        .collect();
+
+    let mut out = String::from("// Generated by lexgen: a table-driven scanner with longest-match and rule-priority semantics.\n");
+    out.push_str("// Rules, first one wins among matches of the same length:\n");
+    for rule in &scanner.rules {
+        out.push_str(&format!("//   {} {}\n", rule.kind, rule.pattern));
+    }
+    out.push_str(&format!("\npub const KINDS: [&str; {}] = [{}];\n", kinds.len(), kinds.join(", ")));
+    out.push_str(&format!("pub const IGNORED: [bool; {}] = [{}];\n", ignored.len(), ignored.join(", ")));
+    out.push_str(&format!("\nconst NONE: usize = {};\n", NONE));
+    out.push_str(&format!("const BOUNDARIES: [u32; {}] = [{}];\n", boundaries.len(), boundaries.join(", ")));
+    out.push_str(&format!("const ACCEPT: [usize; {}] = [{}];\n", accepts.len(), accepts.join(", ")));
+    out.push_str(&format!("const NEXT: [[usize; {}]; {}] = [\n{}];\n", boundaries.len(), rows.len(), rows.concat()));
+    out.push_str(
+        "
+// The longest match at the start of input as (index into KINDS, length in bytes), or None if not even one
+// character matches
+pub fn longest_match(input: &str) -> Option<(usize, usize)> {
+    let mut state = 0;
+    let mut last = None;
+    for (offset, c) in input.char_indices() {
+        let class = BOUNDARIES.partition_point(|&boundary| boundary <= c as u32) - 1;
+        state = NEXT[state][class];
+        if state == NONE {
+            break;
+        }
+        if ACCEPT[state] != NONE {
+            last = Some((ACCEPT[state], offset + c.len_utf8()));
+        }
+    }
+    last
+}
+
+// Every token of input as (kind, text); IGNORED matches are skipped and a character that starts no match
+// becomes an Unknown token on its own, as in lexgen's own scanner
+pub fn scan(input: &str) -> Vec<(&'static str, &str)> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    while offset < input.len() {
+        let rest = &input[offset..];
+        let (kind, length) = match longest_match(rest) {
+            Some((rule, length)) => (Some(rule), length),
+            None => (None, rest.chars().next().unwrap().len_utf8()),
+        };
+        offset += length;
+        match kind {
+            Some(rule) if IGNORED[rule] => {}
+            Some(rule) => tokens.push((KINDS[rule], &rest[..length])),
+            None => tokens.push((\"Unknown\", &rest[..length])),
+        }
+    }
+    tokens
+}
+",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner_for(spec: &str) -> Scanner {
+        build_scanner(read_spec(spec).unwrap()).unwrap()
+    }
+
+    fn kinds(scanner: &Scanner, input: &str) -> Vec<String> {
+        scanner.scan(input).into_iter().map(|lexeme| format!("{}:{}", lexeme.kind, lexeme.text)).collect()
+    }
+
+    #[test]
+    fn test_longest_match_and_priority() {
+        let scanner = scanner_for("%ignore [ \\n]+\nIf if\nIdentifier [a-z_][a-z0-9_]*\nNumber \\d+(\\.\\d+)?\nDot \\.\nDotDot \\.\\.\n");
+        assert_eq!(
+            kinds(&scanner, "if iffy 1.5 1..2 ?"),
+            vec!["If:if", "Identifier:iffy", "Number:1.5", "Number:1", "DotDot:..", "Number:2", "Unknown:?"]
+        );
+        let lexemes = scanner.scan("a\n  b");
+        assert_eq!((lexemes[1].start, lexemes[1].end), ((2, 3), (2, 4)));
+
+        // The textbook (a|b)*abb: 4 states once minimised
+        let abb = scanner_for("X (a|b)*abb");
+        assert!(abb.dfa_states > abb.dfa.next.len());
+        assert_eq!(abb.dfa.next.len(), 4);
+        assert_eq!(abb.longest_match("ababbab"), Some((0, 5)));
+        assert_eq!(abb.longest_match("abab"), None);
+
+        assert!(build_scanner(read_spec("Bad a*").unwrap()).unwrap_err().contains("matches the empty string"));
+        assert!(build_scanner(read_spec("Bad (a").unwrap()).is_err());
+        assert!(build_scanner(read_spec("Bad [b-a]").unwrap()).is_err());
+    }
+
+    // 把生成的模块和一个打印记号的 main 一起用 rustc 编译运行，结果应与 Scanner::scan 相同
+    #[test]
+    fn test_emitted_module_runs() {
+        let scanner = scanner_for("%ignore [ \\n]+\nIf if\nIdentifier [a-z_][a-z0-9_]*\nNumber \\d+(\\.\\d+)?\nDotDot \\.\\.\n");
+        let input = "if iffy 1.5 1..2 ?\nx";
+        let program = format!(
+            "mod scanner {{\n{}}}\n\nfn main() {{\n    for (kind, text) in scanner::scan({:?}) {{\n        println!(\"{{}}:{{}}\", kind, text);\n    }}\n}}\n",
+            emit_rust(&scanner),
+            input
+        );
+
+        let directory = std::env::temp_dir().join(format!("lexgen-emit-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let source = directory.join("main.rs");
+        let binary = directory.join("scanner");
+        std::fs::write(&source, program).unwrap();
+        let compiled = std::process::Command::new("rustc")
+            .args(["--edition", "2021", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .output()
+            .unwrap();
+        assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+        let output = std::process::Command::new(&binary).output().unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let printed: Vec<String> = String::from_utf8(output.stdout).unwrap().lines().map(String::from).collect();
+        assert_eq!(printed, kinds(&scanner, input));
+        assert_eq!(printed.last().map(String::as_str), Some("Identifier:x"));
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use crate::normal_form::{to_cnf, Cnf, Rule};
+use crate::{Grammar, Node};
+
+// CYK over the Chomsky normal form of a grammar: O(n³ · |rules|) for a sentence of n tokens however
+// ambiguous the grammar is, where `parse` enumerates the trees as it goes
+#[derive(Debug, Clone)]
+pub struct Cyk {
+    pub cnf: Cnf,
+    non_terminals: Vec<String>,
+    // Rules A -> B C as (rule, A, B, C) and A -> a as (rule, A), by index into cnf.rules and non_terminals
+    binary: Vec<(usize, usize, usize, usize)>,
+    terminal: HashMap<String, Vec<(usize, usize)>>,
+}
+
+// The triangular table: counts[length - 1][i][A] is the number of trees of A over tokens[i..i + length],
+// 0 where A does not derive that span. Counts stop growing at u128::MAX.
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub tokens: Vec<String>,
+    pub counts: Vec<Vec<Vec<u128>>>,
+}
+
+impl Table {
+    fn count(&self, non_terminal: usize, i: usize, j: usize) -> u128 {
+        self.counts[j - i - 1][i][non_terminal]
+    }
+}
+
+impl Cyk {
+    pub fn new(grammar: &Grammar, start: &str) -> Cyk {
+        let (_, cnf) = to_cnf(grammar, start);
+        let mut non_terminals: Vec<String> = Vec::new();
+        for rule in &cnf.rules {
+            if !non_terminals.contains(&rule.lhs) {
+                non_terminals.push(rule.lhs.clone());
+            }
+        }
+        let index: HashMap<&String, usize> = non_terminals.iter().enumerate().map(|(index, name)| (name, index)).collect();
+
+        let mut binary = Vec::new();
+        let mut terminal: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        for (number, rule) in cnf.rules.iter().enumerate() {
+            let lhs = index[&rule.lhs];
+            match rule.rhs.as_slice() {
+                [a] => terminal.entry(a.clone()).or_default().push((number, lhs)),
+                [b, c] => binary.push((number, lhs, index[b], index[c])),
+                _ => {}
+            }
+        }
+        Cyk {
+            cnf,
+            non_terminals,
+            binary,
+            terminal,
+        }
+    }
+
+    // Spans of length 1 from the rules A -> a, then every longer span from every split point
+    pub fn table(&self, tokens: &[&str]) -> Table {
+        let n = tokens.len();
+        let mut counts: Vec<Vec<Vec<u128>>> = Vec::with_capacity(n);
+        counts.push(
+            tokens
+                .iter()
+                .map(|token| {
+                    let mut cell = vec![0u128; self.non_terminals.len()];
+                    for &(_, lhs) in self.terminal.get(*token).into_iter().flatten() {
+                        cell[lhs] = cell[lhs].saturating_add(1);
+                    }
+                    cell
+                })
+                .collect(),
+        );
+        for length in 2..=n {
+            let mut row = Vec::with_capacity(n + 1 - length);
+            for i in 0..=n - length {
+                let mut cell = vec![0u128; self.non_terminals.len()];
+                for split in 1..length {
+                    let left = &counts[split - 1][i];
+                    let right = &counts[length - split - 1][i + split];
+                    for &(_, lhs, b, c) in &self.binary {
+                        if left[b] != 0 && right[c] != 0 {
+                            cell[lhs] = cell[lhs].saturating_add(left[b].saturating_mul(right[c]));
+                        }
+                    }
+                }
+                row.push(cell);
+            }
+            counts.push(row);
+        }
+        Table {
+            tokens: tokens.iter().map(|token| token.to_string()).collect(),
+            counts,
+        }
+    }
+
+    fn start(&self) -> Option<usize> {
+        self.non_terminals.iter().position(|name| *name == self.cnf.start)
+    }
+
+    fn empty_rule(&self) -> Option<&Rule> {
+        self.cnf.rules.iter().find(|rule| rule.lhs == self.cnf.start && rule.rhs.is_empty())
+    }
+
+    // Number of parse trees, the same number `parse` returns, without building any of them
+    pub fn count(&self, tokens: &[&str]) -> u128 {
+        let Some(start) = self.start() else {
+            return 0;
+        };
+        if tokens.is_empty() {
+            return self.empty_rule().map_or(0, |_| 1);
+        }
+        self.table(tokens).count(start, 0, tokens.len())
+    }
+
+    // Up to `limit` parse trees, in terms of the original grammar: the non-terminals the conversion added
+    // are left out and unit chains folded into one production are put back
+    pub fn trees(&self, tokens: &[&str], limit: usize) -> Vec<Node> {
+        let Some(start) = self.start() else {
+            return vec![];
+        };
+        if tokens.is_empty() {
+            return self.empty_rule().map(|rule| vec![original_node(rule, vec![])]).unwrap_or_default();
+        }
+        let table = self.table(tokens);
+        let mut memo = HashMap::new();
+        self.derivations(&table, &mut memo, start, 0, tokens.len(), limit).into_iter().flatten().collect()
+    }
+
+    // The ways `non_terminal` derives tokens[i..j], at most `limit` of them, each as the nodes it stands for in
+    // the original grammar: one node, or for a non-terminal the conversion added, the children it gives its
+    // parent. Only splits whose two halves have a non-zero count are followed, so every call finds something,
+    // and each (non-terminal, span) is worked out once: with a small limit this stays polynomial however many
+    // trees there are.
+    fn derivations(
+        &self,
+        table: &Table,
+        memo: &mut HashMap<(usize, usize, usize), Vec<Vec<Node>>>,
+        non_terminal: usize,
+        i: usize,
+        j: usize,
+        limit: usize,
+    ) -> Vec<Vec<Node>> {
+        let mut results = Vec::new();
+        if limit == 0 || table.count(non_terminal, i, j) == 0 {
+            return results;
+        }
+        if let Some(known) = memo.get(&(non_terminal, i, j)) {
+            return known.clone();
+        }
+        let add = |rule: &Rule, children: Vec<Node>, results: &mut Vec<Vec<Node>>| {
+            if rule.origin.is_empty() {
+                results.push(children);
+            } else {
+                results.push(vec![original_node(rule, children)]);
+            }
+        };
+        if j == i + 1 {
+            for &(number, lhs) in self.terminal.get(&table.tokens[i]).into_iter().flatten() {
+                if lhs == non_terminal && results.len() < limit {
+                    add(&self.cnf.rules[number], vec![Node::Leaf(table.tokens[i].clone())], &mut results);
+                }
+            }
+        } else {
+            'rules: for &(number, lhs, b, c) in &self.binary {
+                if lhs != non_terminal {
+                    continue;
+                }
+                for k in i + 1..j {
+                    if results.len() >= limit {
+                        break 'rules;
+                    }
+                    if table.count(b, i, k) == 0 || table.count(c, k, j) == 0 {
+                        continue;
+                    }
+                    let lefts = self.derivations(table, memo, b, i, k, limit);
+                    let rights = self.derivations(table, memo, c, k, j, limit);
+                    'pairs: for left in &lefts {
+                        for right in &rights {
+                            if results.len() >= limit {
+                                break 'pairs;
+                            }
+                            add(&self.cnf.rules[number], left.iter().chain(right).cloned().collect(), &mut results);
+                        }
+                    }
+                }
+            }
+        }
+        memo.insert((non_terminal, i, j), results.clone());
+        results
+    }
+
+    // The filled-in table as text, the way it is drawn by hand: the bottom row holds the single tokens and
+    // row `length` the spans of that many tokens, each cell listing the non-terminals with their tree counts
+    pub fn table_to_string(&self, table: &Table) -> String {
+        let cells: Vec<Vec<String>> = table
+            .counts
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        let names: Vec<String> = cell
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, count)| **count != 0)
+                            .map(|(index, count)| {
+                                if *count == 1 {
+                                    self.non_terminals[index].clone()
+                                } else {
+                                    format!("{}×{}", self.non_terminals[index], count)
+                                }
+                            })
+                            .collect();
+                        if names.is_empty() {
+                            "-".to_string()
+                        } else {
+                            names.join(",")
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let width = cells.iter().flatten().chain(&table.tokens).map(|cell| cell.chars().count()).max().unwrap_or(1);
+        let pad = |text: &str| format!("{}{}", text, " ".repeat(width - text.chars().count()));
+
+        let mut out = String::new();
+        for (length, row) in cells.iter().enumerate().rev() {
+            let line: Vec<String> = row.iter().map(|cell| pad(cell)).collect();
+            out.push_str(format!("{:>3} | {}", length + 1, line.join(" | ")).trim_end());
+            out.push('\n');
+        }
+        let tokens: Vec<String> = table.tokens.iter().map(|token| pad(token)).collect();
+        out.push_str(&format!("    | {}\n", tokens.join(" | ")));
+        out
+    }
+}
+
+// The node of the original grammar a converted rule stands for: its last link gets the children, the links
+// before it are the unit productions above it
+fn original_node(rule: &Rule, children: Vec<Node>) -> Node {
+    let (last, chain) = rule.origin.split_last().expect("a rule of the original grammar");
+    chain.iter().rev().fold(Node::Internal(last.lhs.clone(), children), |node, link| Node::Internal(link.lhs.clone(), vec![node]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grammar;
+    use crate::{parse, tree_to_string};
+
+    fn sorted_trees(trees: &[Node]) -> Vec<String> {
+        let mut trees: Vec<String> = trees.iter().map(|tree| tree_to_string("", tree)).collect();
+        trees.sort();
+        trees
+    }
+
+    #[test]
+    fn test_cyk_agrees_with_parse() {
+        let english = grammar(&[
+            ("S", &["NP VP"]),
+            ("NP", &["Det N", "NP PP"]),
+            ("VP", &["V NP", "VP PP"]),
+            ("PP", &["P NP"]),
+            ("Det", &["the", "a"]),
+            ("N", &["cat", "dog", "park", "telescope"]),
+            ("V", &["saw"]),
+            ("P", &["in", "with"]),
+        ]);
+        // ε, unit productions, a unit cycle once ε is removed, and terminals inside longer productions
+        let brackets = grammar(&[("S", &["S S", "( S )", "ε"])]);
+        let mixed = grammar(&[("E", &["E + T", "T"]), ("T", &["F", "- T"]), ("F", &["( E )", "x", "G"]), ("G", &["F", "y G z", "ε"])]);
+
+        let cases: [(&Grammar, &str, &str); 9] = [
+            (&english, "S", "the dog saw a cat in the park"),
+            (&english, "S", "the dog saw a cat in the park with a telescope"),
+            (&english, "S", "the dog saw"),
+            (&brackets, "S", ""),
+            (&brackets, "S", "( ) ( )"),
+            (&brackets, "S", "( ( ) ( ) )"),
+            (&mixed, "E", "x + - ( y z )"),
+            (&mixed, "E", "y y z z + x"),
+            (&mixed, "E", "+"),
+        ];
+        for (grammar, start, sentence) in cases {
+            let tokens: Vec<&str> = sentence.split_whitespace().collect();
+            let cyk = Cyk::new(grammar, start);
+            let expected = parse(grammar, start, &tokens);
+            assert_eq!(cyk.count(&tokens), expected.len() as u128, "{}", sentence);
+            assert_eq!(sorted_trees(&cyk.trees(&tokens, usize::MAX)), sorted_trees(&expected), "{}", sentence);
+        }
+
+        // V NP followed by k prepositional phrases has Catalan(k + 1) parses, far too many to enumerate
+        let cyk = Cyk::new(&english, "S");
+        let sentence = format!("the dog saw a cat{}", " in the park".repeat(15));
+        let tokens: Vec<&str> = sentence.split_whitespace().collect();
+        assert_eq!(cyk.count(&tokens), 35357670);
+        assert_eq!(cyk.trees(&tokens, 3).len(), 3);
+        assert!(cyk.table_to_string(&cyk.table(&["the", "dog"])).starts_with("  2 | NP"));
+    }
+
+    #[test]
+    fn test_first_tree_of_a_long_input() {
+        // 56 tokens of S -> S S | ( S ) | ε have astronomically many trees; the first one is found at once
+        let brackets = grammar(&[("S", &["S S", "( S )", "ε"])]);
+        let cyk = Cyk::new(&brackets, "S");
+        let sentence = "( ) ".repeat(28);
+        let tokens: Vec<&str> = sentence.split_whitespace().collect();
+        let started = std::time::Instant::now();
+        let trees = cyk.trees(&tokens, 1);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(trees.len(), 1);
+        fn leaves(node: &Node) -> usize {
+            match node {
+                Node::Leaf(_) => 1,
+                Node::Internal(_, children) => children.iter().map(leaves).sum(),
+            }
+        }
+        assert_eq!(leaves(&trees[0]), 56);
+        assert_eq!(cyk.trees(&tokens, 4).len(), 4);
+    }
+}
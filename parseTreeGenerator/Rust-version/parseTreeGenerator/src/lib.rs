@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+
+pub mod cyk;
+pub mod normal_form;
+
+pub type Grammar = HashMap<String, Vec<Vec<String>>>;
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Leaf(String),
+    Internal(String, Vec<Node>),
+}
+
+pub fn tree_to_string(indent: &str, tree: &Node) -> String {
+    match tree {
+        Node::Leaf(s) => format!("{}{}\n", indent, s),
+        Node::Internal(label, children) => {
+            let mut result = format!("{}{}\n", indent, label);
+            for child in children {
+                result.push_str(&tree_to_string(&format!("{indent}  "), child));
+            }
+            result
+        }
+    }
+}
+
+pub fn tree_to_typst(indent: &str, tree: &Node) -> String {
+    match tree {
+        Node::Leaf(s) => format!("{indent}tree(\"{}\")", s),
+        Node::Internal(label, children) => {
+            let new_indent = format!("{indent}  ");
+            let children_typst: Vec<String> = children.iter().map(|child| tree_to_typst(&new_indent, child)).collect();
+            let children_str = children_typst.join(",\n");
+            format!("{indent}tree(\"{}\",\n{}\n{indent})", label, children_str)
+        }
+    }
+}
+
+fn possible_splits(i: usize, j: usize, n: usize) -> Vec<Vec<usize>> {
+    if n == 1 {
+        if i < j {
+            vec![vec![]]
+        } else {
+            vec![]
+        }
+    } else {
+        let positions: Vec<usize> = (i + 1..j).collect();
+        fn combinations(acc: Vec<usize>, remaining_positions: &[usize], k: usize) -> Vec<Vec<usize>> {
+            if k == 0 {
+                vec![acc]
+            } else {
+                remaining_positions.iter().enumerate().flat_map(|(idx, &pos)| {
+                    let new_positions: Vec<usize> = remaining_positions.iter().enumerate()
+                        .filter_map(|(i, &p)| if i > idx { Some(p) } else { None })
+                        .collect();
+                    let mut new_acc = acc.clone();
+                    new_acc.push(pos);
+                    combinations(new_acc, &new_positions, k - 1)
+                }).collect()
+            }
+        }
+        combinations(vec![], &positions, n - 1)
+    }
+}
+
+fn lookup_rules(grammar: &Grammar, nt: &str) -> Vec<Vec<String>> {
+    grammar.get(nt).cloned().unwrap_or_else(Vec::new)
+}
+
+fn find_eps_nonterms(grammar: &Grammar) -> HashSet<String> {
+    grammar.iter().fold(HashSet::new(), |mut acc, (lhs, rhs_list)| {
+        if rhs_list.iter().any(|rhs| rhs == &vec!["ε".to_string()]) {
+            acc.insert(lhs.clone());
+        }
+        acc
+    })
+}
+
+fn update_eps_nonterms(eps_nonterms: &HashSet<String>, grammar: &Grammar) -> HashSet<String> {
+    let mut new_eps_nonterms = eps_nonterms.clone();
+    for (lhs, rhs_list) in grammar {
+        if rhs_list.iter().any(|rhs| rhs.iter().all(|sym| eps_nonterms.contains(sym))) {
+            new_eps_nonterms.insert(lhs.clone());
+        }
+    }
+    if new_eps_nonterms.len() > eps_nonterms.len() {
+        update_eps_nonterms(&new_eps_nonterms, grammar)
+    } else {
+        new_eps_nonterms
+    }
+}
+
+// Non-terminals that derive ε
+pub fn nullable_non_terminals(grammar: &Grammar) -> HashSet<String> {
+    update_eps_nonterms(&find_eps_nonterms(grammar), grammar)
+}
+
+pub fn remove_epsilons(grammar: &Grammar) -> (Grammar, Vec<(String, Vec<String>)>) {
+    fn remove_eps_from_rhs(rhs_list: &[String], eps_nonterms: &HashSet<String>) -> Vec<Vec<String>> {
+        match rhs_list.split_first() {
+            None => vec![vec![]],
+            Some((hd, tl)) => {
+                let rest = remove_eps_from_rhs(tl, eps_nonterms);
+                if eps_nonterms.contains(hd) {
+                    let mut new_rest = rest.clone();
+                    for r in &rest {
+                        let mut new_r = vec![hd.clone()];
+                        new_r.extend(r.clone());
+                        new_rest.push(new_r);
+                    }
+                    new_rest
+                } else {
+                    rest.into_iter().map(|r| {
+                        let mut new_r = vec![hd.clone()];
+                        new_r.extend(r);
+                        new_r
+                    }).collect()
+                }
+            }
+        }
+    }
+
+    let eps_nonterms = nullable_non_terminals(grammar);
+
+    let new_grammar = grammar.iter().fold(HashMap::new(), |mut acc, (lhs, rhs_list)| {
+        let new_rhs_list = rhs_list.iter().fold(vec![], |mut acc_rhs, rhs| {
+            if rhs == &vec!["ε".to_string()] {
+                acc_rhs
+            } else {
+                for new_rhs in remove_eps_from_rhs(rhs, &eps_nonterms) {
+                    // Dropping every symbol of an all-nullable right-hand side gives ε back, which is left out
+                    if !new_rhs.is_empty() && !acc_rhs.contains(&new_rhs) {
+                        acc_rhs.push(new_rhs);
+                    }
+                }
+                acc_rhs
+            }
+        });
+        acc.insert(lhs.clone(), new_rhs_list);
+        acc
+    });
+
+    fn generate_new_productions(grammar: &Grammar, eps_nonterms: &HashSet<String>, acc: Vec<(String, Vec<String>)>) -> Vec<(String, Vec<String>)> {
+        grammar.iter().fold(acc, |mut acc, (lhs, rhs_list)| {
+            for rhs in rhs_list {
+                if rhs.iter().any(|sym| eps_nonterms.contains(sym)) {
+                    let new_rhs: Vec<String> = rhs.iter().filter(|sym| !eps_nonterms.contains(*sym)).cloned().collect();
+                    if !new_rhs.is_empty() && !acc.contains(&(lhs.clone(), new_rhs.clone())) {
+                        acc.push((lhs.clone(), new_rhs));
+                    }
+                }
+            }
+            acc
+        })
+    }
+
+    let new_productions = generate_new_productions(&new_grammar, &eps_nonterms, vec![]);
+
+    (new_grammar, new_productions)
+}
+
+// Memo table and input of one run of `parse`, a memoised top-down parser; `parse_span` finds the trees
+// of one non-terminal over one span of the input by trying every split of it among a production's symbols
+struct Chart<'a> {
+    grammar: &'a Grammar,
+    tokens: &'a [&'a str],
+    non_terminals: HashSet<String>,
+    terminals: HashSet<String>,
+    memo: HashMap<(String, usize, usize), Vec<Node>>,
+    // Calls still in progress, with their depth; meeting one again means a cycle of unit productions
+    // such as S -> S, which ε removal creates from S -> S S | ε
+    active: HashMap<(String, usize, usize), usize>,
+    // Shallowest in-progress call the current call ran into; its result is then incomplete and not memoised
+    cut: usize,
+}
+
+// All parse trees of `tokens` from `start`. The grammar may contain ε productions, they are removed first,
+// so nullable non-terminals that derive ε do not show up in the trees; the empty input gives a single
+// childless `start` node when `start` is nullable
+pub fn parse(grammar: &Grammar, start: &str, tokens: &[&str]) -> Vec<Node> {
+    if tokens.is_empty() {
+        return if nullable_non_terminals(grammar).contains(start) {
+            vec![Node::Internal(start.to_owned(), vec![])]
+        } else {
+            vec![]
+        };
+    }
+
+    let (grammar, _) = remove_epsilons(grammar);
+
+    // Collect non-terminals and terminals
+    let non_terminals: HashSet<String> = grammar.keys().cloned().collect();
+    let terminals: HashSet<String> = grammar.values().flat_map(|prods| {
+        prods.iter().flat_map(|prod| {
+            prod.iter().filter(|sym| !non_terminals.contains(*sym)).cloned()
+        })
+    }).collect();
+
+    let mut chart = Chart {
+        grammar: &grammar,
+        tokens,
+        non_terminals,
+        terminals,
+        memo: HashMap::new(),
+        active: HashMap::new(),
+        cut: usize::MAX,
+    };
+    parse_span(&mut chart, start, 0, tokens.len())
+}
+
+// Trees of `nt` spanning tokens[i..j]
+fn parse_span(chart: &mut Chart, nt: &str, i: usize, j: usize) -> Vec<Node> {
+    let key = (nt.to_owned(), i, j);
+    if let Some(result) = chart.memo.get(&key) {
+        return result.clone();
+    }
+    if let Some(&depth) = chart.active.get(&key) {
+        // A tree through this cycle is never the only one, the call in progress finds the cycle-free ones
+        chart.cut = chart.cut.min(depth);
+        return vec![];
+    }
+    let depth = chart.active.len();
+    chart.active.insert(key.clone(), depth);
+    let outer_cut = std::mem::replace(&mut chart.cut, usize::MAX);
+
+    let mut results = Vec::new();
+    if i >= j {
+        // No results
+    } else if chart.terminals.contains(nt) {
+        if i + 1 == j && chart.tokens[i] == nt {
+            results.push(Node::Leaf(nt.to_owned()));
+        }
+    } else if chart.non_terminals.contains(nt) {
+        let rules = lookup_rules(chart.grammar, nt);
+        for production in rules {
+            let n = production.len();
+            if n == 1 {
+                let symbol = &production[0];
+                if chart.terminals.contains(symbol) {
+                    if i + 1 == j && chart.tokens[i] == symbol {
+                        results.push(Node::Internal(nt.to_owned(), vec![Node::Leaf(symbol.to_owned())]));
+                    }
+                } else {
+                    let sub_trees = parse_span(chart, symbol, i, j);
+                    for sub_tree in sub_trees {
+                        results.push(Node::Internal(nt.to_owned(), vec![sub_tree]));
+                    }
+                }
+            } else {
+                for splits in possible_splits(i, j, n) {
+                    let positions = std::iter::once(&i).chain(splits.iter()).chain(std::iter::once(&j)).cloned().collect::<Vec<_>>();
+                    let mut children = Vec::new();
+                    let mut failed = false;
+                    for (idx, ai) in production.iter().enumerate() {
+                        let start = positions[idx];
+                        let end = positions[idx + 1];
+                        let sub_trees = parse_span(chart, ai, start, end);
+                        if sub_trees.is_empty() {
+                            failed = true;
+                            break;
+                        }
+                        children.push(sub_trees);
+                    }
+                    if !failed {
+                        let combinations = children.iter().fold(vec![vec![]], |acc, sub_trees| {
+                            acc.into_iter().flat_map(|acc_subtree| {
+                                sub_trees.iter().map(move |t| {
+                                    let mut new_acc = acc_subtree.clone();
+                                    new_acc.push(t.clone());
+                                    new_acc
+                                })
+                            }).collect::<Vec<_>>()
+                        });
+                        for combination in combinations {
+                            results.push(Node::Internal(nt.to_owned(), combination));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    chart.active.remove(&key);
+    if chart.cut >= depth {
+        chart.memo.insert(key, results.clone());
+        chart.cut = outer_cut;
+    } else {
+        chart.cut = chart.cut.min(outer_cut);
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each alternative is a string of symbols separated by spaces
+    pub(crate) fn grammar(rules: &[(&str, &[&str])]) -> Grammar {
+        let mut grammar: Grammar = HashMap::new();
+        for (lhs, alternatives) in rules {
+            for alternative in *alternatives {
+                grammar.entry(lhs.to_string()).or_default().push(alternative.split_whitespace().map(String::from).collect());
+            }
+        }
+        grammar
+    }
+
+    #[test]
+    fn test_nullable_and_cyclic_grammar() {
+        // ε removal turns S -> S S | ε into S -> S S | S, a unit cycle
+        let g = grammar(&[("S", &["S S", "( S )", "ε"])]);
+
+        assert_eq!(parse(&g, "S", &[]).len(), 1);
+        assert_eq!(parse(&g, "S", &["(", ")"]).len(), 1);
+        assert_eq!(parse(&g, "S", &["(", ")", "(", ")"]).len(), 1);
+        assert_eq!(parse(&g, "S", &["(", "(", ")", "(", ")", ")"]).len(), 1);
+        assert!(parse(&g, "S", &["(", ")", ")"]).is_empty());
+    }
+
+    #[test]
+    fn test_remove_epsilons_leaves_no_empty_or_duplicate_alternatives() {
+        let g = grammar(&[("S", &["A B", "A"]), ("A", &["a", "ε"]), ("B", &["A", "b"])]);
+        assert_eq!(
+            nullable_non_terminals(&g),
+            ["S", "A", "B"].iter().map(|nt| nt.to_string()).collect::<HashSet<String>>()
+        );
+
+        let (without, _) = remove_epsilons(&g);
+        let alternatives = |nt: &str| -> Vec<String> { without[nt].iter().map(|rhs| rhs.join(" ")).collect() };
+        // S -> A B gives B, A and A B; the S -> A that follows is then already there
+        assert_eq!(alternatives("S"), vec!["B", "A", "A B"]);
+        assert_eq!(alternatives("A"), vec!["a"]);
+        assert_eq!(alternatives("B"), vec!["A", "b"]);
+    }
+
+    #[test]
+    fn test_ambiguous_sentence() {
+        // The demo grammar: the prepositional phrase attaches to the verb phrase or to the object
+        let g = grammar(&[
+            ("S", &["NP VP"]),
+            ("NP", &["Det N", "NP PP"]),
+            ("VP", &["V NP", "VP PP"]),
+            ("PP", &["P NP"]),
+            ("Det", &["the", "a"]),
+            ("N", &["cat", "dog", "park"]),
+            ("V", &["saw"]),
+            ("P", &["in"]),
+        ]);
+        let tokens: Vec<&str> = "the dog saw a cat in the park".split_whitespace().collect();
+        assert_eq!(parse(&g, "S", &tokens).len(), 2);
+        assert!(parse(&g, "S", &tokens[..7]).is_empty());
+    }
+
+    #[test]
+    fn test_unit_cycle_without_epsilons() {
+        // S -> A -> S is a cycle even before ε removal; only the cycle-free tree is reported
+        let g = grammar(&[("S", &["A", "a"]), ("A", &["S"])]);
+        let trees = parse(&g, "S", &["a"]);
+        assert_eq!(trees.len(), 1);
+        assert_eq!(tree_to_string("", &trees[0]), "S\n  a\n");
+        assert!(parse(&g, "S", &[]).is_empty());
+    }
+}
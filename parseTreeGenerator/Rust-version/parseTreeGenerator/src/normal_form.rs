@@ -0,0 +1,540 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ll1parser::generate::enumerate_sentences;
+
+use crate::cyk::Cyk;
+use crate::{nullable_non_terminals, Grammar};
+
+// One production of the original grammar as it survives in a transformed one: `kept` are the positions of
+// `rhs` still present, the others were nullable and dropped by ε removal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub lhs: String,
+    pub rhs: Vec<String>,
+    pub kept: Vec<usize>,
+}
+
+// A production of a transformed grammar. `origin` is the chain of original productions it stands for: unit
+// removal folds A -> B, B -> C, C -> x y into A -> x y, whose origin is those three links, each but the
+// last keeping exactly one symbol. Productions added by the conversion itself (the new start symbol, T_a -> a,
+// the pieces of a long right-hand side) have an empty origin, and their non-terminals are helpers that a
+// parse tree in terms of the original grammar leaves out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub lhs: String,
+    pub rhs: Vec<String>,
+    pub origin: Vec<Link>,
+}
+
+// The grammar after one step of the pipeline, for printing
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub name: &'static str,
+    pub start: String,
+    pub productions: Vec<(String, Vec<String>)>,
+}
+
+impl Step {
+    fn new(name: &'static str, start: &str, rules: &[Rule]) -> Step {
+        let mut productions = Vec::new();
+        for rule in rules {
+            let production = (rule.lhs.clone(), rule.rhs.clone());
+            if !productions.contains(&production) {
+                productions.push(production);
+            }
+        }
+        Step {
+            name,
+            start: start.to_string(),
+            productions,
+        }
+    }
+
+    // As a Grammar, with ε written out the way `parse` expects it
+    pub fn grammar(&self) -> Grammar {
+        let mut grammar: Grammar = HashMap::new();
+        for (lhs, rhs) in &self.productions {
+            let rhs = if rhs.is_empty() { vec!["ε".to_string()] } else { rhs.clone() };
+            grammar.entry(lhs.clone()).or_default().push(rhs);
+        }
+        grammar
+    }
+}
+
+// A grammar in Chomsky normal form: every rule is A -> B C or A -> a, plus start -> ε when the language
+// contains the empty sentence; the start symbol is on no right-hand side
+#[derive(Debug, Clone)]
+pub struct Cnf {
+    pub start: String,
+    pub rules: Vec<Rule>,
+}
+
+fn is_epsilon(symbol: &str) -> bool {
+    symbol == "ε"
+}
+
+// The productions of `grammar` with the start symbol first and the others in the order they are reached
+// from it, so that every step prints the same way every time
+fn ordered_rules(grammar: &Grammar, start: &str) -> Vec<Rule> {
+    let mut order = vec![start.to_string()];
+    let mut seen: HashSet<String> = HashSet::from([start.to_string()]);
+    let mut position = 0;
+    while position < order.len() {
+        for rhs in grammar.get(&order[position]).into_iter().flatten() {
+            for symbol in rhs {
+                if grammar.contains_key(symbol) && seen.insert(symbol.clone()) {
+                    order.push(symbol.clone());
+                }
+            }
+        }
+        position += 1;
+    }
+    let mut unreachable: Vec<&String> = grammar.keys().filter(|lhs| !seen.contains(*lhs)).collect();
+    unreachable.sort();
+    order.extend(unreachable.into_iter().cloned());
+
+    let mut rules = Vec::new();
+    for lhs in order {
+        for rhs in grammar.get(&lhs).into_iter().flatten() {
+            let rhs: Vec<String> = rhs.iter().filter(|symbol| !is_epsilon(symbol)).cloned().collect();
+            let link = Link {
+                lhs: lhs.clone(),
+                rhs: rhs.clone(),
+                kept: (0..rhs.len()).collect(),
+            };
+            push_rule(&mut rules, Rule { lhs: lhs.clone(), rhs, origin: vec![link] });
+        }
+    }
+    rules
+}
+
+// Two ways of getting the same right-hand side only give different parse trees when their chains go through
+// different non-terminals, which symbols of an original production were dropped does not show in a tree; the
+// first of the others wins, so the trees CYK rebuilds are the ones `parse` finds, each counted once
+fn push_rule(rules: &mut Vec<Rule>, rule: Rule) {
+    let chain = |rule: &Rule| rule.origin.iter().map(|link| link.lhs.clone()).collect::<Vec<String>>();
+    if !rules.iter().any(|other| other.lhs == rule.lhs && other.rhs == rule.rhs && chain(other) == chain(&rule)) {
+        rules.push(rule);
+    }
+}
+
+// `base`, or `base` with ' appended until no symbol of the grammar has that name
+fn fresh(taken: &mut HashSet<String>, base: &str) -> String {
+    let mut name = base.to_string();
+    while taken.contains(&name) {
+        name.push('\'');
+    }
+    taken.insert(name.clone());
+    name
+}
+
+fn symbols(rules: &[Rule]) -> HashSet<String> {
+    rules.iter().flat_map(|rule| std::iter::once(&rule.lhs).chain(&rule.rhs)).cloned().collect()
+}
+
+// A new start symbol S0 -> S, so that the start symbol is on no right-hand side and S0 -> ε can stay
+fn add_start(rules: &mut Vec<Rule>, start: &str, taken: &mut HashSet<String>) -> String {
+    let new_start = fresh(taken, &format!("{}0", start));
+    rules.insert(
+        0,
+        Rule {
+            lhs: new_start.clone(),
+            rhs: vec![start.to_string()],
+            origin: Vec::new(),
+        },
+    );
+    new_start
+}
+
+// Every way of leaving out nullable symbols, except leaving out all of them; ε productions go, apart from
+// start -> ε when the start symbol is nullable
+fn remove_epsilon_rules(rules: &[Rule], start: &str, nullable: &HashSet<String>, empty_origin: Vec<Link>) -> Vec<Rule> {
+    let mut result = Vec::new();
+    for rule in rules {
+        let optional: Vec<usize> = (0..rule.rhs.len()).filter(|&position| nullable.contains(&rule.rhs[position])).collect();
+        for mask in 0..1usize << optional.len() {
+            let dropped: Vec<usize> = (0..optional.len()).filter(|bit| mask & (1 << bit) != 0).map(|bit| optional[bit]).collect();
+            let kept: Vec<usize> = (0..rule.rhs.len()).filter(|position| !dropped.contains(position)).collect();
+            if kept.is_empty() {
+                continue;
+            }
+            let mut origin = rule.origin.clone();
+            if let Some(link) = origin.last_mut() {
+                link.kept = kept.iter().map(|&position| link.kept[position]).collect();
+            }
+            let rhs = kept.iter().map(|&position| rule.rhs[position].clone()).collect();
+            push_rule(&mut result, Rule { lhs: rule.lhs.clone(), rhs, origin });
+        }
+    }
+    if nullable.contains(start) {
+        push_rule(&mut result, Rule { lhs: start.to_string(), rhs: Vec::new(), origin: empty_origin });
+    }
+    result
+}
+
+fn is_unit(rule: &Rule, non_terminals: &HashSet<String>) -> bool {
+    rule.rhs.len() == 1 && non_terminals.contains(&rule.rhs[0])
+}
+
+// A gets the non-unit productions of every B with A =>+ B through unit productions, the chain of unit
+// productions going into the origin. Every chain that does not go through a non-terminal twice is a
+// different parse tree, as in `parse`, which cuts unit cycles
+fn remove_unit_rules(rules: &[Rule], non_terminals: &HashSet<String>) -> Vec<Rule> {
+    fn follow(
+        rules: &[Rule],
+        non_terminals: &HashSet<String>,
+        lhs: &str,
+        current: &str,
+        chain: &[Link],
+        visited: &mut Vec<String>,
+        result: &mut Vec<Rule>,
+    ) {
+        for rule in rules.iter().filter(|rule| rule.lhs == current) {
+            let origin: Vec<Link> = chain.iter().chain(&rule.origin).cloned().collect();
+            if !is_unit(rule, non_terminals) {
+                push_rule(result, Rule { lhs: lhs.to_string(), rhs: rule.rhs.clone(), origin });
+            } else if !visited.contains(&rule.rhs[0]) {
+                visited.push(rule.rhs[0].clone());
+                follow(rules, non_terminals, lhs, &rule.rhs[0], &origin, visited, result);
+                visited.pop();
+            }
+        }
+    }
+
+    let mut lhs_order: Vec<&String> = Vec::new();
+    for rule in rules {
+        if !lhs_order.contains(&&rule.lhs) {
+            lhs_order.push(&rule.lhs);
+        }
+    }
+
+    let mut result = Vec::new();
+    for lhs in lhs_order {
+        follow(rules, non_terminals, lhs, lhs, &[], &mut vec![lhs.clone()], &mut result);
+    }
+    result
+}
+
+// Productive non-terminals first, then those reachable from the start symbol through the remaining rules;
+// the other order can leave useless symbols behind
+fn remove_useless_rules(rules: &[Rule], start: &str, non_terminals: &HashSet<String>) -> Vec<Rule> {
+    let mut productive: HashSet<&String> = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for rule in rules {
+            if !productive.contains(&rule.lhs)
+                && rule.rhs.iter().all(|symbol| !non_terminals.contains(symbol) || productive.contains(symbol))
+            {
+                productive.insert(&rule.lhs);
+                changed = true;
+            }
+        }
+    }
+    let generating: Vec<&Rule> = rules
+        .iter()
+        .filter(|rule| productive.contains(&rule.lhs) && rule.rhs.iter().all(|symbol| !non_terminals.contains(symbol) || productive.contains(symbol)))
+        .collect();
+
+    let mut reachable: HashSet<&str> = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        for rule in generating.iter().filter(|rule| rule.lhs == current) {
+            for symbol in &rule.rhs {
+                if reachable.insert(symbol) {
+                    queue.push_back(symbol);
+                }
+            }
+        }
+    }
+    generating.into_iter().filter(|rule| reachable.contains(rule.lhs.as_str())).cloned().collect()
+}
+
+// Terminals in right-hand sides of two or more symbols become T_a -> a, and A -> X1 X2 ... Xn becomes
+// A -> X1 A_1, A_1 -> X2 A_2, ..., A_(n-2) -> X(n-1) Xn
+fn to_chomsky(rules: &[Rule], non_terminals: &HashSet<String>, taken: &mut HashSet<String>) -> Vec<Rule> {
+    let mut result = Vec::new();
+    let mut wrapped: HashMap<String, String> = HashMap::new();
+    let mut helper_rules = Vec::new();
+    for rule in rules {
+        if rule.rhs.len() < 2 {
+            push_rule(&mut result, rule.clone());
+            continue;
+        }
+        let rhs: Vec<String> = rule
+            .rhs
+            .iter()
+            .map(|symbol| {
+                if non_terminals.contains(symbol) {
+                    return symbol.clone();
+                }
+                wrapped
+                    .entry(symbol.clone())
+                    .or_insert_with(|| {
+                        let name = fresh(taken, &format!("T_{}", symbol));
+                        helper_rules.push(Rule { lhs: name.clone(), rhs: vec![symbol.clone()], origin: Vec::new() });
+                        name
+                    })
+                    .clone()
+            })
+            .collect();
+
+        let mut lhs = rule.lhs.clone();
+        let mut origin = rule.origin.clone();
+        for (index, symbol) in rhs[..rhs.len() - 2].iter().enumerate() {
+            let next = fresh(taken, &format!("{}_{}", rule.lhs, index + 1));
+            push_rule(&mut result, Rule { lhs, rhs: vec![symbol.clone(), next.clone()], origin });
+            lhs = next;
+            origin = Vec::new();
+        }
+        push_rule(&mut result, Rule { lhs, rhs: rhs[rhs.len() - 2..].to_vec(), origin });
+    }
+    for rule in helper_rules {
+        push_rule(&mut result, rule);
+    }
+    result
+}
+
+// The pipeline up to Chomsky normal form, with the grammar after every step
+pub fn to_cnf(grammar: &Grammar, start: &str) -> (Vec<Step>, Cnf) {
+    let non_terminals_before: HashSet<String> = grammar.keys().cloned().collect();
+    let mut rules = ordered_rules(grammar, start);
+    let mut taken = symbols(&rules);
+    taken.insert(start.to_string());
+    let mut steps = vec![Step::new("original", start, &rules)];
+
+    let new_start = add_start(&mut rules, start, &mut taken);
+    let mut non_terminals = non_terminals_before;
+    non_terminals.insert(new_start.clone());
+    steps.push(Step::new("new start symbol", &new_start, &rules));
+
+    let nullable = nullable_non_terminals(&Step::new("", &new_start, &rules).grammar());
+    // The empty sentence stands for the childless start node `parse` gives it
+    let empty = Link {
+        lhs: start.to_string(),
+        rhs: Vec::new(),
+        kept: Vec::new(),
+    };
+    let rules = remove_epsilon_rules(&rules, &new_start, &nullable, vec![empty]);
+    steps.push(Step::new("ε removal", &new_start, &rules));
+
+    let rules = remove_unit_rules(&rules, &non_terminals);
+    steps.push(Step::new("unit removal", &new_start, &rules));
+
+    let rules = remove_useless_rules(&rules, &new_start, &non_terminals);
+    steps.push(Step::new("useless symbol removal", &new_start, &rules));
+
+    let rules = to_chomsky(&rules, &non_terminals, &mut taken);
+    steps.push(Step::new("Chomsky normal form", &new_start, &rules));
+
+    (steps, Cnf { start: new_start, rules })
+}
+
+// Greibach normal form from Chomsky normal form: every production is A -> a B1 ... Bk, plus start -> ε.
+// With the non-terminals ordered A1 ... An, first make every Ai -> Aj γ have j > i by substituting the
+// productions of the lower Aj and replacing immediate left recursion Ai -> Ai α by a new Bi -> α | α Bi;
+// then An down to A1 all start with a terminal and can be substituted into the ones before, and into the Bi
+pub fn to_gnf(cnf: &Cnf) -> Step {
+    let mut taken = symbols(&cnf.rules);
+    let mut order: Vec<String> = Vec::new();
+    for rule in &cnf.rules {
+        if !order.contains(&rule.lhs) {
+            order.push(rule.lhs.clone());
+        }
+    }
+    let mut productions: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+    let mut epsilon = false;
+    for rule in &cnf.rules {
+        if rule.rhs.is_empty() {
+            epsilon = true;
+        } else {
+            let alternatives = productions.entry(rule.lhs.clone()).or_default();
+            if !alternatives.contains(&rule.rhs) {
+                alternatives.push(rule.rhs.clone());
+            }
+        }
+    }
+
+    let substitute = |rhs: &[String], replacements: &[Vec<String>]| -> Vec<Vec<String>> {
+        replacements.iter().map(|replacement| replacement.iter().chain(&rhs[1..]).cloned().collect()).collect()
+    };
+    let mut recursive_helpers: Vec<String> = Vec::new();
+
+    for (index, name) in order.iter().enumerate() {
+        for lower in &order[..index] {
+            let lower_productions = productions.get(lower).cloned().unwrap_or_default();
+            let current = productions.remove(name).unwrap_or_default();
+            let mut updated = Vec::new();
+            for rhs in current {
+                if rhs[0] == *lower {
+                    for new_rhs in substitute(&rhs, &lower_productions) {
+                        if !updated.contains(&new_rhs) {
+                            updated.push(new_rhs);
+                        }
+                    }
+                } else if !updated.contains(&rhs) {
+                    updated.push(rhs);
+                }
+            }
+            productions.insert(name.clone(), updated);
+        }
+
+        let current = productions.remove(name).unwrap_or_default();
+        let (recursive, others): (Vec<Vec<String>>, Vec<Vec<String>>) = current.into_iter().partition(|rhs| rhs[0] == *name);
+        if recursive.is_empty() {
+            productions.insert(name.clone(), others);
+            continue;
+        }
+        let helper = fresh(&mut taken, &format!("{}_R", name));
+        let mut updated = others.clone();
+        updated.extend(others.iter().map(|rhs| rhs.iter().cloned().chain(std::iter::once(helper.clone())).collect()));
+        productions.insert(name.clone(), updated);
+        let tails: Vec<Vec<String>> = recursive.into_iter().map(|rhs| rhs[1..].to_vec()).collect();
+        let mut helper_productions = tails.clone();
+        helper_productions.extend(tails.iter().map(|tail| tail.iter().cloned().chain(std::iter::once(helper.clone())).collect()));
+        productions.insert(helper.clone(), helper_productions);
+        recursive_helpers.push(helper);
+    }
+
+    // Every production of An starts with a terminal now; going down, those of Ai start with a terminal or
+    // some Aj with j > i, which is already done
+    let starts_with_non_terminal = |rhs: &Vec<String>, productions: &HashMap<String, Vec<Vec<String>>>| productions.contains_key(&rhs[0]);
+    let finish = |name: &String, productions: &mut HashMap<String, Vec<Vec<String>>>| {
+        let current = productions.remove(name).unwrap_or_default();
+        let mut updated = Vec::new();
+        for rhs in current {
+            let replaced = if starts_with_non_terminal(&rhs, productions) {
+                substitute(&rhs, &productions[&rhs[0]])
+            } else {
+                vec![rhs]
+            };
+            for new_rhs in replaced {
+                if !updated.contains(&new_rhs) {
+                    updated.push(new_rhs);
+                }
+            }
+        }
+        productions.insert(name.clone(), updated);
+    };
+    for name in order.iter().rev() {
+        finish(name, &mut productions);
+    }
+    for helper in &recursive_helpers {
+        finish(helper, &mut productions);
+    }
+
+    let mut rules = Vec::new();
+    for name in order.iter().chain(&recursive_helpers) {
+        if *name == cnf.start && epsilon {
+            rules.push(Rule { lhs: name.clone(), rhs: Vec::new(), origin: Vec::new() });
+        }
+        for rhs in productions.get(name).into_iter().flatten() {
+            rules.push(Rule { lhs: name.clone(), rhs: rhs.clone(), origin: Vec::new() });
+        }
+    }
+    // Substitution leaves non-terminals that nothing refers to any more
+    let non_terminals: HashSet<String> = rules.iter().map(|rule| rule.lhs.clone()).collect();
+    let rules = remove_useless_rules(&rules, &cnf.start, &non_terminals);
+    Step::new("Greibach normal form", &cnf.start, &rules)
+}
+
+// Every step from the original grammar to Chomsky and then Greibach normal form
+pub fn normalise(grammar: &Grammar, start: &str) -> Vec<Step> {
+    let (mut steps, cnf) = to_cnf(grammar, start);
+    steps.push(to_gnf(&cnf));
+    steps
+}
+
+// One line per non-terminal, `A -> x y | z`, in the order the non-terminals first appear
+pub fn step_to_string(step: &Step) -> String {
+    let mut order: Vec<&String> = Vec::new();
+    for (lhs, _) in &step.productions {
+        if !order.contains(&lhs) {
+            order.push(lhs);
+        }
+    }
+    let mut out = format!("{} (start symbol {}):\n", step.name, step.start);
+    for lhs in order {
+        let alternatives: Vec<String> = step
+            .productions
+            .iter()
+            .filter(|(other, _)| other == lhs)
+            .map(|(_, rhs)| if rhs.is_empty() { "ε".to_string() } else { rhs.join(" ") })
+            .collect();
+        out.push_str(&format!("  {} -> {}\n", lhs, alternatives.join(" | ")));
+    }
+    out
+}
+
+// Spot check that a step did not change the language: sentences generated from either grammar have to be
+// accepted by the other one, which CYK decides for each of them. Returns the sentences where they differ,
+// with the grammar that accepts them.
+pub fn spot_check(grammar: &Grammar, start: &str, step: &Step, max_length: usize, count: usize) -> Vec<(Vec<String>, &'static str)> {
+    let transformed = step.grammar();
+    let accepts = |cyk: &Cyk, sentence: &[String]| {
+        let tokens: Vec<&str> = sentence.iter().map(String::as_str).collect();
+        cyk.count(&tokens) > 0
+    };
+    let (original_cyk, transformed_cyk) = (Cyk::new(grammar, start), Cyk::new(&transformed, &step.start));
+    let mut differences = Vec::new();
+    for sentence in enumerate_sentences(grammar, start, max_length, count) {
+        if !accepts(&transformed_cyk, &sentence) {
+            differences.push((sentence, "original"));
+        }
+    }
+    for sentence in enumerate_sentences(&transformed, &step.start, max_length, count) {
+        if !accepts(&original_cyk, &sentence) {
+            differences.push((sentence, step.name));
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grammar;
+
+    #[test]
+    fn test_normal_forms() {
+        // Textbook example with ε, a unit cycle through S, an unproductive C and left recursion
+        let original = grammar(&[("S", &["A S A", "a B", "C"]), ("A", &["B", "S"]), ("B", &["b", "ε"]), ("C", &["c C"])]);
+        let steps = normalise(&original, "S");
+        let names: Vec<&str> = steps.iter().map(|step| step.name).collect();
+        assert_eq!(
+            names,
+            vec!["original", "new start symbol", "ε removal", "unit removal", "useless symbol removal", "Chomsky normal form", "Greibach normal form"]
+        );
+
+        let cnf = &steps[5];
+        assert!(cnf.productions.iter().all(|(lhs, rhs)| {
+            let is_non_terminal = |symbol: &String| cnf.productions.iter().any(|(other, _)| other == symbol);
+            match rhs.as_slice() {
+                [] => *lhs == cnf.start,
+                [a] => !is_non_terminal(a),
+                [b, c] => is_non_terminal(b) && is_non_terminal(c) && *b != cnf.start && *c != cnf.start,
+                _ => false,
+            }
+        }));
+        assert!(cnf.productions.iter().all(|(lhs, rhs)| lhs != "C" && !rhs.contains(&"C".to_string())));
+
+        let gnf = &steps[6];
+        assert!(gnf.productions.iter().all(|(lhs, rhs)| {
+            let is_non_terminal = |symbol: &String| gnf.productions.iter().any(|(other, _)| other == symbol);
+            match rhs.split_first() {
+                None => *lhs == gnf.start,
+                Some((first, rest)) => !is_non_terminal(first) && rest.iter().all(is_non_terminal),
+            }
+        }));
+
+        for step in &steps[1..] {
+            assert!(spot_check(&original, "S", step, 4, 30).is_empty(), "{}", step_to_string(step));
+        }
+        assert!(enumerate_sentences(&original, "S", 2, 10).contains(&vec!["a".to_string()]));
+
+        // A left-recursive expression grammar only has to become right-recursive
+        let expression = grammar(&[("E", &["E + T", "T"]), ("T", &["T * F", "F"]), ("F", &["( E )", "x"])]);
+        let gnf = normalise(&expression, "E").pop().unwrap();
+        assert!(spot_check(&expression, "E", &gnf, 5, 40).is_empty());
+    }
+}